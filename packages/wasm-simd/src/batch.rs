@@ -0,0 +1,368 @@
+use wasm_bindgen::prelude::*;
+
+// 一个推理批次：int64 小端字节，可直接包装为 ort.Tensor('int64', new BigInt64Array(bytes.buffer), [batch_size, sequence_length])
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct TensorBatch {
+    batch_size: usize,
+    sequence_length: usize,
+    input_ids: Vec<u8>,
+    attention_mask: Vec<u8>,
+    token_type_ids: Vec<u8>,
+    indices: Vec<u32>,
+    lengths: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl TensorBatch {
+    #[wasm_bindgen(getter)]
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sequence_length(&self) -> usize {
+        self.sequence_length
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn input_ids(&self) -> Vec<u8> {
+        self.input_ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attention_mask(&self) -> Vec<u8> {
+        self.attention_mask.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn token_type_ids(&self) -> Vec<u8> {
+        self.token_type_ids.clone()
+    }
+
+    // 每一行对应的输入序列下标
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+
+    // 每一行补齐前的长度
+    #[wasm_bindgen(getter)]
+    pub fn lengths(&self) -> Vec<u32> {
+        self.lengths.clone()
+    }
+}
+
+// 把变长 token 序列打包成补齐后的 int64 批次，按长度分桶以减少补齐浪费
+#[wasm_bindgen]
+pub struct BatchBuilder {
+    max_batch_size: usize,
+    // 单个批次 batch_size * sequence_length 的上限，0 表示不限制
+    max_tokens: usize,
+    pad_id: u32,
+    pad_to_multiple_of: usize,
+    bucketing: bool,
+}
+
+impl Default for BatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl BatchBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> BatchBuilder {
+        BatchBuilder {
+            max_batch_size: 32,
+            max_tokens: 0,
+            pad_id: 0,
+            pad_to_multiple_of: 0,
+            bucketing: true,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    pub fn set_max_batch_size(&mut self, max_batch_size: usize) {
+        self.max_batch_size = max_batch_size.max(1);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens
+    }
+
+    pub fn set_max_tokens(&mut self, max_tokens: usize) {
+        self.max_tokens = max_tokens;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pad_id(&self) -> u32 {
+        self.pad_id
+    }
+
+    pub fn set_pad_id(&mut self, pad_id: u32) {
+        self.pad_id = pad_id;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn pad_to_multiple_of(&self) -> usize {
+        self.pad_to_multiple_of
+    }
+
+    pub fn set_pad_to_multiple_of(&mut self, pad_to_multiple_of: usize) {
+        self.pad_to_multiple_of = pad_to_multiple_of;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bucketing(&self) -> bool {
+        self.bucketing
+    }
+
+    // 关闭后按输入顺序切分批次
+    pub fn set_bucketing(&mut self, bucketing: bool) {
+        self.bucketing = bucketing;
+    }
+
+    // ids 为所有序列首尾相接，lengths 为各序列长度；type_ids 为空时全部填 0。
+    // 形状不一致时返回空列表
+    pub fn build(&self, ids: &[u32], lengths: &[u32], type_ids: &[u32]) -> Vec<TensorBatch> {
+        let total: usize = lengths.iter().map(|&l| l as usize).sum();
+        if total != ids.len() || (!type_ids.is_empty() && type_ids.len() != ids.len()) {
+            return Vec::new();
+        }
+
+        let mut offsets = Vec::with_capacity(lengths.len());
+        let mut offset = 0;
+        for &length in lengths {
+            offsets.push(offset);
+            offset += length as usize;
+        }
+
+        let mut order: Vec<usize> = (0..lengths.len()).collect();
+        if self.bucketing {
+            order.sort_by_key(|&i| lengths[i]);
+        }
+
+        let mut batches = Vec::new();
+        let mut start = 0;
+        while start < order.len() {
+            let mut end = start + 1;
+            let mut longest = lengths[order[start]] as usize;
+            while end < order.len() && end - start < self.max_batch_size {
+                let candidate = longest.max(lengths[order[end]] as usize);
+                if self.max_tokens > 0
+                    && (end - start + 1) * self.padded_length(candidate) > self.max_tokens
+                {
+                    break;
+                }
+                longest = candidate;
+                end += 1;
+            }
+            batches.push(self.pack(
+                &order[start..end],
+                ids,
+                type_ids,
+                lengths,
+                &offsets,
+                longest,
+            ));
+            start = end;
+        }
+        batches
+    }
+}
+
+impl BatchBuilder {
+    fn padded_length(&self, length: usize) -> usize {
+        let multiple_of = self.pad_to_multiple_of;
+        if multiple_of > 0 && !length.is_multiple_of(multiple_of) {
+            length + multiple_of - length % multiple_of
+        } else {
+            length
+        }
+    }
+
+    fn pack(
+        &self,
+        rows: &[usize],
+        ids: &[u32],
+        type_ids: &[u32],
+        lengths: &[u32],
+        offsets: &[usize],
+        longest: usize,
+    ) -> TensorBatch {
+        let sequence_length = self.padded_length(longest);
+        let bytes = rows.len() * sequence_length * 8;
+        let mut batch = TensorBatch {
+            batch_size: rows.len(),
+            sequence_length,
+            input_ids: Vec::with_capacity(bytes),
+            attention_mask: Vec::with_capacity(bytes),
+            token_type_ids: Vec::with_capacity(bytes),
+            indices: rows.iter().map(|&i| i as u32).collect(),
+            lengths: rows.iter().map(|&i| lengths[i]).collect(),
+        };
+
+        let one = 1i64.to_le_bytes();
+        let zero = 0i64.to_le_bytes();
+        let pad = (self.pad_id as i64).to_le_bytes();
+        for &row in rows {
+            let range = offsets[row]..offsets[row] + lengths[row] as usize;
+            for &id in &ids[range.clone()] {
+                batch
+                    .input_ids
+                    .extend_from_slice(&(id as i64).to_le_bytes());
+                batch.attention_mask.extend_from_slice(&one);
+            }
+            if type_ids.is_empty() {
+                for _ in range.clone() {
+                    batch.token_type_ids.extend_from_slice(&zero);
+                }
+            } else {
+                for &type_id in &type_ids[range.clone()] {
+                    batch
+                        .token_type_ids
+                        .extend_from_slice(&(type_id as i64).to_le_bytes());
+                }
+            }
+            for _ in range.len()..sequence_length {
+                batch.input_ids.extend_from_slice(&pad);
+                batch.attention_mask.extend_from_slice(&zero);
+                batch.token_type_ids.extend_from_slice(&zero);
+            }
+        }
+        batch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int64s(bytes: &[u8]) -> Vec<i64> {
+        assert_eq!(bytes.len() % 8, 0);
+        bytes
+            .chunks_exact(8)
+            .map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    // 6 条序列，长度依次为 5、1、3、2、4、1，token id 为 序列号 * 10 + 位置
+    fn sequences() -> (Vec<u32>, Vec<u32>) {
+        let lengths = vec![5, 1, 3, 2, 4, 1];
+        let ids = lengths
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &len)| (0..len).map(move |j| i as u32 * 10 + j))
+            .collect();
+        (ids, lengths)
+    }
+
+    #[test]
+    fn ids_are_packed_as_little_endian_int64() {
+        let builder = BatchBuilder::new();
+        let batches = builder.build(&[1, 0x1234_5678, u32::MAX], &[3], &[]);
+        assert_eq!(batches.len(), 1);
+        let bytes = batches[0].input_ids();
+        assert_eq!(bytes.len(), 3 * 8);
+        assert_eq!(&bytes[..8], &[1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&bytes[8..16], &[0x78, 0x56, 0x34, 0x12, 0, 0, 0, 0]);
+        // u32 按无符号扩展，不会变成负数
+        assert_eq!(&bytes[16..], &[0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+        assert_eq!(int64s(&batches[0].attention_mask()), [1, 1, 1]);
+        assert_eq!(int64s(&batches[0].token_type_ids()), [0, 0, 0]);
+    }
+
+    #[test]
+    fn bucketing_groups_similar_lengths() {
+        let (ids, lengths) = sequences();
+        let mut builder = BatchBuilder::new();
+        builder.set_max_batch_size(2);
+        let batches = builder.build(&ids, &lengths, &[]);
+
+        let shapes: Vec<(Vec<u32>, usize)> = batches
+            .iter()
+            .map(|b| (b.indices(), b.sequence_length()))
+            .collect();
+        assert_eq!(shapes, [(vec![1, 5], 1), (vec![3, 2], 3), (vec![4, 0], 5)]);
+        assert_eq!(batches[1].lengths(), [2, 3]);
+
+        builder.set_pad_to_multiple_of(4);
+        let padded: Vec<usize> = builder
+            .build(&ids, &lengths, &[])
+            .iter()
+            .map(|b| b.sequence_length())
+            .collect();
+        assert_eq!(padded, [4, 4, 8]);
+
+        // 关闭分桶时按输入顺序切分，批次长度取批内最长
+        builder.set_pad_to_multiple_of(0);
+        builder.set_bucketing(false);
+        let shapes: Vec<(Vec<u32>, usize)> = builder
+            .build(&ids, &lengths, &[])
+            .iter()
+            .map(|b| (b.indices(), b.sequence_length()))
+            .collect();
+        assert_eq!(shapes, [(vec![0, 1], 5), (vec![2, 3], 3), (vec![4, 5], 4)]);
+    }
+
+    #[test]
+    fn max_tokens_limits_padded_batch_size() {
+        let (ids, lengths) = sequences();
+        let mut builder = BatchBuilder::new();
+        builder.set_max_tokens(6);
+        builder.set_pad_to_multiple_of(2);
+        let batches = builder.build(&ids, &lengths, &[]);
+        for batch in &batches {
+            assert!(batch.batch_size() == 1 || batch.batch_size() * batch.sequence_length() <= 6);
+        }
+        let mut covered: Vec<u32> = batches.iter().flat_map(|b| b.indices()).collect();
+        covered.sort_unstable();
+        assert_eq!(covered, [0, 1, 2, 3, 4, 5]);
+        // 单条序列超过上限时仍独占一个批次
+        assert_eq!(batches.last().unwrap().indices(), [0]);
+        assert_eq!(batches.last().unwrap().sequence_length(), 6);
+    }
+
+    #[test]
+    fn padding_and_attention_mask() {
+        let (ids, lengths) = sequences();
+        let type_ids: Vec<u32> = ids.iter().map(|id| id % 2).collect();
+        let mut builder = BatchBuilder::new();
+        builder.set_pad_id(7);
+        builder.set_bucketing(false);
+        builder.set_max_batch_size(3);
+        let batches = builder.build(&ids, &lengths, &type_ids);
+        assert_eq!(batches.len(), 2);
+
+        let batch = &batches[0];
+        assert_eq!((batch.batch_size(), batch.sequence_length()), (3, 5));
+        assert_eq!(
+            int64s(&batch.input_ids()),
+            [0, 1, 2, 3, 4, 10, 7, 7, 7, 7, 20, 21, 22, 7, 7]
+        );
+        assert_eq!(
+            int64s(&batch.attention_mask()),
+            [1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 1, 1, 1, 0, 0]
+        );
+        assert_eq!(
+            int64s(&batch.token_type_ids()),
+            [0, 1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]
+        );
+        assert_eq!(batch.lengths(), [5, 1, 3]);
+    }
+
+    #[test]
+    fn mismatched_shapes_produce_no_batches() {
+        let builder = BatchBuilder::new();
+        assert!(builder.build(&[1, 2, 3], &[2], &[]).is_empty());
+        assert!(builder.build(&[1, 2, 3], &[3], &[0, 0]).is_empty());
+        assert!(builder.build(&[], &[], &[]).is_empty());
+    }
+}
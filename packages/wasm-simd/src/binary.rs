@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::metric::packed_count;
+use crate::store::VectorStore;
+use crate::topk::{TopKHeap, TopKResults};
+
+const BITS_PER_WORD: usize = 64;
+
+#[inline]
+fn words_for_dim(dim: usize) -> usize {
+    dim.div_ceil(BITS_PER_WORD)
+}
+
+// 每维取符号位：x > 0 为 1，否则为 0，按 64 位一组打包
+fn pack_vector(vector: &[f32], out: &mut Vec<u64>) {
+    for chunk in vector.chunks(BITS_PER_WORD) {
+        let word = chunk
+            .iter()
+            .enumerate()
+            .fold(0u64, |word, (bit, &x)| word | (((x > 0.0) as u64) << bit));
+        out.push(word);
+    }
+}
+
+pub(crate) fn pack_sign_bits(vectors: &[f32], vector_dim: usize) -> Vec<u64> {
+    let count = match packed_count(vectors, vector_dim) {
+        Some(n) => n,
+        None => return Vec::new(),
+    };
+    let mut codes = Vec::with_capacity(count * words_for_dim(vector_dim));
+    for vector in vectors.chunks_exact(vector_dim) {
+        pack_vector(vector, &mut codes);
+    }
+    codes
+}
+
+// 基于 popcount 的汉明距离
+#[inline]
+fn hamming_distance(a: &[u64], b: &[u64]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones()).sum()
+}
+
+// 1-bit 二值化向量索引：先用汉明距离粗筛，可选用常驻的全精度 VectorStore 重排。
+// 打包码按槽位连续存放，删除时用最后一个槽位填补空位
+#[wasm_bindgen]
+pub struct BinaryIndex {
+    dim: usize,
+    words: usize,
+    codes: Vec<u64>,
+    labels: Vec<u32>,
+    slots: HashMap<u32, usize>,
+}
+
+#[wasm_bindgen]
+impl BinaryIndex {
+    #[wasm_bindgen(constructor)]
+    pub fn new(dim: usize) -> BinaryIndex {
+        BinaryIndex {
+            dim,
+            words: words_for_dim(dim),
+            codes: Vec::new(),
+            labels: Vec::new(),
+            slots: HashMap::new(),
+        }
+    }
+
+    // 从持久化的 label 和打包码恢复，长度不符或 label 重复时返回 None
+    pub fn from_codes(dim: usize, labels: Vec<u32>, codes: Vec<u64>) -> Option<BinaryIndex> {
+        let words = words_for_dim(dim);
+        if dim == 0 || codes.len() != labels.len() * words {
+            return None;
+        }
+        let slots: HashMap<u32, usize> = labels
+            .iter()
+            .enumerate()
+            .map(|(slot, &label)| (label, slot))
+            .collect();
+        if slots.len() != labels.len() {
+            return None;
+        }
+        Some(BinaryIndex {
+            dim,
+            words,
+            codes,
+            labels,
+            slots,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.labels.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn codes(&self) -> Vec<u64> {
+        self.codes.clone()
+    }
+
+    // 与 codes 按槽位一一对应
+    #[wasm_bindgen(getter)]
+    pub fn labels(&self) -> Vec<u32> {
+        self.labels.clone()
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.codes.len() * std::mem::size_of::<u64>()
+            + self.labels.len() * std::mem::size_of::<u32>()
+    }
+
+    pub fn contains(&self, label: u32) -> bool {
+        self.slots.contains_key(&label)
+    }
+
+    // 批量新增打包向量，label 已存在的向量被跳过，返回新增的数量
+    pub fn add(&mut self, labels: &[u32], vectors: &[f32]) -> usize {
+        if packed_count(vectors, self.dim) != Some(labels.len()) {
+            return 0;
+        }
+        self.codes.reserve(labels.len() * self.words);
+        let mut added = 0;
+        for (&label, vector) in labels.iter().zip(vectors.chunks_exact(self.dim)) {
+            if self.slots.contains_key(&label) {
+                continue;
+            }
+            self.slots.insert(label, self.labels.len());
+            self.labels.push(label);
+            pack_vector(vector, &mut self.codes);
+            added += 1;
+        }
+        added
+    }
+
+    pub fn remove(&mut self, label: u32) -> bool {
+        let slot = match self.slots.remove(&label) {
+            Some(slot) => slot,
+            None => return false,
+        };
+        let last = self.labels.len() - 1;
+        if slot != last {
+            let moved = self.labels[last];
+            self.codes.copy_within(
+                last * self.words..(last + 1) * self.words,
+                slot * self.words,
+            );
+            self.labels[slot] = moved;
+            self.slots.insert(moved, slot);
+        }
+        self.labels.truncate(last);
+        self.codes.truncate(last * self.words);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.codes.clear();
+        self.labels.clear();
+        self.slots.clear();
+    }
+
+    // 汉明距离 Top-K，indices 为 label，scores 为汉明距离（越小越相似）
+    pub fn hamming_top_k(&self, query: &[f32], k: usize) -> TopKResults {
+        if query.len() != self.dim || self.dim == 0 {
+            return TopKResults::default();
+        }
+        let mut packed_query = Vec::with_capacity(self.words);
+        pack_vector(query, &mut packed_query);
+
+        let mut heap = TopKHeap::new(k, false);
+        for (slot, code) in self.codes.chunks_exact(self.words).enumerate() {
+            heap.push(
+                self.labels[slot],
+                hamming_distance(&packed_query, code) as f32,
+            );
+        }
+        heap.into_results()
+    }
+
+    // 两阶段搜索：汉明距离取前 candidates 个，再只对这些候选按 label 取出 store 中的
+    // 全精度向量，以 store 的度量重排取前 k 个；store 中没有的候选被跳过
+    pub fn search_rescored(
+        &self,
+        query: &[f32],
+        store: &VectorStore,
+        k: usize,
+        candidates: usize,
+    ) -> TopKResults {
+        if store.dim() != self.dim {
+            return TopKResults::default();
+        }
+        let coarse = self.hamming_top_k(query, candidates.max(k));
+
+        let metric = store.metric();
+        let mut heap = TopKHeap::for_metric(k, metric);
+        for label in coarse.indices() {
+            if let Some(vector) = store.vector(label) {
+                heap.push(label, metric.score(vector, query));
+            }
+        }
+        heap.into_results()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::Metric;
+    use crate::rng::random_vectors;
+
+    // 逐维比较符号，不经过打包，作为汉明距离的参照
+    fn brute_force(
+        vectors: &[f32],
+        labels: &[u32],
+        query: &[f32],
+        dim: usize,
+        k: usize,
+    ) -> Vec<(u32, f32)> {
+        let mut distances: Vec<(u32, f32)> = vectors
+            .chunks_exact(dim)
+            .zip(labels)
+            .map(|(vector, &label)| {
+                let distance = vector
+                    .iter()
+                    .zip(query)
+                    .filter(|(a, b)| (**a > 0.0) != (**b > 0.0))
+                    .count();
+                (label, distance as f32)
+            })
+            .collect();
+        distances.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        distances.truncate(k);
+        distances
+    }
+
+    fn pairs(results: &TopKResults) -> Vec<(u32, f32)> {
+        results
+            .indices()
+            .into_iter()
+            .zip(results.scores())
+            .collect()
+    }
+
+    #[test]
+    fn sign_bits_are_packed_little_endian_per_word() {
+        let mut vector = vec![-1.0f32; 70];
+        vector[0] = 0.5;
+        vector[63] = 2.0;
+        vector[64] = 1.0;
+        vector[69] = 3.0;
+        // 0 不算正数
+        vector[1] = 0.0;
+        assert_eq!(pack_sign_bits(&vector, 70), [1 | 1 << 63, 1 | 1 << 5]);
+
+        let packed = pack_sign_bits(&random_vectors(1, 3, 70), 70);
+        assert_eq!(packed.len(), 3 * 2);
+        // 最后一个字中超出维度的位保持为 0
+        assert!(packed.chunks_exact(2).all(|w| w[1] >> 6 == 0));
+        assert!(pack_sign_bits(&[1.0; 5], 2).is_empty());
+    }
+
+    #[test]
+    fn hamming_top_k_matches_brute_force() {
+        for dim in [13, 64, 100, 130] {
+            let vectors = random_vectors(dim as u64, 200, dim);
+            let labels: Vec<u32> = (0..200).map(|i| 1000 - i * 2).collect();
+            let mut index = BinaryIndex::new(dim);
+            assert_eq!(index.add(&labels, &vectors), 200);
+            for query in random_vectors(7, 5, dim).chunks_exact(dim) {
+                assert_eq!(
+                    pairs(&index.hamming_top_k(query, 15)),
+                    brute_force(&vectors, &labels, query, dim, 15),
+                    "dim {}",
+                    dim
+                );
+            }
+            // k 超过向量数时返回全部
+            assert_eq!(index.hamming_top_k(&vectors[..dim], 500).length(), 200);
+        }
+    }
+
+    #[test]
+    fn remove_and_re_add_by_label() {
+        let dim = 100;
+        let vectors = random_vectors(3, 50, dim);
+        let labels: Vec<u32> = (0..50).collect();
+        let mut index = BinaryIndex::new(dim);
+        assert_eq!(index.add(&labels, &vectors), 50);
+        // 已存在的 label 被跳过
+        assert_eq!(index.add(&[3], &vectors[..dim]), 0);
+
+        let removed = [0u32, 7, 49, 20];
+        for label in removed {
+            assert!(index.remove(label));
+            assert!(!index.remove(label));
+            assert!(!index.contains(label));
+        }
+        assert_eq!(index.length(), 46);
+
+        // 槽位交换后其余 label 仍对应原来的码
+        let mut kept_vectors = Vec::new();
+        let mut kept_labels = Vec::new();
+        for (label, vector) in (0..50u32).zip(vectors.chunks_exact(dim)) {
+            if !removed.contains(&label) {
+                kept_vectors.extend_from_slice(vector);
+                kept_labels.push(label);
+            }
+        }
+        let query = random_vectors(4, 1, dim);
+        assert_eq!(
+            pairs(&index.hamming_top_k(&query, 46)),
+            brute_force(&kept_vectors, &kept_labels, &query, dim, 46)
+        );
+
+        // 重新加入的 label 使用新的向量
+        let replacement: Vec<f32> = vectors[..dim].iter().map(|x| -x).collect();
+        assert_eq!(index.add(&[7], &replacement), 1);
+        let top = index.hamming_top_k(&replacement, 1);
+        assert_eq!(pairs(&top), [(7, 0.0)]);
+
+        let restored = BinaryIndex::from_codes(dim, index.labels(), index.codes()).unwrap();
+        assert_eq!(
+            pairs(&restored.hamming_top_k(&query, 47)),
+            pairs(&index.hamming_top_k(&query, 47))
+        );
+        assert!(BinaryIndex::from_codes(dim, vec![1, 1], vec![0; 4]).is_none());
+    }
+
+    #[test]
+    fn rescoring_uses_the_store_metric() {
+        let dim = 32;
+        let vectors = random_vectors(5, 100, dim);
+        let labels: Vec<u32> = (0..100).collect();
+        let mut index = BinaryIndex::new(dim);
+        index.add(&labels, &vectors);
+        let mut store = VectorStore::new(dim, Metric::Cosine);
+        assert_eq!(store.add_batch(&labels, &vectors), 100);
+
+        // 候选数覆盖全部向量时与精确搜索一致
+        let query = random_vectors(6, 1, dim);
+        let rescored = index.search_rescored(&query, &store, 5, 100);
+        assert_eq!(rescored.indices(), store.search(&query, 5).indices());
+
+        // store 中缺少的候选被跳过
+        let best = rescored.indices()[0];
+        store.remove(best);
+        assert!(!index
+            .search_rescored(&query, &store, 5, 100)
+            .indices()
+            .contains(&best));
+        assert_eq!(
+            index
+                .search_rescored(&query, &VectorStore::new(dim + 1, Metric::Cosine), 5, 100)
+                .length(),
+            0
+        );
+    }
+}
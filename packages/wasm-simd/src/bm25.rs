@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::topk::{TopKHeap, TopKResults};
+
+const DEFAULT_K1: f32 = 1.2;
+const DEFAULT_B: f32 = 0.75;
+
+// 中日韩文字没有空格分词，按字符二元组切分
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF   // 平假名、片假名
+        | 0x3400..=0x4DBF // CJK 扩展 A
+        | 0x4E00..=0x9FFF // CJK 统一汉字
+        | 0xAC00..=0xD7AF // 韩文音节
+        | 0xF900..=0xFAFF // CJK 兼容汉字
+        | 0x20000..=0x2A6DF)
+}
+
+// 词法分词：小写化，字母数字与下划线组成一个词（保留 snake_case 标识符和错误码），
+// 连续的 CJK 字符输出重叠二元组，单个 CJK 字符单独成词
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk_run: Vec<char> = Vec::new();
+
+    fn flush_cjk(run: &mut Vec<char>, tokens: &mut Vec<String>) {
+        match run.len() {
+            0 => {}
+            1 => tokens.push(run[0].to_string()),
+            _ => tokens.extend(run.windows(2).map(|w| w.iter().collect::<String>())),
+        }
+        run.clear();
+    }
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            cjk_run.push(c);
+        } else if c.is_alphanumeric() || c == '_' {
+            flush_cjk(&mut cjk_run, &mut tokens);
+            word.extend(c.to_lowercase());
+        } else {
+            flush_cjk(&mut cjk_run, &mut tokens);
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+        }
+    }
+    flush_cjk(&mut cjk_run, &mut tokens);
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+#[derive(Clone, Copy)]
+struct Posting {
+    doc_id: u32,
+    term_frequency: u32,
+}
+
+struct Document {
+    length: u32,
+    terms: Vec<u32>,
+}
+
+// BM25 倒排索引，文档以调用方的 u32 id 标识
+#[wasm_bindgen]
+pub struct Bm25Index {
+    k1: f32,
+    b: f32,
+    vocabulary: HashMap<String, u32>,
+    // postings[term_id]
+    postings: Vec<Vec<Posting>>,
+    documents: HashMap<u32, Document>,
+    total_length: u64,
+}
+
+impl Default for Bm25Index {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl Bm25Index {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Bm25Index {
+        Bm25Index {
+            k1: DEFAULT_K1,
+            b: DEFAULT_B,
+            vocabulary: HashMap::new(),
+            postings: Vec::new(),
+            documents: HashMap::new(),
+            total_length: 0,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn k1(&self) -> f32 {
+        self.k1
+    }
+
+    // 词频饱和参数，通常取 1.2 ~ 2.0
+    pub fn set_k1(&mut self, k1: f32) {
+        self.k1 = k1.max(0.0);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn b(&self) -> f32 {
+        self.b
+    }
+
+    // 文档长度归一化强度，0 表示不做长度归一化
+    pub fn set_b(&mut self, b: f32) {
+        self.b = b.clamp(0.0, 1.0);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.documents.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn average_document_length(&self) -> f32 {
+        if self.documents.is_empty() {
+            0.0
+        } else {
+            self.total_length as f32 / self.documents.len() as f32
+        }
+    }
+
+    pub fn contains(&self, doc_id: u32) -> bool {
+        self.documents.contains_key(&doc_id)
+    }
+
+    // 索引文档，doc_id 已存在时替换旧内容；返回词数
+    pub fn index(&mut self, doc_id: u32, text: &str) -> usize {
+        self.remove(doc_id);
+
+        let tokens = tokenize(text);
+        let mut frequencies: HashMap<u32, u32> = HashMap::new();
+        for token in &tokens {
+            let next_id = self.postings.len() as u32;
+            let term_id = *self.vocabulary.entry(token.clone()).or_insert(next_id);
+            if term_id == next_id {
+                self.postings.push(Vec::new());
+            }
+            *frequencies.entry(term_id).or_insert(0) += 1;
+        }
+
+        let mut terms: Vec<u32> = frequencies.keys().copied().collect();
+        terms.sort_unstable();
+        for &term_id in &terms {
+            self.postings[term_id as usize].push(Posting {
+                doc_id,
+                term_frequency: frequencies[&term_id],
+            });
+        }
+        self.total_length += tokens.len() as u64;
+        self.documents.insert(
+            doc_id,
+            Document {
+                length: tokens.len() as u32,
+                terms,
+            },
+        );
+        tokens.len()
+    }
+
+    pub fn remove(&mut self, doc_id: u32) -> bool {
+        let document = match self.documents.remove(&doc_id) {
+            Some(document) => document,
+            None => return false,
+        };
+        for term_id in document.terms {
+            let postings = &mut self.postings[term_id as usize];
+            if let Some(pos) = postings.iter().position(|p| p.doc_id == doc_id) {
+                postings.swap_remove(pos);
+            }
+        }
+        self.total_length -= document.length as u64;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.vocabulary.clear();
+        self.postings.clear();
+        self.documents.clear();
+        self.total_length = 0;
+    }
+
+    // BM25 打分，返回分数最高的 k 个文档，indices 为 doc_id
+    pub fn search(&self, query: &str, k: usize) -> TopKResults {
+        let mut heap = TopKHeap::new(k, true);
+        for (doc_id, score) in self.score_documents(query) {
+            heap.push(doc_id, score);
+        }
+        heap.into_results()
+    }
+}
+
+impl Bm25Index {
+    fn score_documents(&self, query: &str) -> HashMap<u32, f32> {
+        let mut scores: HashMap<u32, f32> = HashMap::new();
+        if self.documents.is_empty() {
+            return scores;
+        }
+        let mut terms: Vec<u32> = tokenize(query)
+            .iter()
+            .filter_map(|token| self.vocabulary.get(token).copied())
+            .collect();
+        terms.sort_unstable();
+        terms.dedup();
+
+        let doc_count = self.documents.len() as f32;
+        let average_length = self.average_document_length().max(f32::MIN_POSITIVE);
+        for term_id in terms {
+            let postings = &self.postings[term_id as usize];
+            if postings.is_empty() {
+                continue;
+            }
+            let df = postings.len() as f32;
+            let idf = (1.0 + (doc_count - df + 0.5) / (df + 0.5)).ln();
+            for posting in postings {
+                let length = self.documents[&posting.doc_id].length as f32;
+                let tf = posting.term_frequency as f32;
+                let norm = self.k1 * (1.0 - self.b + self.b * length / average_length);
+                *scores.entry(posting.doc_id).or_insert(0.0) +=
+                    idf * tf * (self.k1 + 1.0) / (tf + norm);
+            }
+        }
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() <= 1e-6 * expected.abs().max(1.0),
+            "{} vs {}",
+            actual,
+            expected
+        );
+    }
+
+    fn sample_index() -> Bm25Index {
+        let mut index = Bm25Index::new();
+        assert_eq!(index.index(1, "The cat sat"), 3);
+        assert_eq!(index.index(2, "the dog sat on the mat"), 6);
+        assert_eq!(index.index(3, "Cat, cat. Dog!"), 3);
+        index
+    }
+
+    #[test]
+    fn tokenize_keeps_identifiers_and_splits_cjk_into_bigrams() {
+        assert_eq!(
+            tokenize("Hello_World ERR-404 日本語 x 猫"),
+            ["hello_world", "err", "404", "日本", "本語", "x", "猫"]
+        );
+        assert_eq!(tokenize("abc中文def"), ["abc", "中文", "def"]);
+        assert!(tokenize(" ,.!? ").is_empty());
+    }
+
+    #[test]
+    fn scores_match_hand_computed_bm25() {
+        let index = sample_index();
+        assert_eq!(index.average_document_length(), 4.0);
+
+        // N = 3，avgdl = 4，k1 = 1.2，b = 0.75；cat 和 dog 的 df 都是 2
+        let idf = (1.0f32 + 1.5 / 2.5).ln();
+        // 长度 3 的文档：k1 * (1 - b + b * 3 / 4) = 0.975；长度 6：1.65
+        let cat_1 = idf * 2.2 / (1.0 + 0.975);
+        let cat_3 = idf * 2.0 * 2.2 / (2.0 + 0.975);
+        let dog_2 = idf * 2.2 / (1.0 + 1.65);
+        let dog_3 = idf * 2.2 / (1.0 + 0.975);
+
+        let results = index.search("cat", 10);
+        assert_eq!(results.indices(), [3, 1]);
+        assert_close(results.scores()[0], cat_3);
+        assert_close(results.scores()[1], cat_1);
+
+        // 查询中重复的词只计一次，未登录词不影响分数
+        let results = index.search("DOG cat dog unicorn", 10);
+        assert_eq!(results.indices(), [3, 1, 2]);
+        assert_close(results.scores()[0], cat_3 + dog_3);
+        assert_close(results.scores()[1], cat_1);
+        assert_close(results.scores()[2], dog_2);
+
+        // 词频相同时短文档得分更高
+        let results = index.search("sat", 10);
+        assert_eq!(results.indices(), [1, 2]);
+        assert_close(results.scores()[0], idf * 2.2 / (1.0 + 0.975));
+        assert_close(results.scores()[1], idf * 2.2 / (1.0 + 1.65));
+    }
+
+    #[test]
+    fn b_zero_disables_length_normalization() {
+        let mut index = sample_index();
+        index.set_b(0.0);
+        let idf = (1.0f32 + 1.5 / 2.5).ln();
+        let results = index.search("sat", 10);
+        // 词频相同时长短文档同分，按 doc_id 排序
+        assert_eq!(results.indices(), [1, 2]);
+        assert_close(results.scores()[0], idf * 2.2 / 2.2);
+        assert_close(results.scores()[1], idf * 2.2 / 2.2);
+    }
+
+    #[test]
+    fn reindex_and_remove_update_statistics() {
+        let mut index = sample_index();
+        assert_eq!(index.index(2, "cat"), 1);
+        assert_eq!(index.length(), 3);
+        assert_close(index.average_document_length(), 7.0 / 3.0);
+        assert_eq!(index.search("dog", 10).indices(), [3]);
+        assert_eq!(index.search("cat", 10).length(), 3);
+
+        assert!(index.remove(3));
+        assert!(!index.remove(3));
+        assert!(index.search("dog", 10).indices().is_empty());
+        assert_eq!(index.average_document_length(), 2.0);
+
+        // 剩余两篇文档都含 cat：df = N = 2
+        let idf = (1.0f32 + 0.5 / 2.5).ln();
+        let results = index.search("cat", 10);
+        assert_eq!(results.indices(), [2, 1]);
+        assert_close(
+            results.scores()[0],
+            idf * 2.2 / (1.0 + 1.2 * (0.25 + 0.75 * 0.5)),
+        );
+
+        index.clear();
+        assert_eq!(index.length(), 0);
+        assert!(index.search("cat", 10).indices().is_empty());
+    }
+}
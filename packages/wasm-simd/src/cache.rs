@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use wasm_bindgen::prelude::*;
+
+use crate::hash::{hash128, hash64};
+
+const NIL: u32 = u32::MAX;
+const MODEL_SEED: u64 = 0x6d6f_6465_6c5f_6964;
+
+// 与 SemanticSimilarityEngine.cacheStats.embedding 相同的字段，另加淘汰和过期计数
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CacheStats {
+    hits: u32,
+    misses: u32,
+    size: u32,
+    evictions: u32,
+    expirations: u32,
+}
+
+#[wasm_bindgen]
+impl CacheStats {
+    #[wasm_bindgen(getter)]
+    pub fn hits(&self) -> u32 {
+        self.hits
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn misses(&self) -> u32 {
+        self.misses
+    }
+
+    // 当前条目数
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    // 因超出字节预算被淘汰的条目数
+    #[wasm_bindgen(getter)]
+    pub fn evictions(&self) -> u32 {
+        self.evictions
+    }
+
+    // 因超过 TTL 被丢弃的条目数
+    #[wasm_bindgen(getter)]
+    pub fn expirations(&self) -> u32 {
+        self.expirations
+    }
+}
+
+// 槽位元数据，prev / next 组成 LRU 双向链表（head 为最近使用）
+#[derive(Clone, Copy)]
+struct Slot {
+    key: u128,
+    prev: u32,
+    next: u32,
+    inserted_at: f64,
+}
+
+// 按 (模型 id, 文本) 的 128 位哈希缓存向量，向量连续存放在按槽位划分的 slab 中；
+// 超出字节预算时淘汰最久未使用的条目。时间由调用方传入（Date.now()），ttl_ms 为 0 表示不过期
+#[wasm_bindgen]
+pub struct EmbeddingCache {
+    dim: usize,
+    byte_budget: usize,
+    ttl_ms: f64,
+    data: Vec<f32>,
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    map: HashMap<u128, u32>,
+    head: u32,
+    tail: u32,
+    stats: CacheStats,
+}
+
+#[wasm_bindgen]
+impl EmbeddingCache {
+    #[wasm_bindgen(constructor)]
+    pub fn new(dim: usize, byte_budget: usize) -> EmbeddingCache {
+        EmbeddingCache {
+            dim,
+            byte_budget,
+            ttl_ms: 0.0,
+            data: Vec::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            map: HashMap::new(),
+            head: NIL,
+            tail: NIL,
+            stats: CacheStats::default(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn byte_budget(&self) -> usize {
+        self.byte_budget
+    }
+
+    // 缩小预算时立即淘汰多出的条目，并把 slab 压缩到剩余条目的大小
+    pub fn set_byte_budget(&mut self, byte_budget: usize) {
+        let shrinking = byte_budget < self.byte_budget;
+        self.byte_budget = byte_budget;
+        while self.map.len() > self.capacity() {
+            self.evict_tail();
+        }
+        if shrinking {
+            self.compact();
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ttl_ms(&self) -> f64 {
+        self.ttl_ms
+    }
+
+    pub fn set_ttl_ms(&mut self, ttl_ms: f64) {
+        self.ttl_ms = ttl_ms.max(0.0);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.map.len()
+    }
+
+    // 每个条目占用的字节：向量本身加槽位和哈希表项
+    #[wasm_bindgen(getter)]
+    pub fn entry_bytes(&self) -> usize {
+        self.dim * size_of::<f32>() + size_of::<Slot>() + size_of::<(u128, u32)>()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bytes_used(&self) -> usize {
+        self.map.len() * self.entry_bytes()
+    }
+
+    // 预算内最多可容纳的条目数
+    #[wasm_bindgen(getter)]
+    pub fn capacity(&self) -> usize {
+        self.byte_budget / self.entry_bytes()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            size: self.map.len() as u32,
+            ..self.stats
+        }
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+
+    // 命中时返回向量副本并标记为最近使用；过期条目按未命中处理并删除
+    pub fn get(&mut self, model_id: &str, text: &str, now_ms: f64) -> Option<Vec<f32>> {
+        match self.lookup(cache_key(model_id, text), now_ms) {
+            Some(slot) => {
+                self.stats.hits += 1;
+                Some(self.vector(slot).to_vec())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    // 不计入统计、不影响 LRU 顺序
+    pub fn contains(&self, model_id: &str, text: &str, now_ms: f64) -> bool {
+        self.map
+            .get(&cache_key(model_id, text))
+            .is_some_and(|&slot| !self.expired(slot, now_ms))
+    }
+
+    // 维度不符或单个条目超出预算时返回 false；已存在时覆盖并重置 TTL
+    pub fn insert(&mut self, model_id: &str, text: &str, vector: &[f32], now_ms: f64) -> bool {
+        if vector.len() != self.dim || self.capacity() == 0 {
+            return false;
+        }
+        let key = cache_key(model_id, text);
+        let slot = match self.map.get(&key) {
+            Some(&slot) => {
+                self.unlink(slot);
+                slot
+            }
+            None => {
+                if self.map.len() >= self.capacity() {
+                    self.evict_tail();
+                }
+                let slot = self.allocate();
+                self.map.insert(key, slot);
+                slot
+            }
+        };
+        self.slots[slot as usize] = Slot {
+            key,
+            prev: NIL,
+            next: NIL,
+            inserted_at: now_ms,
+        };
+        self.vector_mut(slot).copy_from_slice(vector);
+        self.push_front(slot);
+        true
+    }
+
+    pub fn remove(&mut self, model_id: &str, text: &str) -> bool {
+        match self.map.remove(&cache_key(model_id, text)) {
+            Some(slot) => {
+                self.release(slot);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 丢弃所有已过期的条目，返回数量
+    pub fn prune_expired(&mut self, now_ms: f64) -> usize {
+        if self.ttl_ms == 0.0 {
+            return 0;
+        }
+        let expired: Vec<u128> = self
+            .map
+            .iter()
+            .filter(|(_, &slot)| self.expired(slot, now_ms))
+            .map(|(&key, _)| key)
+            .collect();
+        for key in &expired {
+            if let Some(slot) = self.map.remove(key) {
+                self.release(slot);
+            }
+        }
+        self.stats.expirations += expired.len() as u32;
+        expired.len()
+    }
+
+    // 清空条目，统计保留
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.slots.clear();
+        self.free.clear();
+        self.map.clear();
+        self.head = NIL;
+        self.tail = NIL;
+    }
+}
+
+fn cache_key(model_id: &str, text: &str) -> u128 {
+    hash128(text.as_bytes(), hash64(model_id.as_bytes(), MODEL_SEED))
+}
+
+impl EmbeddingCache {
+    #[inline]
+    fn vector(&self, slot: u32) -> &[f32] {
+        let start = slot as usize * self.dim;
+        &self.data[start..start + self.dim]
+    }
+
+    #[inline]
+    fn vector_mut(&mut self, slot: u32) -> &mut [f32] {
+        let start = slot as usize * self.dim;
+        &mut self.data[start..start + self.dim]
+    }
+
+    fn expired(&self, slot: u32, now_ms: f64) -> bool {
+        self.ttl_ms > 0.0 && now_ms - self.slots[slot as usize].inserted_at >= self.ttl_ms
+    }
+
+    fn lookup(&mut self, key: u128, now_ms: f64) -> Option<u32> {
+        let slot = *self.map.get(&key)?;
+        if self.expired(slot, now_ms) {
+            self.map.remove(&key);
+            self.release(slot);
+            self.stats.expirations += 1;
+            return None;
+        }
+        self.unlink(slot);
+        self.push_front(slot);
+        Some(slot)
+    }
+
+    fn allocate(&mut self) -> u32 {
+        match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.data.resize(self.data.len() + self.dim, 0.0);
+                self.slots.push(Slot {
+                    key: 0,
+                    prev: NIL,
+                    next: NIL,
+                    inserted_at: 0.0,
+                });
+                (self.slots.len() - 1) as u32
+            }
+        }
+    }
+
+    // 槽位已从 map 中移除后调用
+    fn release(&mut self, slot: u32) {
+        self.unlink(slot);
+        self.free.push(slot);
+        if self.map.is_empty() {
+            // 全部清空时归还 slab 内存
+            self.clear();
+        } else if self.free.len() > self.map.len() {
+            // 空闲槽位超过一半时压缩，均摊到每次删除为 O(1)
+            self.compact();
+        }
+    }
+
+    // 按 LRU 顺序把存活条目搬到 slab 前部，截断并归还多余内存
+    fn compact(&mut self) {
+        let mut data = Vec::with_capacity(self.map.len() * self.dim);
+        let mut slots = Vec::with_capacity(self.map.len());
+        let mut slot = self.head;
+        while slot != NIL {
+            let old = self.slots[slot as usize];
+            let new = slots.len() as u32;
+            data.extend_from_slice(self.vector(slot));
+            slots.push(Slot {
+                prev: if new == 0 { NIL } else { new - 1 },
+                next: if old.next == NIL { NIL } else { new + 1 },
+                ..old
+            });
+            self.map.insert(old.key, new);
+            slot = old.next;
+        }
+        (self.head, self.tail) = match slots.len() {
+            0 => (NIL, NIL),
+            n => (0, n as u32 - 1),
+        };
+        self.data = data;
+        self.slots = slots;
+        self.free = Vec::new();
+    }
+
+    fn evict_tail(&mut self) {
+        if self.tail == NIL {
+            return;
+        }
+        let slot = self.tail;
+        self.map.remove(&self.slots[slot as usize].key);
+        self.release(slot);
+        self.stats.evictions += 1;
+    }
+
+    fn unlink(&mut self, slot: u32) {
+        let Slot { prev, next, .. } = self.slots[slot as usize];
+        if prev == NIL {
+            self.head = next;
+        } else {
+            self.slots[prev as usize].next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.slots[next as usize].prev = prev;
+        }
+        self.slots[slot as usize].prev = NIL;
+        self.slots[slot as usize].next = NIL;
+    }
+
+    fn push_front(&mut self, slot: u32) {
+        self.slots[slot as usize].next = self.head;
+        if self.head != NIL {
+            self.slots[self.head as usize].prev = slot;
+        }
+        self.head = slot;
+        if self.tail == NIL {
+            self.tail = slot;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(i: usize) -> Vec<f32> {
+        vec![i as f32; 4]
+    }
+
+    #[test]
+    fn shrinking_budget_compacts_slab() {
+        let mut cache = EmbeddingCache::new(4, 0);
+        cache.set_byte_budget(cache.entry_bytes() * 16);
+        for i in 0..16 {
+            cache.insert("m", &i.to_string(), &vector(i), 0.0);
+        }
+        // 访问 3 使其成为最近使用
+        cache.get("m", "3", 0.0);
+
+        cache.set_byte_budget(cache.entry_bytes() * 4);
+        assert_eq!(cache.length(), 4);
+        assert_eq!(cache.slots.len(), 4);
+        assert_eq!(cache.data.len(), 16);
+        assert!(cache.free.is_empty());
+        for i in [3, 15, 14, 13] {
+            assert_eq!(cache.get("m", &i.to_string(), 0.0), Some(vector(i)));
+        }
+        assert_eq!(cache.stats().evictions(), 12);
+
+        // 压缩后的 LRU 顺序保持不变：再插入一条淘汰的是最久未使用的 3
+        cache.insert("m", "16", &vector(16), 0.0);
+        assert!(!cache.contains("m", "3", 0.0));
+        assert!(cache.contains("m", "13", 0.0));
+    }
+
+    #[test]
+    fn removals_compact_slab() {
+        let mut cache = EmbeddingCache::new(4, 1 << 20);
+        for i in 0..10 {
+            cache.insert("m", &i.to_string(), &vector(i), 0.0);
+        }
+        for i in 0..8 {
+            cache.remove("m", &i.to_string());
+        }
+        assert!(cache.slots.len() <= 2 * cache.length());
+        for i in 8..10 {
+            assert_eq!(cache.get("m", &i.to_string(), 0.0), Some(vector(i)));
+        }
+    }
+}
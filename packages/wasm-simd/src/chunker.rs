@@ -0,0 +1,365 @@
+use unicode_segmentation::UnicodeSegmentation;
+use wasm_bindgen::prelude::*;
+
+// 与 utils/text-chunker.ts 相同的分块结果，额外带有 text 在源文本中的字节区间 [start, end)。
+// 标题块的区间指向 title 本身
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct TextChunk {
+    text: String,
+    source: String,
+    index: usize,
+    word_count: usize,
+    start: usize,
+    end: usize,
+}
+
+#[wasm_bindgen]
+impl TextChunk {
+    #[wasm_bindgen(getter)]
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn source(&self) -> String {
+        self.source.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    #[wasm_bindgen(getter = wordCount)]
+    pub fn word_count(&self) -> usize {
+        self.word_count
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn end(&self) -> usize {
+        self.end
+    }
+}
+
+// 分块的基本单元：一个句子，或超长句子按词数切出的片段
+#[derive(Clone, Copy)]
+struct Span {
+    start: usize,
+    end: usize,
+    words: usize,
+}
+
+// 按 Unicode 句子边界（UAX #29，常见英文缩写处不断句）切句、按词边界计词的分块器，
+// 中日韩文字每个表意字符计为一个词
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct TextChunker {
+    max_words_per_chunk: usize,
+    overlap_sentences: usize,
+    // 按 UTF-16 码元计，与 TS 的 string.length 一致
+    min_chunk_length: usize,
+    include_title: bool,
+}
+
+impl Default for TextChunker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl TextChunker {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> TextChunker {
+        TextChunker {
+            max_words_per_chunk: 80,
+            overlap_sentences: 1,
+            min_chunk_length: 20,
+            include_title: true,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_words_per_chunk(&self) -> usize {
+        self.max_words_per_chunk
+    }
+
+    pub fn set_max_words_per_chunk(&mut self, max_words_per_chunk: usize) {
+        self.max_words_per_chunk = max_words_per_chunk.max(1);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn overlap_sentences(&self) -> usize {
+        self.overlap_sentences
+    }
+
+    // 相邻块之间重叠的句子数
+    pub fn set_overlap_sentences(&mut self, overlap_sentences: usize) {
+        self.overlap_sentences = overlap_sentences;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min_chunk_length(&self) -> usize {
+        self.min_chunk_length
+    }
+
+    // 字符数不超过该值的块会被丢弃
+    pub fn set_min_chunk_length(&mut self, min_chunk_length: usize) {
+        self.min_chunk_length = min_chunk_length;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn include_title(&self) -> bool {
+        self.include_title
+    }
+
+    pub fn set_include_title(&mut self, include_title: bool) {
+        self.include_title = include_title;
+    }
+
+    pub fn chunk_text(&self, content: &str, title: Option<String>) -> Vec<TextChunk> {
+        let mut chunks = Vec::new();
+
+        if let Some(title) = title.as_deref().filter(|_| self.include_title) {
+            let trimmed = title.trim();
+            if trimmed.encode_utf16().count() > 5 {
+                let start = title.len() - title.trim_start().len();
+                chunks.push(TextChunk {
+                    text: trimmed.to_string(),
+                    source: "title".to_string(),
+                    index: 0,
+                    word_count: trimmed.unicode_words().count(),
+                    start,
+                    end: start + trimmed.len(),
+                });
+            }
+        }
+
+        let spans = self.split_spans(content);
+        let mut i = 0;
+        while i < spans.len() {
+            let mut word_count = 0;
+            let mut used = 0;
+            while i + used < spans.len() && word_count < self.max_words_per_chunk {
+                let words = spans[i + used].words;
+                if word_count + words > self.max_words_per_chunk && word_count > 0 {
+                    break;
+                }
+                word_count += words;
+                used += 1;
+            }
+
+            let start = spans[i].start;
+            let end = spans[i + used - 1].end;
+            let text = &content[start..end];
+            if text.encode_utf16().count() > self.min_chunk_length {
+                let index = chunks.len();
+                chunks.push(TextChunk {
+                    text: text.to_string(),
+                    source: format!("content_chunk_{}", index),
+                    index,
+                    word_count,
+                    start,
+                    end,
+                });
+            }
+
+            i += used.saturating_sub(self.overlap_sentences).max(1);
+        }
+        chunks
+    }
+}
+
+// 后面紧跟大写词时 UAX #29 会在其后断句的常见缩写（小写、不含末尾的点）
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "vs", "e.g", "i.e", "cf", "fig",
+    "approx", "dept", "vol", "gen", "col", "lt", "sgt", "capt", "rev", "hon",
+];
+
+// 句子以缩写或单个字母的首字母缩写（如 "J."）结尾时不在此处断句
+fn ends_with_abbreviation(sentence: &str) -> bool {
+    let word = match sentence.trim_end().strip_suffix('.') {
+        Some(rest) => rest.rsplit(char::is_whitespace).next().unwrap_or(""),
+        None => return false,
+    };
+    let word = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => c.is_alphabetic(),
+        _ => ABBREVIATIONS.contains(&word.to_lowercase().as_str()),
+    }
+}
+
+// UAX #29 切句，合并缩写处被切开的句子，返回 (字节偏移, 句子)
+fn sentences(content: &str) -> Vec<(usize, &str)> {
+    let mut sentences: Vec<(usize, &str)> = Vec::new();
+    let mut pending: Option<usize> = None;
+    for (offset, sentence) in content.split_sentence_bound_indices() {
+        let start = pending.take().unwrap_or(offset);
+        let end = offset + sentence.len();
+        if ends_with_abbreviation(sentence) && end < content.len() {
+            pending = Some(start);
+        } else {
+            sentences.push((start, &content[start..end]));
+        }
+    }
+    sentences
+}
+
+impl TextChunker {
+    // 切句并去掉首尾空白；超过 max_words_per_chunk 的句子按词边界再切成多个片段
+    fn split_spans(&self, content: &str) -> Vec<Span> {
+        let mut spans = Vec::new();
+        for (offset, sentence) in sentences(content) {
+            let words: Vec<(usize, &str)> = sentence.unicode_word_indices().collect();
+            if words.is_empty() {
+                continue;
+            }
+            let trimmed_start = offset + (sentence.len() - sentence.trim_start().len());
+            let trimmed_end = offset + sentence.trim_end().len();
+
+            if words.len() <= self.max_words_per_chunk {
+                spans.push(Span {
+                    start: trimmed_start,
+                    end: trimmed_end,
+                    words: words.len(),
+                });
+                continue;
+            }
+
+            let pieces = words.chunks(self.max_words_per_chunk);
+            let piece_count = pieces.len();
+            for (p, piece) in pieces.enumerate() {
+                let start = if p == 0 {
+                    trimmed_start
+                } else {
+                    offset + piece[0].0
+                };
+                // 片段延伸到下一个片段的第一个词之前，保留词间标点
+                let end = if p + 1 == piece_count {
+                    trimmed_end
+                } else {
+                    let next = words[(p + 1) * self.max_words_per_chunk].0;
+                    offset + sentence[..next].trim_end().len()
+                };
+                spans.push(Span {
+                    start,
+                    end,
+                    words: piece.len(),
+                });
+            }
+        }
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunker(max_words: usize, overlap: usize, min_length: usize) -> TextChunker {
+        let mut chunker = TextChunker::new();
+        chunker.set_max_words_per_chunk(max_words);
+        chunker.set_overlap_sentences(overlap);
+        chunker.set_min_chunk_length(min_length);
+        chunker
+    }
+
+    fn texts(chunks: &[TextChunk]) -> Vec<String> {
+        chunks.iter().map(TextChunk::text).collect()
+    }
+
+    #[test]
+    fn cjk_sentences_and_words() {
+        let chunks = chunker(3, 0, 0).chunk_text("第一句。第二句！第三句？", None);
+        assert_eq!(texts(&chunks), ["第一句。", "第二句！", "第三句？"]);
+        // 每个表意字符计为一个词
+        assert!(chunks.iter().all(|c| c.word_count() == 3));
+
+        // 片假名连写按一个词计
+        let chunks = chunker(80, 0, 0).chunk_text("日本語のテキスト。中文句子。", None);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].word_count(), 9);
+    }
+
+    #[test]
+    fn abbreviations_do_not_end_sentences() {
+        let content =
+            "Mr. Smith met Dr. Jones. They talked. See e.g. Figure one. J. K. Rowling wrote it.";
+        let sentences: Vec<&str> = sentences(content).iter().map(|(_, s)| s.trim()).collect();
+        assert_eq!(
+            sentences,
+            [
+                "Mr. Smith met Dr. Jones.",
+                "They talked.",
+                "See e.g. Figure one.",
+                "J. K. Rowling wrote it."
+            ]
+        );
+        let chunks = chunker(5, 0, 0).chunk_text(content, None);
+        assert_eq!(texts(&chunks)[0], "Mr. Smith met Dr. Jones.");
+    }
+
+    #[test]
+    fn offsets_slice_back_to_chunk_text() {
+        let title = "  A page title  ";
+        let content = "  First sentence here. Second one, longer than the first!\\n\\n\
+            第三句在这里。 Fourth sentence with several more words in it? Fifth.  ";
+        let chunks = chunker(8, 1, 0).chunk_text(content, Some(title.to_string()));
+        assert_eq!(chunks[0].source(), "title");
+        assert_eq!(&title[chunks[0].start()..chunks[0].end()], chunks[0].text());
+        for chunk in &chunks[1..] {
+            assert_eq!(&content[chunk.start()..chunk.end()], chunk.text());
+            assert_eq!(chunk.source(), format!("content_chunk_{}", chunk.index()));
+        }
+    }
+
+    #[test]
+    fn overlap_repeats_trailing_sentences() {
+        let content = "One two three. Four five six. Seven eight nine. Ten eleven twelve.";
+        let chunks = chunker(6, 1, 0).chunk_text(content, None);
+        assert_eq!(
+            texts(&chunks),
+            [
+                "One two three. Four five six.",
+                "Four five six. Seven eight nine.",
+                "Seven eight nine. Ten eleven twelve.",
+                "Ten eleven twelve."
+            ]
+        );
+        let chunks = chunker(6, 0, 0).chunk_text(content, None);
+        assert_eq!(
+            texts(&chunks),
+            [
+                "One two three. Four five six.",
+                "Seven eight nine. Ten eleven twelve."
+            ]
+        );
+    }
+
+    #[test]
+    fn overlong_sentences_are_split_by_words() {
+        let content = "a b c d e f g h i j";
+        let chunks = chunker(4, 0, 0).chunk_text(content, None);
+        assert_eq!(texts(&chunks), ["a b c d", "e f g h", "i j"]);
+    }
+
+    #[test]
+    fn short_chunks_and_titles_are_dropped() {
+        // 与 TS 一致：长度不超过 min_chunk_length 的分块直接丢弃，不与相邻分块合并
+        let content = "Tiny. Long enough sentence here.";
+        let chunks = chunker(4, 0, 10).chunk_text(content, Some("Short".into()));
+        assert_eq!(texts(&chunks), ["Long enough sentence here."]);
+        assert_eq!(chunks[0].index(), 0);
+
+        // 长度按 UTF-16 码元计：两个 emoji 为 4 个码元
+        let chunks = chunker(80, 0, 3).chunk_text("😀😀", Some("😀😀😀".into()));
+        assert_eq!(texts(&chunks), ["😀😀😀"]);
+    }
+}
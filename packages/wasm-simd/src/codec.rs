@@ -0,0 +1,230 @@
+use std::fmt;
+
+use wasm_bindgen::JsValue;
+
+// 二进制格式解码错误
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DecodeError {
+    UnexpectedEof,
+    BadMagic,
+    UnsupportedVersion(u16),
+    ChecksumMismatch { expected: u32, found: u32 },
+    // 数据本身有效，但与调用方期望的模型 / 维度 / 度量不一致
+    Mismatch(String),
+    Invalid(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEof => write!(f, "unexpected end of data"),
+            DecodeError::BadMagic => write!(f, "unrecognized format (bad magic bytes)"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            DecodeError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "checksum mismatch (expected {:08x}, found {:08x}); data is corrupt",
+                expected, found
+            ),
+            DecodeError::Mismatch(msg) => write!(f, "mismatch: {}", msg),
+            DecodeError::Invalid(msg) => write!(f, "invalid data: {}", msg),
+        }
+    }
+}
+
+impl From<DecodeError> for JsValue {
+    fn from(err: DecodeError) -> JsValue {
+        JsValue::from_str(&err.to_string())
+    }
+}
+
+// CRC-32（IEEE 802.3，与 zlib 相同）
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc = CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+// 小端字节写入器
+#[derive(Default)]
+pub(crate) struct ByteWriter {
+    buf: Vec<u8>,
+}
+
+impl ByteWriter {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+
+    pub(crate) fn bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub(crate) fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub(crate) fn u16(&mut self, v: u16) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub(crate) fn u32_slice(&mut self, values: &[u32]) {
+        self.buf.reserve(values.len() * 4);
+        for v in values {
+            self.buf.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    // u32 长度前缀 + UTF-8 字节
+    pub(crate) fn str(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.bytes(value.as_bytes());
+    }
+
+    pub(crate) fn f32_slice(&mut self, values: &[f32]) {
+        self.buf.reserve(values.len() * 4);
+        for v in values {
+            self.buf.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+}
+
+// 小端字节读取器，越界时返回 UnexpectedEof
+pub(crate) struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        ByteReader { bytes, pos: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    // 尚未读取的字节数
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len().saturating_sub(self.pos)
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .ok_or(DecodeError::UnexpectedEof)?;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(DecodeError::UnexpectedEof)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DecodeError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    // 校验魔数和版本号
+    pub(crate) fn expect_header(
+        &mut self,
+        magic: &[u8; 4],
+        version: u16,
+    ) -> Result<(), DecodeError> {
+        if self.take(4).map_err(|_| DecodeError::BadMagic)? != magic {
+            return Err(DecodeError::BadMagic);
+        }
+        let found = self.u16()?;
+        if found != version {
+            return Err(DecodeError::UnsupportedVersion(found));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub(crate) fn u32_vec(&mut self, len: usize) -> Result<Vec<u32>, DecodeError> {
+        let bytes = self.take(len.checked_mul(4).ok_or(DecodeError::UnexpectedEof)?)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .collect())
+    }
+
+    pub(crate) fn str(&mut self) -> Result<&'a str, DecodeError> {
+        let len = self.u32()? as usize;
+        std::str::from_utf8(self.take(len)?)
+            .map_err(|_| DecodeError::Invalid("string is not valid UTF-8".into()))
+    }
+
+    pub(crate) fn f32_vec(&mut self, len: usize) -> Result<Vec<f32>, DecodeError> {
+        let bytes = self.take(len.checked_mul(4).ok_or(DecodeError::UnexpectedEof)?)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect())
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), DecodeError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::Invalid(format!(
+                "{} trailing byte(s)",
+                self.bytes.len() - self.pos
+            )))
+        }
+    }
+}
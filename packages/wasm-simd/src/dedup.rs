@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::bm25::tokenize;
+use crate::hash::hash64;
+use crate::rng::Rng;
+use crate::topk::{TopKHeap, TopKResults};
+
+// 固定种子，保证不同会话计算出的签名可以互相比较
+const PERMUTATION_SEED: u64 = 0x6d69_6e68_6173_6821;
+
+struct Fingerprint {
+    simhash: u64,
+    signature: Vec<u32>,
+}
+
+// 近重复文本索引：按词切分后取长度为 shingle_size 的滑动窗口（shingle），
+// 每篇文本计算 64 位 SimHash 和 MinHash 签名。MinHash 签名按 bands 分段做 LSH，
+// 只有至少一段完全相同的文本才会成为候选，再用签名估算 Jaccard 相似度
+#[wasm_bindgen]
+pub struct NearDuplicateIndex {
+    shingle_size: usize,
+    bands: usize,
+    rows: usize,
+    seeds: Vec<u64>,
+    entries: HashMap<u32, Fingerprint>,
+    // buckets[band] 为该段签名的哈希到 label 列表
+    buckets: Vec<HashMap<u64, Vec<u32>>>,
+}
+
+#[wasm_bindgen]
+impl NearDuplicateIndex {
+    // num_perm 会向下取整为 bands 的整数倍；bands 越多，能召回的 Jaccard 相似度下限越低
+    #[wasm_bindgen(constructor)]
+    pub fn new(shingle_size: usize, num_perm: usize, bands: usize) -> NearDuplicateIndex {
+        let num_perm = num_perm.max(1);
+        let bands = bands.clamp(1, num_perm);
+        let rows = num_perm / bands;
+        let mut rng = Rng::new(PERMUTATION_SEED);
+        NearDuplicateIndex {
+            shingle_size: shingle_size.max(1),
+            bands,
+            rows,
+            seeds: (0..bands * rows).map(|_| rng.next_u64()).collect(),
+            entries: HashMap::new(),
+            buckets: vec![HashMap::new(); bands],
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn shingle_size(&self) -> usize {
+        self.shingle_size
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn num_perm(&self) -> usize {
+        self.seeds.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bands(&self) -> usize {
+        self.bands
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn contains(&self, label: u32) -> bool {
+        self.entries.contains_key(&label)
+    }
+
+    // 加入文本（分块或整页），label 已存在时覆盖；文本没有任何词时返回 false
+    pub fn insert(&mut self, label: u32, text: &str) -> bool {
+        self.remove(label);
+        let fingerprint = match self.fingerprint(text) {
+            Some(fingerprint) => fingerprint,
+            None => return false,
+        };
+        for band in 0..self.bands {
+            let key = self.band_key(&fingerprint.signature, band);
+            self.buckets[band].entry(key).or_default().push(label);
+        }
+        self.entries.insert(label, fingerprint);
+        true
+    }
+
+    pub fn remove(&mut self, label: u32) -> bool {
+        let fingerprint = match self.entries.remove(&label) {
+            Some(fingerprint) => fingerprint,
+            None => return false,
+        };
+        for band in 0..self.bands {
+            let key = self.band_key(&fingerprint.signature, band);
+            if let Some(labels) = self.buckets[band].get_mut(&key) {
+                labels.retain(|&l| l != label);
+                if labels.is_empty() {
+                    self.buckets[band].remove(&key);
+                }
+            }
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+    }
+
+    // 文本没有任何词时返回 undefined
+    pub fn simhash(&self, text: &str) -> Option<u64> {
+        let shingles = self.shingles(text);
+        (!shingles.is_empty()).then(|| simhash(&shingles))
+    }
+
+    pub fn minhash(&self, text: &str) -> Vec<u32> {
+        self.signature(&self.shingles(text))
+    }
+
+    // 估算 Jaccard 相似度不低于 min_jaccard 的已有文本，按相似度降序，scores 为估算值。
+    // 只检查 LSH 候选，相似度明显低于 (1 / bands)^(1 / rows) 的文本可能漏召回
+    pub fn find_jaccard(&self, text: &str, min_jaccard: f32) -> TopKResults {
+        let signature = self.minhash(text);
+        if signature.is_empty() {
+            return TopKResults::default();
+        }
+        let mut candidates: Vec<u32> = (0..self.bands)
+            .filter_map(|band| self.buckets[band].get(&self.band_key(&signature, band)))
+            .flatten()
+            .copied()
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut heap = TopKHeap::new(candidates.len(), true);
+        for label in candidates {
+            let other = &self.entries[&label].signature;
+            let same = signature.iter().zip(other).filter(|(a, b)| a == b).count();
+            let jaccard = same as f32 / signature.len() as f32;
+            if jaccard >= min_jaccard {
+                heap.push(label, jaccard);
+            }
+        }
+        heap.into_results()
+    }
+
+    // SimHash 汉明距离不超过 max_distance 的已有文本，按距离升序，scores 为距离
+    pub fn find_hamming(&self, text: &str, max_distance: u32) -> TopKResults {
+        let fingerprint = match self.simhash(text) {
+            Some(fingerprint) => fingerprint,
+            None => return TopKResults::default(),
+        };
+        let mut heap = TopKHeap::new(self.entries.len(), false);
+        for (&label, entry) in &self.entries {
+            let distance = (entry.simhash ^ fingerprint).count_ones();
+            if distance <= max_distance {
+                heap.push(label, distance as f32);
+            }
+        }
+        heap.into_results()
+    }
+}
+
+impl NearDuplicateIndex {
+    // 去重后的 shingle 哈希；词数不足 shingle_size 时整段文本作为一个 shingle
+    fn shingles(&self, text: &str) -> Vec<u64> {
+        let tokens: Vec<u64> = tokenize(text)
+            .iter()
+            .map(|token| hash64(token.as_bytes(), 0))
+            .collect();
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+        let window = self.shingle_size.min(tokens.len());
+        let mut bytes = Vec::with_capacity(window * 8);
+        let mut shingles: Vec<u64> = tokens
+            .windows(window)
+            .map(|words| {
+                bytes.clear();
+                for word in words {
+                    bytes.extend_from_slice(&word.to_le_bytes());
+                }
+                hash64(&bytes, 0)
+            })
+            .collect();
+        shingles.sort_unstable();
+        shingles.dedup();
+        shingles
+    }
+
+    fn signature(&self, shingles: &[u64]) -> Vec<u32> {
+        if shingles.is_empty() {
+            return Vec::new();
+        }
+        self.seeds
+            .iter()
+            .map(|&seed| {
+                shingles
+                    .iter()
+                    .map(|shingle| (hash64(&shingle.to_le_bytes(), seed) >> 32) as u32)
+                    .min()
+                    .unwrap_or(u32::MAX)
+            })
+            .collect()
+    }
+
+    fn fingerprint(&self, text: &str) -> Option<Fingerprint> {
+        let shingles = self.shingles(text);
+        if shingles.is_empty() {
+            return None;
+        }
+        Some(Fingerprint {
+            simhash: simhash(&shingles),
+            signature: self.signature(&shingles),
+        })
+    }
+
+    fn band_key(&self, signature: &[u32], band: usize) -> u64 {
+        let rows = &signature[band * self.rows..(band + 1) * self.rows];
+        let bytes: Vec<u8> = rows.iter().flat_map(|v| v.to_le_bytes()).collect();
+        hash64(&bytes, band as u64)
+    }
+}
+
+// 每个 shingle 的哈希按位投票，票数为正的位置 1
+fn simhash(shingles: &[u64]) -> u64 {
+    let mut votes = [0i32; 64];
+    for &shingle in shingles {
+        for (bit, vote) in votes.iter_mut().enumerate() {
+            if shingle >> bit & 1 == 1 {
+                *vote += 1;
+            } else {
+                *vote -= 1;
+            }
+        }
+    }
+    votes
+        .iter()
+        .enumerate()
+        .fold(0u64, |hash, (bit, &vote)| hash | ((vote > 0) as u64) << bit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(seed: u64, count: usize) -> Vec<String> {
+        let mut rng = Rng::new(seed);
+        (0..count)
+            .map(|_| format!("w{}", rng.below(10_000)))
+            .collect()
+    }
+
+    fn index() -> NearDuplicateIndex {
+        NearDuplicateIndex::new(3, 128, 32)
+    }
+
+    fn labels_in_buckets(index: &NearDuplicateIndex, label: u32) -> usize {
+        index
+            .buckets
+            .iter()
+            .flat_map(|bucket| bucket.values())
+            .flatten()
+            .filter(|&&l| l == label)
+            .count()
+    }
+
+    #[test]
+    fn near_duplicates_are_found() {
+        let base = words(1, 200);
+        let mut edited = base.clone();
+        edited[100] = "changed".into();
+        edited.push("appended".into());
+
+        let mut index = index();
+        assert!(index.insert(1, &base.join(" ")));
+        assert!(index.insert(2, &words(2, 200).join(" ")));
+
+        let results = index.find_jaccard(&edited.join(" "), 0.8);
+        assert_eq!(results.indices(), [1]);
+        // 真实的 shingle Jaccard 约为 195 / 202
+        assert!(results.scores()[0] > 0.85, "{:?}", results.scores());
+
+        let results = index.find_hamming(&edited.join(" "), 8);
+        assert_eq!(results.indices(), [1]);
+
+        // 标点、大小写不影响分词，完全相同的文本签名一致
+        let shouted = base.join(", ").to_uppercase();
+        assert_eq!(index.find_jaccard(&shouted, 1.0).indices(), [1]);
+        assert_eq!(index.find_hamming(&shouted, 0).scores(), [0.0]);
+    }
+
+    #[test]
+    fn unrelated_documents_are_not_found() {
+        let mut index = index();
+        for label in 0..20 {
+            assert!(index.insert(label, &words(label as u64 + 10, 100).join(" ")));
+        }
+        let query = words(99, 100).join(" ");
+        assert!(index.find_jaccard(&query, 0.2).indices().is_empty());
+        assert!(index.find_hamming(&query, 8).indices().is_empty());
+        assert!(index.find_jaccard("", 0.0).indices().is_empty());
+        assert!(!index.insert(100, " ... "));
+    }
+
+    #[test]
+    fn remove_clears_every_bucket() {
+        let text = words(3, 50).join(" ");
+        let mut index = index();
+        assert!(index.insert(1, &text));
+        assert!(index.insert(2, &words(4, 50).join(" ")));
+        assert_eq!(labels_in_buckets(&index, 1), index.bands());
+
+        assert!(index.remove(1));
+        assert!(!index.remove(1));
+        assert!(!index.contains(1));
+        assert_eq!(labels_in_buckets(&index, 1), 0);
+        assert!(index.find_jaccard(&text, 0.0).indices().is_empty());
+        assert_eq!(index.find_hamming(&text, 64).indices(), [2]);
+
+        // 覆盖同一 label 时旧文本的桶也被清理
+        assert!(index.insert(2, &text));
+        assert_eq!(labels_in_buckets(&index, 2), index.bands());
+        assert_eq!(
+            index.buckets.iter().map(|b| b.len()).sum::<usize>(),
+            index.bands()
+        );
+        assert_eq!(index.find_jaccard(&text, 1.0).indices(), [2]);
+
+        index.remove(2);
+        assert!(index.buckets.iter().all(|bucket| bucket.is_empty()));
+    }
+}
@@ -0,0 +1,365 @@
+use wasm_bindgen::prelude::*;
+
+use crate::codec::{crc32, ByteReader, ByteWriter, DecodeError};
+use crate::snapshot::{verify_checksum, Snapshot};
+
+const DELTA_MAGIC: &[u8; 4] = b"SMDL";
+const DELTA_VERSION: u16 = 1;
+const HEADER_LEN: usize = 10;
+
+const RECORD_ADD: u8 = 1;
+const RECORD_REMOVE: u8 = 2;
+const RECORD_SET_METADATA: u8 = 3;
+const RECORD_CLEAR_METADATA: u8 = 4;
+const RECORD_PUT_DOCUMENT: u8 = 5;
+const RECORD_REMOVE_TAB: u8 = 6;
+const RECORD_REMOVE_URL: u8 = 7;
+
+const DEFAULT_COMPACTION_THRESHOLD: usize = 4 << 20;
+
+// 快照之后的追加式变更日志，加载时在快照上回放。
+//
+// 布局（小端）：magic "SMDL" | u16 版本 | u32 基准快照的校验和 | 记录...
+// 每条记录：u8 类型 | u32 内容长度 | 内容 | u32 CRC32（覆盖类型、长度和内容）
+#[wasm_bindgen]
+pub struct DeltaLog {
+    base_checksum: u32,
+    bytes: Vec<u8>,
+    records: usize,
+    // 已通过 take_pending 交出的字节数
+    flushed: usize,
+    compaction_threshold: usize,
+    discarded: usize,
+}
+
+#[wasm_bindgen]
+impl DeltaLog {
+    // 创建一个基于 base_snapshot（Snapshot.to_bytes 的结果）的空日志
+    #[wasm_bindgen(constructor)]
+    pub fn new(base_snapshot: &[u8]) -> Result<DeltaLog, JsValue> {
+        Ok(DeltaLog::empty(verify_checksum(base_snapshot)?))
+    }
+
+    // 末尾不完整或校验失败的记录（写入中断）会被丢弃，数量见 discarded_bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<DeltaLog, JsValue> {
+        Ok(Self::decode_bytes(bytes)?)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn base_checksum(&self) -> u32 {
+        self.base_checksum
+    }
+
+    // 记录条数
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.records
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn byte_length(&self) -> usize {
+        self.bytes.len()
+    }
+
+    // 加载时丢弃的尾部字节数；大于 0 时应当用 to_bytes 重写已存储的日志
+    #[wasm_bindgen(getter)]
+    pub fn discarded_bytes(&self) -> usize {
+        self.discarded
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn compaction_threshold(&self) -> usize {
+        self.compaction_threshold
+    }
+
+    // 日志字节数超过该值时 needs_compaction 为 true
+    pub fn set_compaction_threshold(&mut self, bytes: usize) {
+        self.compaction_threshold = bytes;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn needs_compaction(&self) -> bool {
+        self.bytes.len() > self.compaction_threshold
+    }
+
+    pub fn add(&mut self, label: u32, vector: &[f32]) {
+        let mut payload = ByteWriter::new();
+        payload.u32(label);
+        payload.f32_slice(vector);
+        self.append(RECORD_ADD, &payload.into_bytes());
+    }
+
+    pub fn remove(&mut self, label: u32) {
+        self.append(RECORD_REMOVE, &label.to_le_bytes());
+    }
+
+    pub fn set_metadata(&mut self, label: u32, value: &str) {
+        let mut payload = ByteWriter::new();
+        payload.u32(label);
+        payload.bytes(value.as_bytes());
+        self.append(RECORD_SET_METADATA, &payload.into_bytes());
+    }
+
+    pub fn clear_metadata(&mut self, label: u32) {
+        self.append(RECORD_CLEAR_METADATA, &label.to_le_bytes());
+    }
+
+    // 写入或覆盖 label 对应的文档记录（见 DocumentStore.insert），label 须已有向量
+    #[allow(clippy::too_many_arguments)]
+    pub fn put_document(
+        &mut self,
+        label: u32,
+        tab_id: i32,
+        url: &str,
+        title: &str,
+        chunk_index: u32,
+        text: &str,
+        timestamp: f64,
+    ) {
+        let mut payload = ByteWriter::new();
+        payload.u32(label);
+        payload.u32(tab_id as u32);
+        payload.u32(chunk_index);
+        payload.u64(timestamp.to_bits());
+        payload.str(url);
+        payload.str(title);
+        payload.str(text);
+        self.append(RECORD_PUT_DOCUMENT, &payload.into_bytes());
+    }
+
+    // 回放时删除该 tab 的全部文档及其向量和元数据
+    pub fn remove_tab(&mut self, tab_id: i32) {
+        self.append(RECORD_REMOVE_TAB, &(tab_id as u32).to_le_bytes());
+    }
+
+    pub fn remove_url(&mut self, url: &str) {
+        let mut payload = ByteWriter::new();
+        payload.str(url);
+        self.append(RECORD_REMOVE_URL, &payload.into_bytes());
+    }
+
+    // 上次调用之后新增的字节，追加到已存储的日志末尾即可
+    pub fn take_pending(&mut self) -> Vec<u8> {
+        let pending = self.bytes[self.flushed..].to_vec();
+        self.flushed = self.bytes.len();
+        pending
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+
+    // 在基准快照上按顺序回放所有记录
+    pub fn replay(&self, base_snapshot: &[u8]) -> Result<Snapshot, JsValue> {
+        Ok(self.replay_onto(base_snapshot)?)
+    }
+
+    // 把日志折叠进新的快照并清空日志，返回新快照的字节；
+    // 之后 take_pending 会返回新日志的头部，调用方应覆盖而不是追加已存储的日志
+    pub fn compact(&mut self, base_snapshot: &[u8]) -> Result<Vec<u8>, JsValue> {
+        let snapshot = self.replay_onto(base_snapshot)?.to_bytes();
+        let threshold = self.compaction_threshold;
+        *self = DeltaLog::empty(verify_checksum(&snapshot)?);
+        self.compaction_threshold = threshold;
+        Ok(snapshot)
+    }
+}
+
+impl DeltaLog {
+    fn empty(base_checksum: u32) -> DeltaLog {
+        let mut header = ByteWriter::new();
+        header.bytes(DELTA_MAGIC);
+        header.u16(DELTA_VERSION);
+        header.u32(base_checksum);
+        DeltaLog {
+            base_checksum,
+            bytes: header.into_bytes(),
+            records: 0,
+            flushed: 0,
+            compaction_threshold: DEFAULT_COMPACTION_THRESHOLD,
+            discarded: 0,
+        }
+    }
+
+    fn append(&mut self, kind: u8, payload: &[u8]) {
+        let start = self.bytes.len();
+        self.bytes.push(kind);
+        self.bytes
+            .extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        self.bytes.extend_from_slice(payload);
+        let checksum = crc32(&self.bytes[start..]);
+        self.bytes.extend_from_slice(&checksum.to_le_bytes());
+        self.records += 1;
+    }
+
+    // 逐条读取记录，返回 (类型, 内容)；遇到不完整或损坏的记录时停止
+    fn records(&self) -> impl Iterator<Item = (u8, &[u8])> + '_ {
+        let mut pos = HEADER_LEN;
+        std::iter::from_fn(move || {
+            let (record, next) = read_record(&self.bytes, pos)?;
+            pos = next;
+            Some(record)
+        })
+    }
+
+    pub(crate) fn decode_bytes(bytes: &[u8]) -> Result<DeltaLog, DecodeError> {
+        let mut reader = ByteReader::new(bytes);
+        reader.expect_header(DELTA_MAGIC, DELTA_VERSION)?;
+        let mut log = DeltaLog::empty(reader.u32()?);
+
+        let mut pos = HEADER_LEN;
+        while let Some((_, next)) = read_record(bytes, pos) {
+            pos = next;
+            log.records += 1;
+        }
+        log.bytes = bytes[..pos].to_vec();
+        log.flushed = pos;
+        log.discarded = bytes.len() - pos;
+        Ok(log)
+    }
+
+    pub(crate) fn replay_onto(&self, base_snapshot: &[u8]) -> Result<Snapshot, DecodeError> {
+        let checksum = verify_checksum(base_snapshot)?;
+        if checksum != self.base_checksum {
+            return Err(DecodeError::Mismatch(format!(
+                "log was written against snapshot {:08x}, got {:08x}",
+                self.base_checksum, checksum
+            )));
+        }
+        let mut snapshot = Snapshot::decode_bytes(base_snapshot)?;
+        for (index, (kind, payload)) in self.records().enumerate() {
+            apply_record(&mut snapshot, kind, payload)
+                .map_err(|err| DecodeError::Invalid(format!("log record {}: {}", index, err)))?;
+        }
+        Ok(snapshot)
+    }
+}
+
+fn read_record(bytes: &[u8], pos: usize) -> Option<((u8, &[u8]), usize)> {
+    let mut reader = ByteReader::new(bytes.get(pos..)?);
+    let kind = reader.u8().ok()?;
+    let len = reader.u32().ok()? as usize;
+    let payload = reader.take(len).ok()?;
+    let checksum = reader.u32().ok()?;
+    let end = pos + 5 + len;
+    if crc32(&bytes[pos..end]) != checksum {
+        return None;
+    }
+    Some(((kind, payload), end + 4))
+}
+
+fn apply_record(snapshot: &mut Snapshot, kind: u8, payload: &[u8]) -> Result<(), DecodeError> {
+    let mut reader = ByteReader::new(payload);
+    match kind {
+        RECORD_ADD => {
+            let label = reader.u32()?;
+            let rest = payload.len() - 4;
+            if !rest.is_multiple_of(4) {
+                return Err(DecodeError::Invalid(
+                    "vector length is not a multiple of 4".into(),
+                ));
+            }
+            let vector = reader.f32_vec(rest / 4)?;
+            snapshot.apply_add(label, &vector)
+        }
+        RECORD_REMOVE => {
+            let label = reader.u32()?;
+            reader.finish()?;
+            snapshot.apply_remove(label);
+            Ok(())
+        }
+        RECORD_SET_METADATA => {
+            let label = reader.u32()?;
+            let value = std::str::from_utf8(&payload[4..])
+                .map_err(|_| DecodeError::Invalid("metadata is not valid UTF-8".into()))?;
+            snapshot.apply_metadata(label, Some(value.to_string()))
+        }
+        RECORD_CLEAR_METADATA => {
+            let label = reader.u32()?;
+            reader.finish()?;
+            snapshot.apply_metadata(label, None)
+        }
+        RECORD_PUT_DOCUMENT => {
+            let label = reader.u32()?;
+            let tab_id = reader.u32()? as i32;
+            let chunk_index = reader.u32()?;
+            let timestamp = f64::from_bits(reader.u64()?);
+            let url = reader.str()?;
+            let title = reader.str()?;
+            let text = reader.str()?;
+            reader.finish()?;
+            snapshot.apply_document(label, tab_id, url, title, chunk_index, text, timestamp)
+        }
+        RECORD_REMOVE_TAB => {
+            let tab_id = reader.u32()? as i32;
+            reader.finish()?;
+            snapshot.apply_remove_tab(tab_id);
+            Ok(())
+        }
+        RECORD_REMOVE_URL => {
+            let url = reader.str()?;
+            reader.finish()?;
+            snapshot.apply_remove_url(url);
+            Ok(())
+        }
+        other => Err(DecodeError::Invalid(format!(
+            "unknown record type {}",
+            other
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documents::DocumentStore;
+    use crate::metric::Metric;
+    use crate::store::VectorStore;
+
+    fn base_snapshot() -> Vec<u8> {
+        let mut store = VectorStore::new(2, Metric::Cosine);
+        store.add(1, &[1.0, 0.0]);
+        store.add(2, &[0.0, 1.0]);
+        let mut documents = DocumentStore::new();
+        documents.insert(1, 10, "https://a.example/", "A", 0, "first", 1.0);
+        documents.insert(2, 20, "https://b.example/", "B", 0, "second", 2.0);
+        let mut snapshot = Snapshot::from_store("model", &store);
+        assert!(snapshot.set_documents(&documents));
+        snapshot.to_bytes()
+    }
+
+    #[test]
+    fn replay_and_compact_keep_documents_added_after_snapshot() {
+        let base = base_snapshot();
+        let mut log = DeltaLog::new(&base).unwrap();
+        log.add(3, &[1.0, 1.0]);
+        log.put_document(3, 10, "https://a.example/next", "A2", 0, "third", 3.0);
+        log.add(4, &[0.5, 1.0]);
+        log.put_document(4, 30, "https://c.example/", "C", 0, "fourth", 4.0);
+        // 覆盖快照中已有的文档
+        log.put_document(2, 20, "https://b.example/", "B", 1, "second v2", 5.0);
+        log.remove_url("https://c.example/");
+
+        let compacted = log.compact(&base).unwrap();
+        let snapshot = Snapshot::from_bytes(&compacted).unwrap();
+        let documents = snapshot.documents().unwrap();
+        assert_eq!(snapshot.labels(), vec![1, 2, 3]);
+        assert_eq!(documents.labels(), vec![1, 2, 3]);
+        assert_eq!(documents.labels_for_tab(10), vec![1, 3]);
+        assert_eq!(documents.get(3).unwrap().text(), "third");
+        assert_eq!(documents.get(2).unwrap().text(), "second v2");
+
+        log.remove_tab(10);
+        let snapshot = log.replay_onto(&compacted).unwrap();
+        assert_eq!(snapshot.labels(), vec![2]);
+        assert_eq!(snapshot.documents().unwrap().labels(), vec![2]);
+    }
+
+    #[test]
+    fn document_for_unknown_label_is_rejected() {
+        let base = base_snapshot();
+        let mut log = DeltaLog::new(&base).unwrap();
+        log.put_document(9, 10, "https://a.example/", "A", 0, "orphan", 1.0);
+        assert!(log.replay_onto(&base).is_err());
+    }
+}
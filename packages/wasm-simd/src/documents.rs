@@ -0,0 +1,499 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use wasm_bindgen::prelude::*;
+
+use crate::codec::{ByteReader, ByteWriter, DecodeError};
+
+// 编码后每条记录除正文外的固定字节数：label、tab、块序号、时间戳、url、标题、正文长度
+const RECORD_FIXED_BYTES: usize = 4 + 4 + 4 + 8 + 4 + 4 + 4;
+// 字符串表和记录表各自的 u32 计数
+const HEADER_BYTES: usize = 8;
+
+// 一条文档记录的只读视图
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct DocumentRecord {
+    label: u32,
+    tab_id: i32,
+    url: String,
+    title: String,
+    chunk_index: u32,
+    text: String,
+    timestamp: f64,
+}
+
+#[wasm_bindgen]
+impl DocumentRecord {
+    #[wasm_bindgen(getter)]
+    pub fn label(&self) -> u32 {
+        self.label
+    }
+
+    #[wasm_bindgen(getter = tabId)]
+    pub fn tab_id(&self) -> i32 {
+        self.tab_id
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    #[wasm_bindgen(getter = chunkIndex)]
+    pub fn chunk_index(&self) -> u32 {
+        self.chunk_index
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn text(&self) -> String {
+        self.text.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn timestamp(&self) -> f64 {
+        self.timestamp
+    }
+}
+
+// 带引用计数的字符串表：同一页面的多个分块共享 url 和标题
+#[derive(Clone, Default)]
+struct StringPool {
+    strings: Vec<String>,
+    refs: Vec<u32>,
+    ids: HashMap<String, u32>,
+    free: Vec<u32>,
+}
+
+impl StringPool {
+    fn get(&self, id: u32) -> &str {
+        &self.strings[id as usize]
+    }
+
+    fn find(&self, value: &str) -> Option<u32> {
+        self.ids.get(value).copied()
+    }
+
+    // 返回字符串 id，以及是否为新加入的字符串
+    fn intern(&mut self, value: &str) -> (u32, bool) {
+        if let Some(id) = self.find(value) {
+            self.refs[id as usize] += 1;
+            return (id, false);
+        }
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.strings[id as usize] = value.to_string();
+                self.refs[id as usize] = 1;
+                id
+            }
+            None => {
+                self.strings.push(value.to_string());
+                self.refs.push(1);
+                (self.strings.len() - 1) as u32
+            }
+        };
+        self.ids.insert(value.to_string(), id);
+        (id, true)
+    }
+
+    // 引用计数归零时释放字符串并返回其长度
+    fn release(&mut self, id: u32) -> Option<usize> {
+        let refs = &mut self.refs[id as usize];
+        *refs -= 1;
+        if *refs > 0 {
+            return None;
+        }
+        let value = std::mem::take(&mut self.strings[id as usize]);
+        self.ids.remove(&value);
+        self.free.push(id);
+        Some(value.len())
+    }
+}
+
+#[derive(Clone)]
+struct Record {
+    tab_id: i32,
+    url: u32,
+    title: u32,
+    chunk_index: u32,
+    text: String,
+    timestamp: f64,
+}
+
+// label → 文档记录（分块正文、url、标题、tabId、时间戳），取代 VectorDatabase 中的
+// documents / tabDocuments 两个 Map；按 tab 和 url 维护二级索引，
+// byte_length 为 to_bytes 结果的精确字节数
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct DocumentStore {
+    records: BTreeMap<u32, Record>,
+    strings: StringPool,
+    by_tab: HashMap<i32, BTreeSet<u32>>,
+    by_url: HashMap<u32, BTreeSet<u32>>,
+    byte_length: usize,
+}
+
+impl Default for DocumentStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl DocumentStore {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> DocumentStore {
+        DocumentStore {
+            records: BTreeMap::new(),
+            strings: StringPool::default(),
+            by_tab: HashMap::new(),
+            by_url: HashMap::new(),
+            byte_length: HEADER_BYTES,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.records.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn byte_length(&self) -> usize {
+        self.byte_length
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tab_count(&self) -> usize {
+        self.by_tab.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn url_count(&self) -> usize {
+        self.by_url.len()
+    }
+
+    pub fn contains(&self, label: u32) -> bool {
+        self.records.contains_key(&label)
+    }
+
+    // 写入记录，label 已存在时覆盖；返回是否为新增
+    #[allow(clippy::too_many_arguments)]
+    pub fn insert(
+        &mut self,
+        label: u32,
+        tab_id: i32,
+        url: &str,
+        title: &str,
+        chunk_index: u32,
+        text: &str,
+        timestamp: f64,
+    ) -> bool {
+        let replaced = self.remove(label);
+        let url = self.intern(url);
+        let title = self.intern(title);
+        self.byte_length += RECORD_FIXED_BYTES + text.len();
+        self.by_tab.entry(tab_id).or_default().insert(label);
+        self.by_url.entry(url).or_default().insert(label);
+        self.records.insert(
+            label,
+            Record {
+                tab_id,
+                url,
+                title,
+                chunk_index,
+                text: text.to_string(),
+                timestamp,
+            },
+        );
+        !replaced
+    }
+
+    pub fn remove(&mut self, label: u32) -> bool {
+        let record = match self.records.remove(&label) {
+            Some(record) => record,
+            None => return false,
+        };
+        self.byte_length -= RECORD_FIXED_BYTES + record.text.len();
+        if let Some(labels) = self.by_tab.get_mut(&record.tab_id) {
+            labels.remove(&label);
+            if labels.is_empty() {
+                self.by_tab.remove(&record.tab_id);
+            }
+        }
+        if let Some(labels) = self.by_url.get_mut(&record.url) {
+            labels.remove(&label);
+            if labels.is_empty() {
+                self.by_url.remove(&record.url);
+            }
+        }
+        self.release(record.url);
+        self.release(record.title);
+        true
+    }
+
+    // 删除某个 tab 的全部记录，返回被删除的 label（用于同步删除向量）
+    pub fn remove_tab(&mut self, tab_id: i32) -> Vec<u32> {
+        let labels = self.labels_for_tab(tab_id);
+        for &label in &labels {
+            self.remove(label);
+        }
+        labels
+    }
+
+    pub fn remove_url(&mut self, url: &str) -> Vec<u32> {
+        let labels = self.labels_for_url(url);
+        for &label in &labels {
+            self.remove(label);
+        }
+        labels
+    }
+
+    pub fn clear(&mut self) {
+        *self = DocumentStore::new();
+    }
+
+    pub fn get(&self, label: u32) -> Option<DocumentRecord> {
+        self.records.get(&label).map(|record| DocumentRecord {
+            label,
+            tab_id: record.tab_id,
+            url: self.strings.get(record.url).to_string(),
+            title: self.strings.get(record.title).to_string(),
+            chunk_index: record.chunk_index,
+            text: record.text.clone(),
+            timestamp: record.timestamp,
+        })
+    }
+
+    // 升序
+    pub fn labels(&self) -> Vec<u32> {
+        self.records.keys().copied().collect()
+    }
+
+    pub fn labels_for_tab(&self, tab_id: i32) -> Vec<u32> {
+        self.by_tab
+            .get(&tab_id)
+            .map_or_else(Vec::new, |labels| labels.iter().copied().collect())
+    }
+
+    pub fn labels_for_url(&self, url: &str) -> Vec<u32> {
+        self.strings
+            .find(url)
+            .and_then(|id| self.by_url.get(&id))
+            .map_or_else(Vec::new, |labels| labels.iter().copied().collect())
+    }
+
+    pub fn tab_ids(&self) -> Vec<i32> {
+        let mut tabs: Vec<i32> = self.by_tab.keys().copied().collect();
+        tabs.sort_unstable();
+        tabs
+    }
+
+    // 布局（小端）：u32 字符串数 | 字符串... | u32 记录数 |
+    // 记录（u32 label | i32 tab | u32 块序号 | f64 时间戳 | u32 url | u32 标题 | 正文）...
+    // 记录按 label 升序，字符串按首次引用的顺序编号
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        self.write(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<DocumentStore, JsValue> {
+        let mut reader = ByteReader::new(bytes);
+        let store = Self::read(&mut reader)?;
+        reader.finish()?;
+        Ok(store)
+    }
+}
+
+impl DocumentStore {
+    fn intern(&mut self, value: &str) -> u32 {
+        let (id, added) = self.strings.intern(value);
+        if added {
+            self.byte_length += 4 + value.len();
+        }
+        id
+    }
+
+    fn release(&mut self, id: u32) {
+        if let Some(len) = self.strings.release(id) {
+            self.byte_length -= 4 + len;
+        }
+    }
+
+    // (label, tabId, url, 时间戳)，按 label 升序
+    pub(crate) fn sources(&self) -> impl Iterator<Item = (u32, i32, &str, f64)> + '_ {
+        self.records.iter().map(|(&label, record)| {
+            (
+                label,
+                record.tab_id,
+                self.strings.get(record.url),
+                record.timestamp,
+            )
+        })
+    }
+
+    pub(crate) fn write(&self, writer: &mut ByteWriter) {
+        // 把内存中的字符串 id 重新编号为连续的序号
+        let mut order: HashMap<u32, u32> = HashMap::new();
+        let mut strings: Vec<u32> = Vec::new();
+        for record in self.records.values() {
+            for id in [record.url, record.title] {
+                order.entry(id).or_insert_with(|| {
+                    strings.push(id);
+                    (strings.len() - 1) as u32
+                });
+            }
+        }
+
+        writer.u32(strings.len() as u32);
+        for &id in &strings {
+            writer.str(self.strings.get(id));
+        }
+        writer.u32(self.records.len() as u32);
+        for (&label, record) in &self.records {
+            writer.u32(label);
+            writer.u32(record.tab_id as u32);
+            writer.u32(record.chunk_index);
+            writer.u64(record.timestamp.to_bits());
+            writer.u32(order[&record.url]);
+            writer.u32(order[&record.title]);
+            writer.str(&record.text);
+        }
+    }
+
+    pub(crate) fn read(reader: &mut ByteReader) -> Result<DocumentStore, DecodeError> {
+        let invalid = |msg: String| DecodeError::Invalid(format!("documents: {}", msg));
+        let string_count = reader.u32()? as usize;
+        let mut strings = Vec::with_capacity(string_count.min(1 << 16));
+        let mut seen = HashMap::new();
+        for i in 0..string_count {
+            let value = reader.str()?;
+            if seen.insert(value, i).is_some() {
+                return Err(invalid(format!("string {:?} appears twice", value)));
+            }
+            strings.push(value);
+        }
+
+        let mut store = DocumentStore::new();
+        let mut used = vec![false; string_count];
+        let record_count = reader.u32()?;
+        for _ in 0..record_count {
+            let label = reader.u32()?;
+            let tab_id = reader.u32()? as i32;
+            let chunk_index = reader.u32()?;
+            let timestamp = f64::from_bits(reader.u64()?);
+            let url = reader.u32()? as usize;
+            let title = reader.u32()? as usize;
+            let text = reader.str()?;
+            if url >= string_count || title >= string_count {
+                return Err(invalid(format!(
+                    "label {} references a missing string",
+                    label
+                )));
+            }
+            used[url] = true;
+            used[title] = true;
+            if !store.insert(
+                label,
+                tab_id,
+                strings[url],
+                strings[title],
+                chunk_index,
+                text,
+                timestamp,
+            ) {
+                return Err(invalid(format!("label {} appears twice", label)));
+            }
+        }
+        if used.contains(&false) {
+            return Err(invalid("string table has unreferenced entries".into()));
+        }
+        Ok(store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_exact_length(store: &DocumentStore) {
+        assert_eq!(store.byte_length(), store.to_bytes().len());
+    }
+
+    fn decode(bytes: &[u8]) -> Result<DocumentStore, DecodeError> {
+        let mut reader = ByteReader::new(bytes);
+        let store = DocumentStore::read(&mut reader)?;
+        reader.finish()?;
+        Ok(store)
+    }
+
+    fn sample() -> DocumentStore {
+        let mut store = DocumentStore::new();
+        assert_exact_length(&store);
+        for label in 0..6 {
+            let url = format!("https://example.com/{}", label % 3);
+            let title = format!("页面 {}", label % 2);
+            let text = "正文 ".repeat(label as usize + 1);
+            assert!(store.insert(label, label as i32 % 2, &url, &title, label, &text, 1e12));
+            assert_exact_length(&store);
+        }
+        store
+    }
+
+    #[test]
+    fn byte_length_tracks_encoded_size() {
+        let mut store = sample();
+
+        // 覆盖同一 label：正文变长、url 换成新字符串、旧字符串仍被其他记录引用
+        assert!(!store.insert(
+            1,
+            7,
+            "https://other.example/",
+            "新标题",
+            0,
+            "更长的正文内容",
+            2.0
+        ));
+        assert_exact_length(&store);
+        assert_eq!(store.length(), 6);
+
+        assert_eq!(store.remove_tab(0), vec![0, 2, 4]);
+        assert_exact_length(&store);
+        assert_eq!(store.remove_url("https://other.example/"), vec![1]);
+        assert_exact_length(&store);
+        assert_eq!(store.labels(), vec![3, 5]);
+        assert!(store.remove_tab(0).is_empty());
+
+        store.remove(3);
+        store.remove(5);
+        assert_exact_length(&store);
+        assert_eq!(store.byte_length(), HEADER_BYTES);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut store = sample();
+        store.remove(2);
+        let bytes = store.to_bytes();
+        let restored = decode(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.byte_length(), bytes.len());
+        assert_eq!(restored.labels(), store.labels());
+        assert_eq!(restored.tab_ids(), vec![0, 1]);
+        assert_eq!(
+            restored.labels_for_url("https://example.com/0"),
+            store.labels_for_url("https://example.com/0")
+        );
+        let record = restored.get(5).unwrap();
+        assert_eq!(record.url(), "https://example.com/2");
+        assert_eq!(record.title(), "页面 1");
+        assert_eq!(record.text(), "正文 ".repeat(6));
+        assert_eq!(record.timestamp(), 1e12);
+
+        assert!(decode(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
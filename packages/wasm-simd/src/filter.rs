@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::documents::DocumentStore;
+use crate::hash::host_hash;
+
+#[derive(Clone, Debug)]
+struct Attributes {
+    tags: Vec<u32>,
+    timestamp: f64,
+    host: u64,
+}
+
+// 每个向量的过滤属性：整数标签（如 tabId）、时间戳（毫秒）和 URL 的 host 哈希，按 label 存放
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct VectorAttributes {
+    entries: HashMap<u32, Attributes>,
+}
+
+#[wasm_bindgen]
+impl VectorAttributes {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> VectorAttributes {
+        VectorAttributes::default()
+    }
+
+    // 由文档记录生成属性：标签为 tabId，时间戳和 host 取自记录本身。
+    // 文档记录随快照和增量日志持久化，加载后用它重建属性即可，无需单独保存
+    pub fn from_documents(documents: &DocumentStore) -> VectorAttributes {
+        let mut attributes = VectorAttributes::new();
+        attributes.sync_documents(documents);
+        attributes
+    }
+
+    // 用文档记录覆盖同名 label 的属性，不在 documents 中的 label 保持不变
+    pub fn sync_documents(&mut self, documents: &DocumentStore) {
+        for (label, tab_id, url, timestamp) in documents.sources() {
+            self.set(label, &[tab_id as u32], timestamp, url);
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn contains(&self, label: u32) -> bool {
+        self.entries.contains_key(&label)
+    }
+
+    // 设置或覆盖 label 的属性，url 也可以直接传 host
+    pub fn set(&mut self, label: u32, tags: &[u32], timestamp: f64, url: &str) {
+        self.entries.insert(
+            label,
+            Attributes {
+                tags: tags.to_vec(),
+                timestamp,
+                host: host_hash(url),
+            },
+        );
+    }
+
+    pub fn remove(&mut self, label: u32) -> bool {
+        self.entries.remove(&label).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn tags(&self, label: u32) -> Option<Vec<u32>> {
+        self.entries.get(&label).map(|a| a.tags.clone())
+    }
+
+    pub fn timestamp(&self, label: u32) -> Option<f64> {
+        self.entries.get(&label).map(|a| a.timestamp)
+    }
+}
+
+// 搜索过滤条件，各条件之间为“且”；没有设置任何条件时所有向量都匹配
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct SearchFilter {
+    // 命中任意一个标签即可
+    tags: Vec<u32>,
+    // 闭区间
+    time_from: f64,
+    time_to: f64,
+    hosts: Vec<u64>,
+}
+
+impl Default for SearchFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl SearchFilter {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SearchFilter {
+        SearchFilter {
+            tags: Vec::new(),
+            time_from: f64::NEG_INFINITY,
+            time_to: f64::INFINITY,
+            hosts: Vec::new(),
+        }
+    }
+
+    // 空列表表示不按标签过滤
+    pub fn set_tags(&mut self, tags: &[u32]) {
+        self.tags = tags.to_vec();
+    }
+
+    // 只保留时间戳在 [from, to] 内的向量，传 Infinity 表示不限上界
+    pub fn set_time_range(&mut self, from: f64, to: f64) {
+        self.time_from = from;
+        self.time_to = to;
+    }
+
+    pub fn clear_time_range(&mut self) {
+        self.time_from = f64::NEG_INFINITY;
+        self.time_to = f64::INFINITY;
+    }
+
+    // 只保留 host 与任一 URL（或 host）相同的向量，空列表表示不按 host 过滤
+    pub fn set_hosts(&mut self, urls: Vec<String>) {
+        self.hosts = urls.iter().map(|url| host_hash(url)).collect();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.hosts.is_empty()
+            && self.time_from == f64::NEG_INFINITY
+            && self.time_to == f64::INFINITY
+    }
+}
+
+impl SearchFilter {
+    // 没有属性的 label 只在过滤条件为空时匹配
+    pub(crate) fn matches(&self, attributes: &VectorAttributes, label: u32) -> bool {
+        let attrs = match attributes.entries.get(&label) {
+            Some(attrs) => attrs,
+            None => return self.is_empty(),
+        };
+        (self.tags.is_empty() || attrs.tags.iter().any(|tag| self.tags.contains(tag)))
+            && (self.hosts.is_empty() || self.hosts.contains(&attrs.host))
+            && attrs.timestamp >= self.time_from
+            && attrs.timestamp <= self.time_to
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_derived_from_documents_filter_by_tab_host_and_time() {
+        let mut documents = DocumentStore::new();
+        documents.insert(1, 7, "https://a.example/x", "A", 0, "one", 100.0);
+        documents.insert(2, 7, "https://b.example/y", "B", 0, "two", 200.0);
+        documents.insert(3, -1, "https://a.example:8080/z", "A", 1, "three", 300.0);
+        let attributes = VectorAttributes::from_documents(&documents);
+        assert_eq!(attributes.length(), 3);
+        assert_eq!(attributes.tags(3), Some(vec![-1i32 as u32]));
+        assert_eq!(attributes.timestamp(2), Some(200.0));
+
+        let matching = |filter: &SearchFilter| -> Vec<u32> {
+            (1..=3)
+                .filter(|&label| filter.matches(&attributes, label))
+                .collect()
+        };
+        let mut filter = SearchFilter::new();
+        filter.set_tags(&[7]);
+        assert_eq!(matching(&filter), vec![1, 2]);
+        filter.set_hosts(vec!["a.example".into()]);
+        assert_eq!(matching(&filter), vec![1]);
+
+        let mut filter = SearchFilter::new();
+        filter.set_hosts(vec!["https://A.example/".into()]);
+        filter.set_time_range(150.0, f64::INFINITY);
+        assert_eq!(matching(&filter), vec![3]);
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use crate::topk::{TopKHeap, TopKResults};
+
+// 倒数排名融合（RRF）：score = Σ 1 / (rrf_k + rank)，rank 从 1 开始。
+// 两个列表均按从好到差排序，只依赖名次，不需要分数可比
+pub(crate) fn reciprocal_rank_fusion(
+    lexical_ids: &[u32],
+    semantic_ids: &[u32],
+    rrf_k: f32,
+    k: usize,
+) -> TopKResults {
+    let rrf_k = if rrf_k > 0.0 { rrf_k } else { 60.0 };
+    let mut fused: HashMap<u32, f32> = HashMap::new();
+    for ids in [lexical_ids, semantic_ids] {
+        for (rank, &id) in ids.iter().enumerate() {
+            *fused.entry(id).or_insert(0.0) += 1.0 / (rrf_k + rank as f32 + 1.0);
+        }
+    }
+    top_k(fused, k)
+}
+
+// 加权分数融合：两组分数各自 min-max 归一化到 [0, 1] 后按
+// semantic_weight * 语义 + (1 - semantic_weight) * 词法 相加，缺失的一侧记 0。
+// 两组分数都应为越大越好
+pub(crate) fn weighted_score_fusion(
+    lexical_ids: &[u32],
+    lexical_scores: &[f32],
+    semantic_ids: &[u32],
+    semantic_scores: &[f32],
+    semantic_weight: f32,
+    k: usize,
+) -> TopKResults {
+    if lexical_ids.len() != lexical_scores.len() || semantic_ids.len() != semantic_scores.len() {
+        return TopKResults::default();
+    }
+    let semantic_weight = semantic_weight.clamp(0.0, 1.0);
+    let mut fused: HashMap<u32, f32> = HashMap::new();
+    for (ids, scores, weight) in [
+        (lexical_ids, lexical_scores, 1.0 - semantic_weight),
+        (semantic_ids, semantic_scores, semantic_weight),
+    ] {
+        let (min, max) = scores
+            .iter()
+            .filter(|s| s.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &s| {
+                (lo.min(s), hi.max(s))
+            });
+        let range = max - min;
+        for (&id, &score) in ids.iter().zip(scores) {
+            if !score.is_finite() {
+                continue;
+            }
+            // 所有分数相同时视为同等相关
+            let normalized = if range > 0.0 {
+                (score - min) / range
+            } else {
+                1.0
+            };
+            *fused.entry(id).or_insert(0.0) += weight * normalized;
+        }
+    }
+    top_k(fused, k)
+}
+
+fn top_k(fused: HashMap<u32, f32>, k: usize) -> TopKResults {
+    let mut heap = TopKHeap::new(k, true);
+    for (id, score) in fused {
+        heap.push(id, score);
+    }
+    heap.into_results()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rrf_rewards_ids_in_both_lists() {
+        let results = reciprocal_rank_fusion(&[1, 2, 3], &[3, 4], 60.0, 10);
+        // 3：1/63 + 1/61；1：1/61；2 和 4 都是 1/62，同分按 id 排序
+        assert_eq!(results.indices(), [3, 1, 2, 4]);
+        let scores = results.scores();
+        assert_eq!(scores[0], 1.0 / 61.0 + 1.0 / 63.0);
+        assert_eq!(scores[1], 1.0 / 61.0);
+        assert_eq!(scores[2], 1.0 / 62.0);
+        assert_eq!(scores[3], 1.0 / 62.0);
+
+        assert_eq!(
+            reciprocal_rank_fusion(&[1, 2, 3], &[3, 4], 60.0, 2).indices(),
+            [3, 1]
+        );
+        // 非正的 rrf_k 使用默认值 60
+        assert_eq!(
+            reciprocal_rank_fusion(&[1, 2, 3], &[3, 4], 0.0, 10).scores(),
+            scores
+        );
+        assert_eq!(reciprocal_rank_fusion(&[], &[7], 60.0, 10).indices(), [7]);
+        assert!(reciprocal_rank_fusion(&[], &[], 60.0, 10)
+            .indices()
+            .is_empty());
+    }
+
+    #[test]
+    fn weighted_fusion_normalizes_each_list() {
+        let results = weighted_score_fusion(
+            &[1, 2, 3],
+            &[10.0, 5.0, 0.0],
+            &[3, 4],
+            &[0.9, 0.5],
+            0.25,
+            10,
+        );
+        // 词法：1 → 1，2 → 0.5，3 → 0；语义：3 → 1，4 → 0
+        assert_eq!(results.indices(), [1, 2, 3, 4]);
+        assert_eq!(results.scores(), [0.75, 0.375, 0.25, 0.0]);
+    }
+
+    #[test]
+    fn weighted_fusion_with_equal_scores() {
+        // min == max 时整组视为同等相关，归一化为 1 而不是除以 0
+        let results = weighted_score_fusion(&[1, 2], &[5.0, 5.0], &[2, 3], &[0.3, 0.9], 0.25, 10);
+        assert_eq!(results.indices(), [1, 2, 3]);
+        assert_eq!(results.scores(), [0.75, 0.75, 0.25]);
+
+        let results = weighted_score_fusion(&[4], &[-2.0], &[4], &[0.1], 0.5, 10);
+        assert_eq!(results.indices(), [4]);
+        assert_eq!(results.scores(), [1.0]);
+        assert!(results.scores().iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn weighted_fusion_skips_non_finite_and_rejects_mismatched_input() {
+        let results = weighted_score_fusion(&[1, 2, 3], &[f32::NAN, 2.0, 1.0], &[], &[], 0.0, 10);
+        assert_eq!(results.indices(), [2, 3]);
+        assert_eq!(results.scores(), [1.0, 0.0]);
+
+        assert!(weighted_score_fusion(&[1, 2], &[1.0], &[], &[], 0.5, 10)
+            .indices()
+            .is_empty());
+    }
+}
@@ -0,0 +1,436 @@
+use wasm_bindgen::prelude::*;
+use wide::{f32x4, u32x4};
+
+use crate::kernels::{compute_norm_squared_simd, SIMD_LANES};
+use crate::metric::{cosine_from_parts, packed_count, Metric};
+use crate::topk::{TopKHeap, TopKResults};
+
+// 半精度存储格式
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HalfFormat {
+    // IEEE 754 binary16：精度更高，范围 ±65504
+    F16 = 0,
+    // bfloat16：与 f32 相同的指数范围，尾数只有 7 位
+    Bf16 = 1,
+}
+
+// f32 -> f16，就近舍入到偶数，处理溢出、次正规数和 NaN
+pub(crate) fn f32_to_f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mant = bits & 0x007f_ffff;
+
+    if exp == 0xff {
+        // Inf / NaN（保留 quiet 位）
+        let nan_bits = if mant != 0 {
+            0x0200 | (mant >> 13) as u16
+        } else {
+            0
+        };
+        return sign | 0x7c00 | nan_bits;
+    }
+
+    let half_exp = exp - 127 + 15;
+    if half_exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if half_exp <= 0 {
+        if half_exp < -10 {
+            return sign;
+        }
+        // 次正规数：带上隐含位后右移
+        let mant = mant | 0x0080_0000;
+        let shift = (14 - half_exp) as u32;
+        let half_mant = mant >> shift;
+        let rem = mant & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round_up = rem > halfway || (rem == halfway && half_mant & 1 == 1);
+        return sign | (half_mant + round_up as u32) as u16;
+    }
+
+    let half = ((half_exp as u32) << 10) | (mant >> 13);
+    let rem = mant & 0x1fff;
+    let round_up = rem > 0x1000 || (rem == 0x1000 && half & 1 == 1);
+    // 进位可能溢出到指数位，结果仍然正确（最大值舍入为 Inf）
+    sign | (half + round_up as u32) as u16
+}
+
+pub(crate) fn f16_bits_to_f32(bits: u16) -> f32 {
+    widen_f16(u32x4::splat(bits as u32)).to_array()[0]
+}
+
+// f32 -> bf16，就近舍入到偶数
+pub(crate) fn f32_to_bf16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    if value.is_nan() {
+        return ((bits >> 16) | 0x0040) as u16;
+    }
+    let rounding = 0x7fff + ((bits >> 16) & 1);
+    (bits.wrapping_add(rounding) >> 16) as u16
+}
+
+pub(crate) fn bf16_bits_to_f32(bits: u16) -> f32 {
+    f32::from_bits((bits as u32) << 16)
+}
+
+// 寄存器内把 4 个 f16 扩展为 f32：指数尾数左移 13 位后乘以 2^112 完成重新偏置，
+// 这样次正规数也能得到正确结果；Inf / NaN 单独把指数置满
+#[inline(always)]
+fn widen_f16(halves: u32x4) -> f32x4 {
+    let sign = (halves & u32x4::splat(0x8000)) << 16;
+    let exp_mant = halves & u32x4::splat(0x7fff);
+    let scaled: f32x4 = bytemuck::cast(exp_mant << 13);
+    let scaled_bits: u32x4 = bytemuck::cast(scaled * f32x4::splat(f32::from_bits(0x7780_0000)));
+    let inf_nan = exp_mant.cmp_gt(u32x4::splat(0x7bff));
+    let bits = inf_nan.blend(scaled_bits | u32x4::splat(0x7f80_0000), scaled_bits);
+    bytemuck::cast(bits | sign)
+}
+
+#[inline(always)]
+fn widen_bf16(halves: u32x4) -> f32x4 {
+    bytemuck::cast(halves << 16)
+}
+
+#[inline(always)]
+fn load_half4(format: HalfFormat, slice: &[u16], offset: usize) -> f32x4 {
+    let halves = u32x4::new([
+        slice[offset] as u32,
+        slice[offset + 1] as u32,
+        slice[offset + 2] as u32,
+        slice[offset + 3] as u32,
+    ]);
+    match format {
+        HalfFormat::F16 => widen_f16(halves),
+        HalfFormat::Bf16 => widen_bf16(halves),
+    }
+}
+
+#[inline(always)]
+fn half_to_f32(format: HalfFormat, bits: u16) -> f32 {
+    match format {
+        HalfFormat::F16 => f16_bits_to_f32(bits),
+        HalfFormat::Bf16 => bf16_bits_to_f32(bits),
+    }
+}
+
+pub(crate) fn encode_half(format: HalfFormat, values: &[f32]) -> Vec<u16> {
+    match format {
+        HalfFormat::F16 => values.iter().map(|&x| f32_to_f16_bits(x)).collect(),
+        HalfFormat::Bf16 => values.iter().map(|&x| f32_to_bf16_bits(x)).collect(),
+    }
+}
+
+pub(crate) fn decode_half(format: HalfFormat, bits: &[u16]) -> Vec<f32> {
+    let simd_len = bits.len() - (bits.len() % SIMD_LANES);
+    let mut values = Vec::with_capacity(bits.len());
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        values.extend_from_slice(&load_half4(format, bits, i).to_array());
+    }
+    values.extend(bits[simd_len..].iter().map(|&b| half_to_f32(format, b)));
+    values
+}
+
+// 半精度行与 f32 查询的点积和行的范数平方
+#[inline]
+fn dot_and_norm_half(format: HalfFormat, row: &[u16], query: &[f32]) -> (f32, f32) {
+    let len = row.len();
+    let simd_len = len - (len % SIMD_LANES);
+    let mut dot_sum_simd = f32x4::ZERO;
+    let mut norm_sum_simd = f32x4::ZERO;
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let a_chunk = load_half4(format, row, i);
+        let b_array: [f32; 4] = query[i..i + SIMD_LANES].try_into().unwrap();
+        dot_sum_simd = a_chunk.mul_add(f32x4::new(b_array), dot_sum_simd);
+        norm_sum_simd = a_chunk.mul_add(a_chunk, norm_sum_simd);
+    }
+
+    let mut dot_product = dot_sum_simd.reduce_add();
+    let mut norm_sq = norm_sum_simd.reduce_add();
+    for i in simd_len..len {
+        let a = half_to_f32(format, row[i]);
+        dot_product += a * query[i];
+        norm_sq += a * a;
+    }
+    (dot_product, norm_sq)
+}
+
+#[inline]
+fn distance_half(format: HalfFormat, row: &[u16], query: &[f32], squared: bool) -> f32 {
+    let len = row.len();
+    let simd_len = len - (len % SIMD_LANES);
+    let mut sum_simd = f32x4::ZERO;
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let b_array: [f32; 4] = query[i..i + SIMD_LANES].try_into().unwrap();
+        let diff = load_half4(format, row, i) - f32x4::new(b_array);
+        sum_simd = if squared {
+            diff.mul_add(diff, sum_simd)
+        } else {
+            sum_simd + diff.abs()
+        };
+    }
+
+    let mut sum = sum_simd.reduce_add();
+    for i in simd_len..len {
+        let diff = half_to_f32(format, row[i]) - query[i];
+        sum += if squared { diff * diff } else { diff.abs() };
+    }
+    sum
+}
+
+// 以 f16 / bf16 存储的向量集合，打分时在 SIMD 循环内扩展为 f32
+#[wasm_bindgen]
+pub struct HalfVectors {
+    dim: usize,
+    format: HalfFormat,
+    data: Vec<u16>,
+}
+
+#[wasm_bindgen]
+impl HalfVectors {
+    #[wasm_bindgen(constructor)]
+    pub fn new(dim: usize, format: HalfFormat) -> HalfVectors {
+        HalfVectors {
+            dim,
+            format,
+            data: Vec::new(),
+        }
+    }
+
+    // 从导出的原始位恢复，长度不符时返回 None
+    pub fn from_bits(dim: usize, format: HalfFormat, bits: Vec<u16>) -> Option<HalfVectors> {
+        if dim == 0 || !bits.len().is_multiple_of(dim) {
+            return None;
+        }
+        Some(HalfVectors {
+            dim,
+            format,
+            data: bits,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn format(&self) -> HalfFormat {
+        self.format
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.data.len().checked_div(self.dim).unwrap_or(0)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bits(&self) -> Vec<u16> {
+        self.data.clone()
+    }
+
+    pub fn memory_bytes(&self) -> usize {
+        self.data.len() * std::mem::size_of::<u16>()
+    }
+
+    // 批量导入 f32 打包向量，返回新增数量
+    pub fn add(&mut self, vectors: &[f32]) -> usize {
+        let count = match packed_count(vectors, self.dim) {
+            Some(n) => n,
+            None => return 0,
+        };
+        self.data.extend(encode_half(self.format, vectors));
+        count
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    // 导出为 f32 打包向量
+    pub fn to_f32(&self) -> Vec<f32> {
+        decode_half(self.format, &self.data)
+    }
+
+    pub fn scores(&self, query: &[f32], metric: Metric) -> Vec<f32> {
+        let mut results = Vec::with_capacity(self.length());
+        self.for_each_score(query, metric, |_, score| results.push(score));
+        results
+    }
+
+    pub fn top_k(&self, query: &[f32], k: usize, metric: Metric) -> TopKResults {
+        let mut heap = TopKHeap::for_metric(k, metric);
+        self.for_each_score(query, metric, |i, score| heap.push(i as u32, score));
+        heap.into_results()
+    }
+}
+
+impl HalfVectors {
+    fn for_each_score(&self, query: &[f32], metric: Metric, mut on_score: impl FnMut(usize, f32)) {
+        if query.len() != self.dim || self.dim == 0 {
+            return;
+        }
+        let query_norm = compute_norm_squared_simd(query).sqrt();
+
+        for (i, row) in self.data.chunks_exact(self.dim).enumerate() {
+            let score = match metric {
+                Metric::Dot => dot_and_norm_half(self.format, row, query).0,
+                Metric::Cosine => {
+                    let (dot_product, norm_sq) = dot_and_norm_half(self.format, row, query);
+                    cosine_from_parts(dot_product, norm_sq.sqrt(), query_norm)
+                }
+                Metric::L2Squared => distance_half(self.format, row, query, true),
+                Metric::L1 => distance_half(self.format, row, query, false),
+            };
+            on_score(i, score);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    const METRICS: [Metric; 4] = [Metric::Dot, Metric::Cosine, Metric::L2Squared, Metric::L1];
+
+    #[test]
+    fn f16_edge_values() {
+        let smallest = f32::from_bits(0x3380_0000); // 2^-24
+        let cases = [
+            (0.0, 0x0000),
+            (-0.0, 0x8000),
+            (smallest, 0x0001),
+            (-smallest, 0x8001),
+            // 最小次正规数的一半：正好在 0 和 0x0001 中间，舍入到偶数 0
+            (smallest * 0.5, 0x0000),
+            (smallest * 0.5000001, 0x0001),
+            // 1.5 个最小次正规数：舍入到偶数 0x0002
+            (smallest * 1.5, 0x0002),
+            (smallest * 0.25, 0x0000),
+            (65504.0, 0x7bff),
+            (-65504.0, 0xfbff),
+            // 65520 是 65504 与 2^16 的中点，舍入到偶数即溢出为 Inf
+            (65519.0, 0x7bff),
+            (65520.0, 0x7c00),
+            (1.0e6, 0x7c00),
+            (-1.0e6, 0xfc00),
+            (f32::INFINITY, 0x7c00),
+            (f32::NEG_INFINITY, 0xfc00),
+            (1.0, 0x3c00),
+            (-2.0, 0xc000),
+        ];
+        for (value, bits) in cases {
+            assert_eq!(f32_to_f16_bits(value), bits, "{:e}", value);
+        }
+
+        let nan = f32_to_f16_bits(f32::NAN);
+        assert_eq!(nan & 0x7c00, 0x7c00);
+        assert_ne!(nan & 0x03ff, 0);
+        assert!(f16_bits_to_f32(nan).is_nan());
+
+        assert_eq!(f16_bits_to_f32(0x0001), smallest);
+        assert_eq!(f16_bits_to_f32(0x03ff), smallest * 1023.0);
+        assert_eq!(f16_bits_to_f32(0x7bff), 65504.0);
+        assert_eq!(f16_bits_to_f32(0x7c00), f32::INFINITY);
+        assert_eq!(f16_bits_to_f32(0xfc00), f32::NEG_INFINITY);
+        assert_eq!(f16_bits_to_f32(0x8000).to_bits(), (-0.0f32).to_bits());
+    }
+
+    #[test]
+    fn every_f16_round_trips() {
+        for bits in 0..=u16::MAX {
+            let value = f16_bits_to_f32(bits);
+            if value.is_nan() {
+                assert_eq!(bits & 0x7c00, 0x7c00);
+                continue;
+            }
+            assert_eq!(f32_to_f16_bits(value), bits, "{:04x}", bits);
+        }
+    }
+
+    #[test]
+    fn bf16_edge_values() {
+        let smallest = f32::from_bits(0x0001_0000); // 2^-133
+        let largest = f32::from_bits(0x7f7f_0000);
+        let cases = [
+            (0.0, 0x0000),
+            (-0.0, 0x8000),
+            (smallest, 0x0001),
+            (-smallest, 0x8001),
+            // 中点舍入到偶数
+            (f32::from_bits(0x0000_8000), 0x0000),
+            (f32::from_bits(0x0000_8001), 0x0001),
+            (f32::from_bits(0x0001_8000), 0x0002),
+            (largest, 0x7f7f),
+            // f32::MAX 超过 bf16 最大值与 Inf 的中点，溢出为 Inf
+            (f32::MAX, 0x7f80),
+            (f32::MIN, 0xff80),
+            (f32::INFINITY, 0x7f80),
+            (f32::NEG_INFINITY, 0xff80),
+            (1.0, 0x3f80),
+        ];
+        for (value, bits) in cases {
+            assert_eq!(f32_to_bf16_bits(value), bits, "{:e}", value);
+        }
+
+        assert!(bf16_bits_to_f32(f32_to_bf16_bits(f32::NAN)).is_nan());
+        // 尾数只在被截掉的低位上时也要保持 NaN，而不是变成 Inf
+        assert!(bf16_bits_to_f32(f32_to_bf16_bits(f32::from_bits(0x7f80_0001))).is_nan());
+        assert_eq!(bf16_bits_to_f32(0x0001), smallest);
+        assert_eq!(bf16_bits_to_f32(0x7f7f), largest);
+        assert_eq!(bf16_bits_to_f32(0x8000).to_bits(), (-0.0f32).to_bits());
+    }
+
+    #[test]
+    fn simd_decode_matches_scalar_for_every_length() {
+        // 覆盖次正规数、Inf、NaN 以及尾部不足 4 个的情况
+        let f16_bits: Vec<u16> = (0..=u16::MAX).step_by(97).chain([0x7c00, 0xfe00]).collect();
+        for format in [HalfFormat::F16, HalfFormat::Bf16] {
+            for len in 0..=11 {
+                let bits = &f16_bits[..len];
+                let decoded = decode_half(format, bits);
+                assert_eq!(decoded.len(), len);
+                for (&b, v) in bits.iter().zip(&decoded) {
+                    assert_eq!(v.to_bits(), half_to_f32(format, b).to_bits());
+                }
+            }
+            let decoded = decode_half(format, &f16_bits);
+            for (&b, v) in f16_bits.iter().zip(&decoded) {
+                assert_eq!(v.to_bits(), half_to_f32(format, b).to_bits(), "{:04x}", b);
+            }
+        }
+    }
+
+    #[test]
+    fn simd_scores_match_scalar_for_dims_not_multiple_of_four() {
+        for format in [HalfFormat::F16, HalfFormat::Bf16] {
+            for dim in [1, 3, 5, 7, 13] {
+                let mut vectors = HalfVectors::new(dim, format);
+                assert_eq!(vectors.add(&random_vectors(dim as u64, 6, dim)), 6);
+                let decoded = vectors.to_f32();
+                let query = random_vectors(100 + dim as u64, 1, dim);
+                for metric in METRICS {
+                    let scores = vectors.scores(&query, metric);
+                    assert_eq!(scores.len(), 6);
+                    for (row, &score) in decoded.chunks_exact(dim).zip(&scores) {
+                        let expected = metric.score(row, &query);
+                        assert!(
+                            (score - expected).abs() <= 1e-5 * expected.abs().max(1.0),
+                            "{:?} {:?} dim {}: {} vs {}",
+                            format,
+                            metric,
+                            dim,
+                            score,
+                            expected
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// 64 位 FNV-1a
+pub(crate) fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash = FNV_OFFSET;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+// 取出 URL 的 host（小写、去掉端口和末尾的点）；没有 scheme 时整个输入视为 host
+pub(crate) fn url_host(url: &str) -> String {
+    let url = url.trim();
+    let rest = match url.find("://") {
+        Some(pos) => &url[pos + 3..],
+        None => url,
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority.rsplit('@').next().unwrap_or("");
+    let host = if host_port.starts_with('[') {
+        // IPv6 字面量，保留方括号
+        host_port
+            .find(']')
+            .map_or(host_port, |end| &host_port[..=end])
+    } else {
+        host_port.split(':').next().unwrap_or("")
+    };
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+// 同一 host 的不同 URL 得到相同的哈希
+pub(crate) fn host_hash(url: &str) -> u64 {
+    fnv1a64(url_host(url).as_bytes())
+}
+
+const MIX_A: u64 = 0xa076_1d64_78bd_642f;
+const MIX_B: u64 = 0xe703_7ed1_a0b4_28db;
+
+// 128 位乘法后高低位异或
+#[inline]
+fn fold_mul(a: u64, b: u64) -> u64 {
+    let product = a as u128 * b as u128;
+    (product as u64) ^ ((product >> 64) as u64)
+}
+
+// 每次处理 8 字节的 64 位哈希（wyhash 风格），用于缓存键等非对抗场景
+pub(crate) fn hash64(bytes: &[u8], seed: u64) -> u64 {
+    let mut hash = seed ^ MIX_A ^ (bytes.len() as u64).wrapping_mul(MIX_B);
+    let mut chunks = bytes.chunks_exact(8);
+    for chunk in &mut chunks {
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+        hash = fold_mul(word ^ hash.rotate_left(29) ^ MIX_B, hash ^ MIX_A);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut word = [0u8; 8];
+        word[..tail.len()].copy_from_slice(tail);
+        hash = fold_mul(
+            u64::from_le_bytes(word) ^ hash.rotate_left(29) ^ MIX_B,
+            hash ^ MIX_A,
+        );
+    }
+    fold_mul(hash ^ MIX_A, hash.rotate_left(32) ^ MIX_B)
+}
+
+// 两个不同种子的 64 位哈希拼成 128 位
+pub(crate) fn hash128(bytes: &[u8], seed: u64) -> u128 {
+    let high = hash64(bytes, seed);
+    let low = hash64(bytes, seed ^ 0x9e37_79b9_7f4a_7c15);
+    (high as u128) << 64 | low as u128
+}
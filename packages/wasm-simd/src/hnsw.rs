@@ -0,0 +1,929 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use wasm_bindgen::prelude::*;
+
+use crate::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::filter::{SearchFilter, VectorAttributes};
+use crate::kernels::{dot_product_simd_only, l1_simd, l2_squared_simd};
+use crate::metric::Metric;
+use crate::rng::Rng;
+use crate::topk::{TopKHeap, TopKResults};
+use crate::unit::normalize_in_place;
+
+const MAX_LEVEL: usize = 16;
+
+// 带距离的槽位，距离越小越近；距离相同时按槽位排序保证确定性
+#[derive(Clone, Copy, Debug)]
+struct Scored {
+    distance: f32,
+    slot: u32,
+}
+
+impl PartialEq for Scored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then_with(|| self.slot.cmp(&other.slot))
+    }
+}
+
+// 原生 HNSW 图索引，复用 SIMD 距离内核。
+// 支持真正的删除：删除节点后会为其邻居重新挑选连接
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct HnswIndex {
+    dim: usize,
+    metric: Metric,
+    m: usize,
+    m_max0: usize,
+    ef_construction: usize,
+    ef_search: usize,
+    level_mult: f64,
+    rng: Rng,
+    // 按槽位存储，删除后的槽位进入 free_slots 复用
+    vectors: Vec<f32>,
+    labels: Vec<u32>,
+    // links[slot][layer] 为该层的邻居槽位
+    links: Vec<Vec<Vec<u32>>>,
+    // in_links[slot][layer] 为该层指向 slot 的槽位（边不一定对称），删除时只修复这些节点
+    in_links: Vec<Vec<Vec<u32>>>,
+    alive: Vec<bool>,
+    free_slots: Vec<u32>,
+    label_to_slot: HashMap<u32, u32>,
+    entry_point: Option<u32>,
+    max_level: usize,
+}
+
+#[wasm_bindgen]
+impl HnswIndex {
+    // m 为每层最大连接数（第 0 层为 2m），seed 决定层级抽样，相同输入顺序可复现同一张图
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        dim: usize,
+        metric: Metric,
+        m: usize,
+        ef_construction: usize,
+        seed: u64,
+    ) -> HnswIndex {
+        let m = m.max(2);
+        HnswIndex {
+            dim,
+            metric,
+            m,
+            m_max0: m * 2,
+            ef_construction: ef_construction.max(m),
+            ef_search: 50,
+            level_mult: 1.0 / (m as f64).ln(),
+            rng: Rng::new(seed),
+            vectors: Vec::new(),
+            labels: Vec::new(),
+            links: Vec::new(),
+            in_links: Vec::new(),
+            alive: Vec::new(),
+            free_slots: Vec::new(),
+            label_to_slot: HashMap::new(),
+            entry_point: None,
+            max_level: 0,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ef_construction(&self) -> usize {
+        self.ef_construction
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ef_search(&self) -> usize {
+        self.ef_search
+    }
+
+    pub fn set_ef_search(&mut self, ef_search: usize) {
+        self.ef_search = ef_search.max(1);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.label_to_slot.len()
+    }
+
+    pub fn contains(&self, label: u32) -> bool {
+        self.label_to_slot.contains_key(&label)
+    }
+
+    // 按槽位顺序返回所有存活的 label
+    pub fn labels(&self) -> Vec<u32> {
+        self.live_slots().map(|slot| self.labels[slot]).collect()
+    }
+
+    // 余弦度量下返回的是归一化后的向量
+    pub fn get(&self, label: u32) -> Option<Vec<f32>> {
+        self.label_to_slot
+            .get(&label)
+            .map(|&slot| self.vector(slot).to_vec())
+    }
+
+    // 插入向量；label 已存在时先删除旧向量再插入
+    pub fn insert(&mut self, label: u32, vector: &[f32]) -> bool {
+        if vector.len() != self.dim || self.dim == 0 {
+            return false;
+        }
+        if self.label_to_slot.contains_key(&label) {
+            self.remove(label);
+        }
+
+        let level = self.random_level();
+        let slot = self.allocate_slot(label, vector, level);
+
+        let entry_point = match self.entry_point {
+            Some(entry_point) => entry_point,
+            None => {
+                self.entry_point = Some(slot);
+                self.max_level = level;
+                return true;
+            }
+        };
+
+        let query = self.vector(slot).to_vec();
+        let mut entry = vec![Scored {
+            distance: self.distance_to(&query, entry_point),
+            slot: entry_point,
+        }];
+
+        // 高层贪心下降
+        for layer in (level + 1..=self.max_level).rev() {
+            entry = self.search_layer(&query, &entry, 1, layer);
+        }
+
+        for layer in (0..=level.min(self.max_level)).rev() {
+            let candidates = self.search_layer(&query, &entry, self.ef_construction, layer);
+            let neighbors = self.select_neighbors(&candidates, self.m);
+            self.set_links(slot, layer, neighbors.clone());
+
+            for neighbor in neighbors {
+                self.connect(neighbor, slot, layer);
+            }
+            entry = candidates;
+        }
+
+        if level > self.max_level {
+            self.max_level = level;
+            self.entry_point = Some(slot);
+        }
+        true
+    }
+
+    // 真正删除节点，并为指向它的节点重新挑选邻居
+    pub fn remove(&mut self, label: u32) -> bool {
+        let slot = match self.label_to_slot.remove(&label) {
+            Some(slot) => slot,
+            None => return false,
+        };
+        let removed_links = self.links[slot as usize].clone();
+        for layer in 0..removed_links.len() {
+            self.set_links(slot, layer, Vec::new());
+        }
+        self.links[slot as usize].clear();
+        let removed_in_links = std::mem::take(&mut self.in_links[slot as usize]);
+        self.alive[slot as usize] = false;
+
+        for (layer, removed_neighbors) in removed_links.iter().enumerate() {
+            // 按槽位顺序修复，保证结果与插入 / 删除顺序一一对应
+            let mut affected = removed_in_links[layer].clone();
+            affected.sort_unstable();
+            for node in affected {
+                self.repair_links(node, slot, removed_neighbors, layer);
+            }
+        }
+
+        if self.entry_point == Some(slot) {
+            self.reset_entry_point();
+        }
+        self.free_slots.push(slot);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.vectors.clear();
+        self.labels.clear();
+        self.links.clear();
+        self.in_links.clear();
+        self.alive.clear();
+        self.free_slots.clear();
+        self.label_to_slot.clear();
+        self.entry_point = None;
+        self.max_level = 0;
+    }
+
+    // 近似 k 近邻搜索，indices 为 label，分数与 Metric 一致（相似度或距离）
+    pub fn search(&self, query: &[f32], k: usize) -> TopKResults {
+        if query.len() != self.dim || k == 0 {
+            return TopKResults::default();
+        }
+        let query = self.prepare_query(query);
+        let candidates = self.search_candidates(&query, self.ef_search.max(k), |_| true);
+        self.top_k(&candidates, k)
+    }
+
+    // 遍历图时跳过不满足 filter 的节点（仍经由它们继续扩展），
+    // 匹配节点不足 k 个时退化为对匹配节点的精确扫描，保证结果数量
+    pub fn search_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        attributes: &VectorAttributes,
+        filter: &SearchFilter,
+    ) -> TopKResults {
+        if query.len() != self.dim || k == 0 {
+            return TopKResults::default();
+        }
+        let query = self.prepare_query(query);
+        let accept = |slot: u32| filter.matches(attributes, self.labels[slot as usize]);
+        let mut candidates = self.search_candidates(&query, self.ef_search.max(k), accept);
+        if candidates.len() < k {
+            candidates = self
+                .live_slots()
+                .map(|slot| slot as u32)
+                .filter(|&slot| accept(slot))
+                .map(|slot| Scored {
+                    distance: self.distance_to(&query, slot),
+                    slot,
+                })
+                .collect();
+            candidates.sort();
+        }
+        self.top_k(&candidates, k)
+    }
+}
+
+impl HnswIndex {
+    fn random_level(&mut self) -> usize {
+        // 1 - U 落在 (0, 1]，避免 ln(0)
+        let uniform = 1.0 - self.rng.next_f64();
+        ((-uniform.ln() * self.level_mult) as usize).min(MAX_LEVEL)
+    }
+
+    fn allocate_slot(&mut self, label: u32, vector: &[f32], level: usize) -> u32 {
+        let slot = match self.free_slots.pop() {
+            Some(slot) => {
+                let start = slot as usize * self.dim;
+                self.vectors[start..start + self.dim].copy_from_slice(vector);
+                self.labels[slot as usize] = label;
+                self.links[slot as usize] = vec![Vec::new(); level + 1];
+                self.in_links[slot as usize] = vec![Vec::new(); level + 1];
+                self.alive[slot as usize] = true;
+                slot
+            }
+            None => {
+                self.vectors.extend_from_slice(vector);
+                self.labels.push(label);
+                self.links.push(vec![Vec::new(); level + 1]);
+                self.in_links.push(vec![Vec::new(); level + 1]);
+                self.alive.push(true);
+                (self.labels.len() - 1) as u32
+            }
+        };
+        if self.metric == Metric::Cosine {
+            // 与 hnswlib 一致：余弦空间存储归一化向量，距离退化为 1 - 点积
+            let start = slot as usize * self.dim;
+            normalize_in_place(&mut self.vectors[start..start + self.dim]);
+        }
+        self.label_to_slot.insert(label, slot);
+        slot
+    }
+
+    fn live_slots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.alive
+            .iter()
+            .enumerate()
+            .filter(|(_, &alive)| alive)
+            .map(|(slot, _)| slot)
+    }
+
+    #[inline]
+    fn vector(&self, slot: u32) -> &[f32] {
+        let start = slot as usize * self.dim;
+        &self.vectors[start..start + self.dim]
+    }
+
+    fn prepare_query(&self, query: &[f32]) -> Vec<f32> {
+        let mut query = query.to_vec();
+        if self.metric == Metric::Cosine {
+            normalize_in_place(&mut query);
+        }
+        query
+    }
+
+    // 内部统一使用“越小越近”的距离
+    #[inline]
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self.metric {
+            Metric::Dot => -dot_product_simd_only(a, b),
+            Metric::Cosine => 1.0 - dot_product_simd_only(a, b),
+            Metric::L2Squared => l2_squared_simd(a, b),
+            Metric::L1 => l1_simd(a, b),
+        }
+    }
+
+    #[inline]
+    fn distance_to(&self, query: &[f32], slot: u32) -> f32 {
+        self.distance(query, self.vector(slot))
+    }
+
+    #[inline]
+    fn distance_between(&self, a: u32, b: u32) -> f32 {
+        self.distance(self.vector(a), self.vector(b))
+    }
+
+    fn distance_to_score(&self, distance: f32) -> f32 {
+        match self.metric {
+            Metric::Dot => -distance,
+            Metric::Cosine => (1.0 - distance).clamp(-1.0, 1.0),
+            Metric::L2Squared | Metric::L1 => distance,
+        }
+    }
+
+    #[inline]
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.m_max0
+        } else {
+            self.m
+        }
+    }
+
+    fn top_k(&self, candidates: &[Scored], k: usize) -> TopKResults {
+        let mut heap = TopKHeap::for_metric(k, self.metric);
+        for candidate in candidates.iter().take(k) {
+            heap.push(
+                self.labels[candidate.slot as usize],
+                self.distance_to_score(candidate.distance),
+            );
+        }
+        heap.into_results()
+    }
+
+    // 从入口点贪心下降到第 1 层，再在第 0 层以 ef 展开，返回按距离升序、满足 accept 的候选
+    fn search_candidates(
+        &self,
+        query: &[f32],
+        ef: usize,
+        accept: impl Fn(u32) -> bool,
+    ) -> Vec<Scored> {
+        let entry_point = match self.entry_point {
+            Some(entry_point) => entry_point,
+            None => return Vec::new(),
+        };
+        let mut entry = vec![Scored {
+            distance: self.distance_to(query, entry_point),
+            slot: entry_point,
+        }];
+        for layer in (1..=self.max_level).rev() {
+            entry = self.search_layer(query, &entry, 1, layer);
+        }
+        self.search_layer_filtered(query, &entry, ef, 0, accept)
+    }
+
+    // 单层 best-first 搜索，返回按距离升序的至多 ef 个结果
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry: &[Scored],
+        ef: usize,
+        layer: usize,
+    ) -> Vec<Scored> {
+        self.search_layer_filtered(query, entry, ef, layer, |_| true)
+    }
+
+    // 不满足 accept 的节点不进入结果，但仍作为候选继续扩展
+    fn search_layer_filtered(
+        &self,
+        query: &[f32],
+        entry: &[Scored],
+        ef: usize,
+        layer: usize,
+        accept: impl Fn(u32) -> bool,
+    ) -> Vec<Scored> {
+        let mut visited: HashSet<u32> = entry.iter().map(|e| e.slot).collect();
+        let mut candidates: BinaryHeap<Reverse<Scored>> =
+            entry.iter().map(|&e| Reverse(e)).collect();
+        let mut results: BinaryHeap<Scored> =
+            entry.iter().copied().filter(|e| accept(e.slot)).collect();
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(Reverse(current)) = candidates.pop() {
+            if let Some(worst) = results.peek() {
+                if results.len() >= ef && current.distance > worst.distance {
+                    break;
+                }
+            }
+            let neighbors = match self.links[current.slot as usize].get(layer) {
+                Some(neighbors) => neighbors,
+                None => continue,
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let distance = self.distance_to(query, neighbor);
+                let admit =
+                    results.len() < ef || results.peek().is_some_and(|w| distance < w.distance);
+                if admit {
+                    let scored = Scored {
+                        distance,
+                        slot: neighbor,
+                    };
+                    candidates.push(Reverse(scored));
+                    if accept(neighbor) {
+                        results.push(scored);
+                        if results.len() > ef {
+                            results.pop();
+                        }
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    // 启发式邻居选择：候选按距离升序，若候选离已选邻居比离基准点更近则跳过
+    fn select_neighbors(&self, candidates: &[Scored], max_links: usize) -> Vec<u32> {
+        let mut selected: Vec<Scored> = Vec::with_capacity(max_links);
+        for &candidate in candidates {
+            if selected.len() >= max_links {
+                break;
+            }
+            let diverse = selected
+                .iter()
+                .all(|s| self.distance_between(candidate.slot, s.slot) >= candidate.distance);
+            if diverse {
+                selected.push(candidate);
+            }
+        }
+        selected.iter().map(|s| s.slot).collect()
+    }
+
+    // 为 node 在 layer 层添加到 neighbor 的边，超出上限时重新筛选
+    fn connect(&mut self, node: u32, neighbor: u32, layer: usize) {
+        let max_links = self.max_links(layer);
+        let links = &mut self.links[node as usize][layer];
+        if links.contains(&neighbor) {
+            return;
+        }
+        if links.len() < max_links {
+            links.push(neighbor);
+            self.in_links[neighbor as usize][layer].push(node);
+            return;
+        }
+
+        let mut candidates: Vec<Scored> = self.links[node as usize][layer]
+            .iter()
+            .chain(std::iter::once(&neighbor))
+            .map(|&slot| Scored {
+                distance: self.distance_between(node, slot),
+                slot,
+            })
+            .collect();
+        candidates.sort();
+        let selected = self.select_neighbors(&candidates, max_links);
+        self.set_links(node, layer, selected);
+    }
+
+    // 替换 node 在 layer 层的邻居，并同步更新反向边
+    fn set_links(&mut self, node: u32, layer: usize, links: Vec<u32>) {
+        let old = std::mem::replace(&mut self.links[node as usize][layer], links);
+        for target in old {
+            if !self.links[node as usize][layer].contains(&target) {
+                if let Some(sources) = self.in_links[target as usize].get_mut(layer) {
+                    sources.retain(|&source| source != node);
+                }
+            }
+        }
+        for i in 0..self.links[node as usize][layer].len() {
+            let target = self.links[node as usize][layer][i];
+            let sources = &mut self.in_links[target as usize][layer];
+            if !sources.contains(&node) {
+                sources.push(node);
+            }
+        }
+    }
+
+    // 删除 removed 后修复 node 的邻居：以现有邻居和被删节点的邻居为候选重新挑选
+    fn repair_links(&mut self, node: u32, removed: u32, removed_neighbors: &[u32], layer: usize) {
+        let mut seen: HashSet<u32> = HashSet::new();
+        let mut candidates: Vec<Scored> = self.links[node as usize][layer]
+            .iter()
+            .chain(removed_neighbors)
+            .copied()
+            .filter(|&slot| slot != removed && slot != node && self.alive[slot as usize])
+            .filter(|&slot| self.links[slot as usize].len() > layer && seen.insert(slot))
+            .map(|slot| Scored {
+                distance: self.distance_between(node, slot),
+                slot,
+            })
+            .collect();
+        candidates.sort();
+        let selected = self.select_neighbors(&candidates, self.max_links(layer));
+        self.set_links(node, layer, selected);
+    }
+
+    // 入口点被删除时选层级最高的存活节点（层级相同取槽位最小）作为新入口
+    fn reset_entry_point(&mut self) {
+        let best = self
+            .live_slots()
+            .map(|slot| (self.links[slot].len(), Reverse(slot)))
+            .max();
+        match best {
+            Some((levels, Reverse(slot))) => {
+                self.entry_point = Some(slot as u32);
+                self.max_level = levels - 1;
+            }
+            None => {
+                self.entry_point = None;
+                self.max_level = 0;
+            }
+        }
+    }
+}
+
+// 快照读写：图结构按槽位写出，向量由快照的向量段按 label 提供
+impl HnswIndex {
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (u32, &[f32])> + '_ {
+        self.live_slots()
+            .map(|slot| (self.labels[slot], self.vector(slot as u32)))
+    }
+
+    pub(crate) fn write_graph(&self, writer: &mut ByteWriter) {
+        writer.u32(self.m as u32);
+        writer.u32(self.ef_construction as u32);
+        writer.u32(self.ef_search as u32);
+        writer.u64(self.rng.state());
+        writer.u32(self.entry_point.unwrap_or(u32::MAX));
+        writer.u32(self.max_level as u32);
+        writer.u32(self.labels.len() as u32);
+        for slot in 0..self.labels.len() {
+            writer.u8(self.alive[slot] as u8);
+            writer.u32(self.labels[slot]);
+            writer.u32(self.links[slot].len() as u32);
+            for links in &self.links[slot] {
+                writer.u32(links.len() as u32);
+                writer.u32_slice(links);
+            }
+        }
+        writer.u32(self.free_slots.len() as u32);
+        writer.u32_slice(&self.free_slots);
+    }
+
+    pub(crate) fn read_graph(
+        reader: &mut ByteReader,
+        dim: usize,
+        metric: Metric,
+        vectors: &HashMap<u32, &[f32]>,
+    ) -> Result<HnswIndex, DecodeError> {
+        let invalid = |msg: String| DecodeError::Invalid(format!("HNSW graph: {}", msg));
+        let m = reader.u32()? as usize;
+        let ef_construction = reader.u32()? as usize;
+        let ef_search = reader.u32()? as usize;
+        let rng_state = reader.u64()?;
+        let entry_point = reader.u32()?;
+        let max_level = reader.u32()? as usize;
+        let slots = reader.u32()? as usize;
+        if m < 2 || max_level > MAX_LEVEL {
+            return Err(invalid(format!("m {} / max level {}", m, max_level)));
+        }
+        // 每个槽位至少占 9 字节（标志、label、层数），先按剩余字节数限制槽位数再分配向量
+        if slots > reader.remaining() / 9 || slots < vectors.len() {
+            return Err(invalid(format!(
+                "{} slots for {} vectors and {} remaining bytes",
+                slots,
+                vectors.len(),
+                reader.remaining()
+            )));
+        }
+
+        let mut index = HnswIndex::new(dim, metric, m, ef_construction, rng_state);
+        index.set_ef_search(ef_search);
+        index.max_level = max_level;
+        index.vectors = vec![0.0; slots.checked_mul(dim).ok_or(DecodeError::UnexpectedEof)?];
+        for slot in 0..slots {
+            let alive = match reader.u8()? {
+                0 => false,
+                1 => true,
+                flag => return Err(invalid(format!("slot {} has flag {}", slot, flag))),
+            };
+            let label = reader.u32()?;
+            let levels = reader.u32()? as usize;
+            if alive != (1..=max_level + 1).contains(&levels) {
+                return Err(invalid(format!("slot {} has {} levels", slot, levels)));
+            }
+            let mut links = Vec::with_capacity(levels);
+            for _ in 0..levels {
+                let count = reader.u32()? as usize;
+                let layer = reader.u32_vec(count)?;
+                if layer.iter().any(|&l| l as usize >= slots) {
+                    return Err(invalid(format!("slot {} links outside the graph", slot)));
+                }
+                links.push(layer);
+            }
+            if alive {
+                let vector = vectors.get(&label).ok_or_else(|| {
+                    invalid(format!(
+                        "label {} is missing from the vector section",
+                        label
+                    ))
+                })?;
+                if index.label_to_slot.insert(label, slot as u32).is_some() {
+                    return Err(invalid(format!("label {} appears twice", label)));
+                }
+                index.vectors[slot * dim..(slot + 1) * dim].copy_from_slice(vector);
+            }
+            index.labels.push(label);
+            index.links.push(links);
+            index.alive.push(alive);
+        }
+
+        // 空闲槽位必须恰好是所有已删除的槽位，每个只出现一次
+        let free_count = reader.u32()? as usize;
+        index.free_slots = reader.u32_vec(free_count)?;
+        let mut vacant = vec![false; slots];
+        for &slot in &index.free_slots {
+            match vacant.get_mut(slot as usize) {
+                Some(seen) if !*seen && !index.alive[slot as usize] => *seen = true,
+                _ => {
+                    return Err(invalid(format!(
+                        "free slot {} is live, repeated or out of range",
+                        slot
+                    )))
+                }
+            }
+        }
+        if free_count != index.alive.iter().filter(|&&alive| !alive).count() {
+            return Err(invalid(format!(
+                "{} free slots but {} vacant slots",
+                free_count,
+                slots - index.label_to_slot.len()
+            )));
+        }
+        if index.label_to_slot.len() != vectors.len() {
+            return Err(invalid(format!(
+                "{} nodes but {} vectors",
+                index.label_to_slot.len(),
+                vectors.len()
+            )));
+        }
+        index.entry_point = match entry_point {
+            u32::MAX if index.label_to_slot.is_empty() => None,
+            slot if (slot as usize) < slots && index.alive[slot as usize] => Some(slot),
+            slot => return Err(invalid(format!("entry point {} is not a live node", slot))),
+        };
+        index.in_links = index
+            .links
+            .iter()
+            .map(|levels| vec![Vec::new(); levels.len()])
+            .collect();
+        for (slot, levels) in index.links.iter().enumerate() {
+            for (layer, links) in levels.iter().enumerate() {
+                for &target in links {
+                    match index.in_links[target as usize].get_mut(layer) {
+                        Some(sources) => sources.push(slot as u32),
+                        None => {
+                            return Err(invalid(format!(
+                                "slot {} links to slot {} above its level",
+                                slot, target
+                            )))
+                        }
+                    }
+                }
+            }
+        }
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    const DIM: usize = 8;
+
+    fn build(count: usize, seed: u64) -> (HnswIndex, Vec<f32>) {
+        let data = random_vectors(seed, count, DIM);
+        let mut index = HnswIndex::new(DIM, Metric::L2Squared, 8, 64, seed);
+        for (label, vector) in data.chunks_exact(DIM).enumerate() {
+            assert!(index.insert(label as u32, vector));
+        }
+        (index, data)
+    }
+
+    fn graph_bytes(index: &HnswIndex) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        index.write_graph(&mut writer);
+        writer.into_bytes()
+    }
+
+    fn read(index: &HnswIndex, bytes: &[u8]) -> Result<HnswIndex, DecodeError> {
+        let vectors: HashMap<u32, &[f32]> = index.entries().collect();
+        let mut reader = ByteReader::new(bytes);
+        let graph = HnswIndex::read_graph(&mut reader, DIM, Metric::L2Squared, &vectors)?;
+        reader.finish()?;
+        Ok(graph)
+    }
+
+    fn brute_force(data: &[f32], query: &[f32], k: usize, skip: &[u32]) -> Vec<u32> {
+        let mut scored: Vec<(u32, f32)> = data
+            .chunks_exact(DIM)
+            .enumerate()
+            .map(|(i, v)| (i as u32, Metric::L2Squared.score(v, query)))
+            .filter(|(label, _)| !skip.contains(label))
+            .collect();
+        scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+        scored.iter().take(k).map(|&(label, _)| label).collect()
+    }
+
+    // 存活节点的出边只指向存活节点，且与 in_links 互为反向
+    fn assert_consistent(index: &HnswIndex) {
+        for slot in index.live_slots() {
+            for (layer, links) in index.links[slot].iter().enumerate() {
+                for &target in links {
+                    assert!(
+                        index.alive[target as usize],
+                        "slot {} links to a removed slot",
+                        slot
+                    );
+                    assert!(index.in_links[target as usize][layer].contains(&(slot as u32)));
+                }
+            }
+        }
+        for (slot, levels) in index.in_links.iter().enumerate() {
+            for (layer, sources) in levels.iter().enumerate() {
+                for &source in sources {
+                    assert!(index.links[source as usize][layer].contains(&(slot as u32)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn recall_matches_brute_force() {
+        let (mut index, data) = build(500, 11);
+        index.set_ef_search(64);
+        let queries = random_vectors(12, 50, DIM);
+        let k = 10;
+        let mut hits = 0;
+        for query in queries.chunks_exact(DIM) {
+            let expected = brute_force(&data, query, k, &[]);
+            let found = index.search(query, k).indices();
+            hits += found
+                .iter()
+                .filter(|label| expected.contains(label))
+                .count();
+        }
+        let recall = hits as f64 / (50 * k) as f64;
+        assert!(recall >= 0.95, "recall@10 {:.3}", recall);
+    }
+
+    #[test]
+    fn removed_labels_are_never_returned() {
+        let (mut index, data) = build(300, 13);
+        let removed: Vec<u32> = (0..300).step_by(3).collect();
+        for &label in &removed {
+            assert!(index.remove(label));
+        }
+        assert!(!index.remove(0));
+        assert_eq!(index.length(), 200);
+        assert_consistent(&index);
+
+        index.set_ef_search(64);
+        let mut hits = 0;
+        for query in random_vectors(14, 30, DIM).chunks_exact(DIM) {
+            let found = index.search(query, 10).indices();
+            assert_eq!(found.len(), 10);
+            assert!(found.iter().all(|label| !removed.contains(label)));
+            let expected = brute_force(&data, query, 10, &removed);
+            hits += found
+                .iter()
+                .filter(|label| expected.contains(label))
+                .count();
+        }
+        assert!(hits as f64 / 300.0 >= 0.9, "recall after removal {}", hits);
+    }
+
+    #[test]
+    fn removed_slots_are_reused() {
+        let (mut index, data) = build(50, 15);
+        let slot = index.label_to_slot[&7];
+        assert!(index.remove(7));
+        assert!(!index.contains(7));
+        assert_eq!(index.free_slots, vec![slot]);
+
+        let vector = &data[7 * DIM..8 * DIM];
+        assert!(index.insert(100, vector));
+        assert_eq!(index.label_to_slot[&100], slot);
+        assert!(index.free_slots.is_empty());
+        assert_eq!(index.labels.len(), 50);
+        assert_eq!(index.search(vector, 1).indices(), vec![100]);
+        assert_consistent(&index);
+
+        // 覆盖已有 label 不会泄漏槽位
+        assert!(index.insert(100, &data[..DIM]));
+        assert_eq!(index.length(), 50);
+        assert_eq!(index.labels.len(), 50);
+        assert_consistent(&index);
+    }
+
+    #[test]
+    fn same_seed_builds_the_same_graph() {
+        let (a, _) = build(200, 17);
+        let (b, _) = build(200, 17);
+        assert_eq!(graph_bytes(&a), graph_bytes(&b));
+        let mut c = HnswIndex::new(DIM, Metric::L2Squared, 8, 64, 18);
+        for (label, vector) in random_vectors(17, 200, DIM).chunks_exact(DIM).enumerate() {
+            c.insert(label as u32, vector);
+        }
+        assert_ne!(graph_bytes(&a), graph_bytes(&c));
+    }
+
+    #[test]
+    fn graph_round_trips() {
+        let (mut index, _) = build(100, 1);
+        index.remove(3);
+        index.remove(40);
+        let bytes = graph_bytes(&index);
+        assert_eq!(graph_bytes(&read(&index, &bytes).unwrap()), bytes);
+    }
+
+    #[test]
+    fn oversized_slot_count_is_rejected_before_allocating() {
+        let (index, _) = build(10, 2);
+        let mut bytes = graph_bytes(&index);
+        // 槽位数位于 m、ef、ef_search（各 u32）、rng（u64）、入口、最大层级之后
+        bytes[28..32].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read(&index, &bytes).is_err());
+    }
+
+    #[test]
+    fn inconsistent_free_slots_are_rejected() {
+        let (mut index, _) = build(20, 3);
+        index.remove(5);
+        index.remove(9);
+        let bytes = graph_bytes(&index);
+        let tail = bytes.len() - 8;
+        let free = |bytes: &[u8], i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        let (first, second) = (free(&bytes, tail), free(&bytes, tail + 4));
+
+        // 同一个空闲槽位出现两次
+        let mut duplicated = bytes.clone();
+        duplicated[tail + 4..].copy_from_slice(&first.to_le_bytes());
+        assert!(read(&index, &duplicated).is_err());
+
+        // 引用存活的槽位
+        let mut live = bytes.clone();
+        let live_slot = (0..20u32).find(|&s| s != first && s != second).unwrap();
+        live[tail + 4..].copy_from_slice(&live_slot.to_le_bytes());
+        assert!(read(&index, &live).is_err());
+
+        // 越界
+        let mut outside = bytes.clone();
+        outside[tail + 4..].copy_from_slice(&20u32.to_le_bytes());
+        assert!(read(&index, &outside).is_err());
+
+        // 漏掉一个已删除的槽位
+        let mut missing = bytes[..tail - 4].to_vec();
+        missing.extend_from_slice(&1u32.to_le_bytes());
+        missing.extend_from_slice(&first.to_le_bytes());
+        assert!(read(&index, &missing).is_err());
+    }
+}
@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::filter::{SearchFilter, VectorAttributes};
+use crate::kmeans::KMeans;
+use crate::metric::{for_each_score, packed_count, Metric};
+use crate::topk::{TopKHeap, TopKResults};
+
+// 倒排列表：label 与向量按相同顺序连续存放
+#[derive(Clone, Default)]
+struct PostingList {
+    labels: Vec<u32>,
+    vectors: Vec<f32>,
+}
+
+// IVF 倒排索引：k-means 粗量化器把向量划分到 nlist 个倒排列表，
+// 查询时只扫描离查询最近的 nprobe 个列表。
+// 质心按 L2 k-means 训练（余弦为球面 k-means），向量分配和列表探测使用同一度量，
+// 列表内再按索引的 metric 打分
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct IvfIndex {
+    dim: usize,
+    metric: Metric,
+    nlist: usize,
+    nprobe: usize,
+    centroids: Vec<f32>,
+    lists: Vec<PostingList>,
+    label_to_list: HashMap<u32, usize>,
+}
+
+#[wasm_bindgen]
+impl IvfIndex {
+    #[wasm_bindgen(constructor)]
+    pub fn new(dim: usize, metric: Metric, nlist: usize) -> IvfIndex {
+        IvfIndex {
+            dim,
+            metric,
+            nlist: nlist.max(1),
+            nprobe: 1,
+            centroids: Vec::new(),
+            lists: Vec::new(),
+            label_to_list: HashMap::new(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nlist(&self) -> usize {
+        self.nlist
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nprobe(&self) -> usize {
+        self.nprobe
+    }
+
+    // 探测的列表数，越大召回越高、速度越慢
+    pub fn set_nprobe(&mut self, nprobe: usize) {
+        self.nprobe = nprobe.clamp(1, self.nlist);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_trained(&self) -> bool {
+        !self.centroids.is_empty()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.label_to_list.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn centroids(&self) -> Vec<f32> {
+        self.centroids.clone()
+    }
+
+    // 各倒排列表的向量数
+    pub fn list_sizes(&self) -> Vec<u32> {
+        self.lists.iter().map(|l| l.labels.len() as u32).collect()
+    }
+
+    pub fn contains(&self, label: u32) -> bool {
+        self.label_to_list.contains_key(&label)
+    }
+
+    // 训练粗量化器，样本数少于 nlist 时返回 false；重新训练会清空已有向量
+    pub fn train(&mut self, sample: &[f32], iterations: usize, seed: u64) -> bool {
+        match packed_count(sample, self.dim) {
+            Some(n) if n >= self.nlist => {}
+            _ => return false,
+        }
+        // 余弦度量使用球面 k-means，其余度量使用 L2 k-means
+        let mut kmeans = KMeans::new(self.nlist);
+        kmeans.set_max_iterations(iterations);
+        kmeans.set_seed(seed);
+        kmeans.set_spherical(self.metric == Metric::Cosine);
+        match kmeans.fit(sample, self.dim) {
+            Some(result) => {
+                self.centroids = result.into_centroids();
+                self.lists = vec![PostingList::default(); self.nlist];
+                self.label_to_list.clear();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 新增向量，未训练、label 已存在或维度不符时返回 false
+    pub fn add(&mut self, label: u32, vector: &[f32]) -> bool {
+        if !self.is_trained() || vector.len() != self.dim || self.label_to_list.contains_key(&label)
+        {
+            return false;
+        }
+        let list = self.nearest_list(vector);
+        self.lists[list].labels.push(label);
+        self.lists[list].vectors.extend_from_slice(vector);
+        self.label_to_list.insert(label, list);
+        true
+    }
+
+    // 批量新增，返回成功数量
+    pub fn add_batch(&mut self, labels: &[u32], vectors: &[f32]) -> usize {
+        if self.dim == 0 || vectors.len() != labels.len() * self.dim {
+            return 0;
+        }
+        labels
+            .iter()
+            .zip(vectors.chunks_exact(self.dim))
+            .filter(|(&label, vector)| self.add(label, vector))
+            .count()
+    }
+
+    // 从所在列表中 swap-remove
+    pub fn remove(&mut self, label: u32) -> bool {
+        let list = match self.label_to_list.remove(&label) {
+            Some(list) => &mut self.lists[list],
+            None => return false,
+        };
+        let pos = match list.labels.iter().position(|&l| l == label) {
+            Some(pos) => pos,
+            None => return false,
+        };
+        let last = list.labels.len() - 1;
+        list.labels.swap_remove(pos);
+        if pos != last {
+            let (head, tail) = list.vectors.split_at_mut(last * self.dim);
+            head[pos * self.dim..(pos + 1) * self.dim].copy_from_slice(tail);
+        }
+        list.vectors.truncate(last * self.dim);
+        true
+    }
+
+    pub fn get(&self, label: u32) -> Option<Vec<f32>> {
+        let list = &self.lists[*self.label_to_list.get(&label)?];
+        let pos = list.labels.iter().position(|&l| l == label)?;
+        Some(list.vectors[pos * self.dim..(pos + 1) * self.dim].to_vec())
+    }
+
+    // 清空向量，保留已训练的质心
+    pub fn clear(&mut self) {
+        for list in &mut self.lists {
+            list.labels.clear();
+            list.vectors.clear();
+        }
+        self.label_to_list.clear();
+    }
+
+    // 扫描最近的 nprobe 个列表，indices 为 label
+    pub fn search(&self, query: &[f32], k: usize) -> TopKResults {
+        if query.len() != self.dim || !self.is_trained() {
+            return TopKResults::default();
+        }
+        let mut heap = TopKHeap::for_metric(k, self.metric);
+        for list in self.probe_lists(query) {
+            let list = &self.lists[list];
+            for_each_score(self.metric, &list.vectors, query, self.dim, |i, score| {
+                heap.push(list.labels[i], score)
+            });
+        }
+        heap.into_results()
+    }
+
+    // 按质心远近依次扫描列表并跳过不满足 filter 的向量；
+    // 扫描完 nprobe 个列表后若匹配结果不足 k 个，继续扫描更远的列表
+    pub fn search_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        attributes: &VectorAttributes,
+        filter: &SearchFilter,
+    ) -> TopKResults {
+        if query.len() != self.dim || !self.is_trained() {
+            return TopKResults::default();
+        }
+        let mut heap = TopKHeap::for_metric(k, self.metric);
+        let mut found = 0;
+        for (probed, list) in self
+            .ranked_lists(query, self.lists.len())
+            .into_iter()
+            .enumerate()
+        {
+            if probed >= self.nprobe && found >= k {
+                break;
+            }
+            let list = &self.lists[list];
+            for (&label, vector) in list.labels.iter().zip(list.vectors.chunks_exact(self.dim)) {
+                if filter.matches(attributes, label) {
+                    heap.push(label, self.metric.score(vector, query));
+                    found += 1;
+                }
+            }
+        }
+        heap.into_results()
+    }
+}
+
+impl IvfIndex {
+    // 与 k-means 训练一致的粗量化度量
+    fn coarse_metric(&self) -> Metric {
+        if self.metric == Metric::Cosine {
+            Metric::Cosine
+        } else {
+            Metric::L2Squared
+        }
+    }
+
+    fn nearest_list(&self, vector: &[f32]) -> usize {
+        self.ranked_lists(vector, 1).first().copied().unwrap_or(0)
+    }
+
+    // 按粗量化度量挑选离查询最近的 nprobe 个质心
+    fn probe_lists(&self, query: &[f32]) -> Vec<usize> {
+        self.ranked_lists(query, self.nprobe)
+    }
+
+    // 离查询最近的 count 个列表，由近到远
+    fn ranked_lists(&self, query: &[f32], count: usize) -> Vec<usize> {
+        let coarse = self.coarse_metric();
+        let mut heap = TopKHeap::for_metric(count, coarse);
+        for_each_score(coarse, &self.centroids, query, self.dim, |c, score| {
+            heap.push(c as u32, score)
+        });
+        heap.into_results()
+            .indices()
+            .into_iter()
+            .map(|c| c as usize)
+            .collect()
+    }
+}
+
+// 快照读写：写出质心和各倒排列表的 label，向量由快照的向量段按 label 提供
+impl IvfIndex {
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (u32, &[f32])> + '_ {
+        self.lists.iter().flat_map(move |list| {
+            list.labels
+                .iter()
+                .copied()
+                .zip(list.vectors.chunks_exact(self.dim))
+        })
+    }
+
+    pub(crate) fn write_lists(&self, writer: &mut ByteWriter) {
+        writer.u32(self.nlist as u32);
+        writer.u32(self.nprobe as u32);
+        writer.u32(self.centroids.len() as u32);
+        writer.f32_slice(&self.centroids);
+        writer.u32(self.lists.len() as u32);
+        for list in &self.lists {
+            writer.u32(list.labels.len() as u32);
+            writer.u32_slice(&list.labels);
+        }
+    }
+
+    pub(crate) fn read_lists(
+        reader: &mut ByteReader,
+        dim: usize,
+        metric: Metric,
+        vectors: &HashMap<u32, &[f32]>,
+    ) -> Result<IvfIndex, DecodeError> {
+        let invalid = |msg: String| DecodeError::Invalid(format!("IVF lists: {}", msg));
+        let nlist = reader.u32()? as usize;
+        let nprobe = reader.u32()? as usize;
+        let mut index = IvfIndex::new(dim, metric, nlist);
+        index.set_nprobe(nprobe);
+
+        let centroid_len = reader.u32()? as usize;
+        index.centroids = reader.f32_vec(centroid_len)?;
+        let list_count = reader.u32()? as usize;
+        let trained = centroid_len > 0;
+        let expected_len = nlist
+            .checked_mul(dim)
+            .ok_or_else(|| invalid(format!("{} lists x {} dimensions overflows", nlist, dim)))?;
+        if (trained && (centroid_len != expected_len || list_count != nlist))
+            || (!trained && list_count != 0)
+        {
+            return Err(invalid(format!(
+                "{} centroid values and {} lists for nlist {}",
+                centroid_len, list_count, nlist
+            )));
+        }
+
+        index.lists = vec![PostingList::default(); list_count];
+        for (i, list) in index.lists.iter_mut().enumerate() {
+            let count = reader.u32()? as usize;
+            // 每个 label 只能出现一次，剩余的向量数就是该列表长度的上限
+            let remaining = vectors.len() - index.label_to_list.len();
+            if count > remaining {
+                return Err(invalid(format!(
+                    "list {} has {} labels but only {} vectors remain",
+                    i, count, remaining
+                )));
+            }
+            list.labels = reader.u32_vec(count)?;
+            list.vectors.reserve(count.checked_mul(dim).ok_or_else(|| {
+                invalid(format!("list {} size {} x {} overflows", i, count, dim))
+            })?);
+            for &label in &list.labels {
+                let vector = vectors.get(&label).ok_or_else(|| {
+                    invalid(format!(
+                        "label {} is missing from the vector section",
+                        label
+                    ))
+                })?;
+                if index.label_to_list.insert(label, i).is_some() {
+                    return Err(invalid(format!("label {} appears twice", label)));
+                }
+                list.vectors.extend_from_slice(vector);
+            }
+        }
+        if index.label_to_list.len() != vectors.len() {
+            return Err(invalid(format!(
+                "{} listed labels but {} vectors",
+                index.label_to_list.len(),
+                vectors.len()
+            )));
+        }
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    const DIM: usize = 12;
+    const COUNT: usize = 300;
+    const NLIST: usize = 8;
+
+    fn build(metric: Metric) -> (IvfIndex, Vec<f32>) {
+        let data = random_vectors(11, COUNT, DIM);
+        let mut index = IvfIndex::new(DIM, metric, NLIST);
+        assert!(index.train(&data, 20, 5));
+        let labels: Vec<u32> = (0..COUNT as u32).map(|i| i * 3).collect();
+        assert_eq!(index.add_batch(&labels, &data), COUNT);
+        (index, data)
+    }
+
+    fn brute_force(metric: Metric, data: &[f32], query: &[f32], k: usize) -> Vec<u32> {
+        let mut heap = TopKHeap::for_metric(k, metric);
+        for (i, vector) in data.chunks_exact(DIM).enumerate() {
+            heap.push(i as u32 * 3, metric.score(vector, query));
+        }
+        heap.into_results().indices()
+    }
+
+    #[test]
+    fn probing_every_list_matches_brute_force() {
+        let queries = random_vectors(12, 10, DIM);
+        for metric in [Metric::Dot, Metric::Cosine, Metric::L2Squared, Metric::L1] {
+            let (mut index, data) = build(metric);
+            index.set_nprobe(NLIST);
+            for query in queries.chunks_exact(DIM) {
+                assert_eq!(
+                    index.search(query, 10).indices(),
+                    brute_force(metric, &data, query, 10),
+                    "{:?}",
+                    metric
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn vectors_are_assigned_to_their_training_cell() {
+        // 分配与训练使用同一度量：Dot / L1 也按 L2 选最近的质心
+        for metric in [Metric::Dot, Metric::Cosine, Metric::L2Squared, Metric::L1] {
+            let (index, data) = build(metric);
+            let coarse = if metric == Metric::Cosine {
+                Metric::Cosine
+            } else {
+                Metric::L2Squared
+            };
+            for (i, vector) in data.chunks_exact(DIM).enumerate() {
+                let mut heap = TopKHeap::for_metric(1, coarse);
+                for (c, centroid) in index.centroids.chunks_exact(DIM).enumerate() {
+                    heap.push(c as u32, coarse.score(centroid, vector));
+                }
+                let nearest = heap.into_results().indices()[0] as usize;
+                assert_eq!(
+                    index.label_to_list[&(i as u32 * 3)],
+                    nearest,
+                    "{:?}",
+                    metric
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn removed_labels_disappear() {
+        let (mut index, data) = build(Metric::L2Squared);
+        index.set_nprobe(NLIST);
+        let removed: Vec<u32> = (0..COUNT as u32).step_by(2).map(|i| i * 3).collect();
+        for &label in &removed {
+            assert!(index.remove(label));
+            assert!(!index.remove(label));
+            assert!(!index.contains(label));
+            assert!(index.get(label).is_none());
+        }
+        assert_eq!(index.length(), COUNT - removed.len());
+        assert_eq!(
+            index.list_sizes().iter().sum::<u32>() as usize,
+            index.length()
+        );
+
+        // swap-remove 之后剩余向量仍与 label 对应
+        for (i, vector) in data.chunks_exact(DIM).enumerate() {
+            let label = i as u32 * 3;
+            if i % 2 == 1 {
+                assert_eq!(index.get(label).as_deref(), Some(vector));
+            }
+        }
+        for query in random_vectors(13, 5, DIM).chunks_exact(DIM) {
+            let results = index.search(query, 20).indices();
+            assert_eq!(results.len(), 20);
+            assert!(results.iter().all(|label| !removed.contains(label)));
+        }
+
+        // 删除后可以用同一 label 重新加入
+        assert!(index.add(0, &data[..DIM]));
+        assert_eq!(index.search(&data[..DIM], 1).indices(), vec![0]);
+    }
+
+    fn read(bytes: &[u8], vectors: &HashMap<u32, &[f32]>) -> Result<IvfIndex, DecodeError> {
+        let mut reader = ByteReader::new(bytes);
+        let index = IvfIndex::read_lists(&mut reader, DIM, Metric::L2Squared, vectors)?;
+        reader.finish()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn lists_round_trip() {
+        let (mut index, _) = build(Metric::L2Squared);
+        index.set_nprobe(3);
+        let mut writer = ByteWriter::new();
+        index.write_lists(&mut writer);
+        let bytes = writer.into_bytes();
+
+        let vectors: HashMap<u32, &[f32]> = index.entries().collect();
+        let restored = read(&bytes, &vectors).unwrap();
+        assert_eq!(restored.nprobe(), 3);
+        assert_eq!(restored.centroids(), index.centroids());
+        assert_eq!(restored.list_sizes(), index.list_sizes());
+        let query = random_vectors(14, 1, DIM);
+        assert_eq!(
+            restored.search(&query, 10).indices(),
+            index.search(&query, 10).indices()
+        );
+    }
+
+    #[test]
+    fn oversized_list_count_is_rejected() {
+        let data = random_vectors(15, 4, DIM);
+        let vectors: HashMap<u32, &[f32]> = data
+            .chunks_exact(DIM)
+            .enumerate()
+            .map(|(i, v)| (i as u32, v))
+            .collect();
+
+        // 第一个列表声称有 u32::MAX 个 label，必须在分配前报错
+        let mut writer = ByteWriter::new();
+        writer.u32(2);
+        writer.u32(1);
+        writer.u32(2 * DIM as u32);
+        writer.f32_slice(&data[..2 * DIM]);
+        writer.u32(2);
+        writer.u32(u32::MAX);
+        assert!(matches!(
+            read(&writer.into_bytes(), &vectors),
+            Err(DecodeError::Invalid(_))
+        ));
+
+        // 列表中的 label 重复或缺少对应向量
+        for labels in [[0u32, 0], [0, 9]] {
+            let mut writer = ByteWriter::new();
+            writer.u32(2);
+            writer.u32(1);
+            writer.u32(2 * DIM as u32);
+            writer.f32_slice(&data[..2 * DIM]);
+            writer.u32(2);
+            writer.u32(2);
+            writer.u32_slice(&labels);
+            writer.u32(2);
+            writer.u32_slice(&[1, 2]);
+            assert!(matches!(
+                read(&writer.into_bytes(), &vectors),
+                Err(DecodeError::Invalid(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use wide::{f32x4, i16x8, i32x4};
+
+pub(crate) const SIMD_LANES: usize = 4;
+
+// 从切片偏移处加载 4 个 f32 到 SIMD 寄存器
+#[inline(always)]
+pub(crate) fn load_f32x4(slice: &[f32], offset: usize) -> f32x4 {
+    let array: [f32; 4] = slice[offset..offset + SIMD_LANES].try_into().unwrap();
+    f32x4::new(array)
+}
+
+#[inline(always)]
+fn simd_len(len: usize) -> usize {
+    len - (len % SIMD_LANES)
+}
+
+// 仅计算点积 (SIMD)
+#[inline]
+pub(crate) fn dot_product_simd_only(vec_a: &[f32], vec_b: &[f32]) -> f32 {
+    let len = vec_a.len();
+    let simd_len = simd_len(len);
+    let mut dot_sum_simd = f32x4::ZERO;
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        dot_sum_simd = load_f32x4(vec_a, i).mul_add(load_f32x4(vec_b, i), dot_sum_simd);
+    }
+
+    let mut dot_product = dot_sum_simd.reduce_add();
+    for i in simd_len..len {
+        dot_product += vec_a[i] * vec_b[i];
+    }
+    dot_product
+}
+
+// SIMD 计算范数平方
+#[inline]
+pub(crate) fn compute_norm_squared_simd(vec: &[f32]) -> f32 {
+    let simd_len = simd_len(vec.len());
+    let mut norm_sum_simd = f32x4::ZERO;
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let chunk = load_f32x4(vec, i);
+        norm_sum_simd = chunk.mul_add(chunk, norm_sum_simd);
+    }
+
+    let mut norm_sq = norm_sum_simd.reduce_add();
+    for x in &vec[simd_len..] {
+        norm_sq += x * x;
+    }
+    norm_sq
+}
+
+// 同时计算点积和 vec_a 的范数平方
+#[inline]
+pub(crate) fn dot_product_and_norm_simd(vec_a: &[f32], vec_b: &[f32]) -> (f32, f32) {
+    let len = vec_a.len(); // 假设 vec_a.len() == vec_b.len()
+    let simd_len = simd_len(len);
+
+    let mut dot_sum_simd = f32x4::ZERO;
+    let mut norm_a_sum_simd = f32x4::ZERO;
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let a_chunk = load_f32x4(vec_a, i);
+        let b_chunk = load_f32x4(vec_b, i);
+
+        dot_sum_simd = a_chunk.mul_add(b_chunk, dot_sum_simd);
+        norm_a_sum_simd = a_chunk.mul_add(a_chunk, norm_a_sum_simd);
+    }
+
+    let mut dot_product = dot_sum_simd.reduce_add();
+    let mut norm_a_sq = norm_a_sum_simd.reduce_add();
+
+    for i in simd_len..len {
+        dot_product += vec_a[i] * vec_b[i];
+        norm_a_sq += vec_a[i] * vec_a[i];
+    }
+    (dot_product, norm_a_sq)
+}
+
+// 同时计算点积和两个向量的范数平方
+#[inline]
+pub(crate) fn dot_product_and_norms_simd(vec_a: &[f32], vec_b: &[f32]) -> (f32, f32, f32) {
+    let len = vec_a.len();
+    let simd_len = simd_len(len);
+
+    let mut dot_sum_simd = f32x4::ZERO;
+    let mut norm_a_sum_simd = f32x4::ZERO;
+    let mut norm_b_sum_simd = f32x4::ZERO;
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let a_chunk = load_f32x4(vec_a, i);
+        let b_chunk = load_f32x4(vec_b, i);
+
+        // 使用 Fused Multiply-Add (FMA)
+        dot_sum_simd = a_chunk.mul_add(b_chunk, dot_sum_simd);
+        norm_a_sum_simd = a_chunk.mul_add(a_chunk, norm_a_sum_simd);
+        norm_b_sum_simd = b_chunk.mul_add(b_chunk, norm_b_sum_simd);
+    }
+
+    // 水平求和
+    let mut dot_product = dot_sum_simd.reduce_add();
+    let mut norm_a_sq = norm_a_sum_simd.reduce_add();
+    let mut norm_b_sq = norm_b_sum_simd.reduce_add();
+
+    // 处理剩余元素
+    for i in simd_len..len {
+        dot_product += vec_a[i] * vec_b[i];
+        norm_a_sq += vec_a[i] * vec_a[i];
+        norm_b_sq += vec_b[i] * vec_b[i];
+    }
+    (dot_product, norm_a_sq, norm_b_sq)
+}
+
+// 欧氏距离的平方 (SIMD)
+#[inline]
+pub(crate) fn l2_squared_simd(vec_a: &[f32], vec_b: &[f32]) -> f32 {
+    let len = vec_a.len();
+    let simd_len = simd_len(len);
+    let mut sum_simd = f32x4::ZERO;
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let diff = load_f32x4(vec_a, i) - load_f32x4(vec_b, i);
+        sum_simd = diff.mul_add(diff, sum_simd);
+    }
+
+    let mut sum = sum_simd.reduce_add();
+    for i in simd_len..len {
+        let diff = vec_a[i] - vec_b[i];
+        sum += diff * diff;
+    }
+    sum
+}
+
+// 曼哈顿距离 (SIMD)
+#[inline]
+pub(crate) fn l1_simd(vec_a: &[f32], vec_b: &[f32]) -> f32 {
+    let len = vec_a.len();
+    let simd_len = simd_len(len);
+    let mut sum_simd = f32x4::ZERO;
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        sum_simd += (load_f32x4(vec_a, i) - load_f32x4(vec_b, i)).abs();
+    }
+
+    let mut sum = sum_simd.reduce_add();
+    for i in simd_len..len {
+        sum += (vec_a[i] - vec_b[i]).abs();
+    }
+    sum
+}
+
+// acc += row (SIMD)
+#[inline]
+pub(crate) fn add_assign_simd(acc: &mut [f32], row: &[f32]) {
+    let len = acc.len();
+    let simd_len = simd_len(len);
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let sum = (load_f32x4(acc, i) + load_f32x4(row, i)).to_array();
+        acc[i..i + SIMD_LANES].copy_from_slice(&sum);
+    }
+    for i in simd_len..len {
+        acc[i] += row[i];
+    }
+}
+
+// vec *= factor (SIMD)
+#[inline]
+pub(crate) fn scale_in_place_simd(vec: &mut [f32], factor: f32) {
+    let simd_len = simd_len(vec.len());
+    let scale = f32x4::splat(factor);
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let scaled = (load_f32x4(vec, i) * scale).to_array();
+        vec[i..i + SIMD_LANES].copy_from_slice(&scaled);
+    }
+    for x in &mut vec[simd_len..] {
+        *x *= factor;
+    }
+}
+
+// acc += weight * row (SIMD)
+#[inline]
+pub(crate) fn add_scaled_simd(acc: &mut [f32], row: &[f32], weight: f32) {
+    let len = acc.len();
+    let simd_len = simd_len(len);
+    let weight_simd = f32x4::splat(weight);
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let sum = load_f32x4(row, i)
+            .mul_add(weight_simd, load_f32x4(acc, i))
+            .to_array();
+        acc[i..i + SIMD_LANES].copy_from_slice(&sum);
+    }
+    for i in simd_len..len {
+        acc[i] += weight * row[i];
+    }
+}
+
+// acc = max(acc, row) (SIMD)
+#[inline]
+pub(crate) fn max_assign_simd(acc: &mut [f32], row: &[f32]) {
+    let len = acc.len();
+    let simd_len = simd_len(len);
+
+    for i in (0..simd_len).step_by(SIMD_LANES) {
+        let max = load_f32x4(acc, i).max(load_f32x4(row, i)).to_array();
+        acc[i..i + SIMD_LANES].copy_from_slice(&max);
+    }
+    for i in simd_len..len {
+        acc[i] = acc[i].max(row[i]);
+    }
+}
+
+// int8 点积：先扩展到 i16x8，再用成对乘加累积到 i32x4
+#[inline]
+pub(crate) fn dot_i8_simd(vec_a: &[i8], vec_b: &[i8]) -> i32 {
+    const I8_LANES: usize = 8;
+    let len = vec_a.len();
+    let simd_len = len - (len % I8_LANES);
+    let mut dot_sum_simd = i32x4::ZERO;
+
+    for i in (0..simd_len).step_by(I8_LANES) {
+        let a_chunk = i16x8::new(std::array::from_fn(|j| vec_a[i + j] as i16));
+        let b_chunk = i16x8::new(std::array::from_fn(|j| vec_b[i + j] as i16));
+        dot_sum_simd += a_chunk.dot(b_chunk);
+    }
+
+    let mut dot_product = dot_sum_simd.reduce_add();
+    for i in simd_len..len {
+        dot_product += vec_a[i] as i32 * vec_b[i] as i32;
+    }
+    dot_product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    // 覆盖空向量、不足一组 SIMD 以及尾部 1~3 个元素的情况
+    const LENGTHS: [usize; 9] = [0, 1, 3, 4, 5, 7, 8, 13, 130];
+
+    fn assert_close(actual: f32, expected: f64) {
+        assert!(
+            (actual as f64 - expected).abs() <= 1e-5 * expected.abs().max(1.0),
+            "{} vs {}",
+            actual,
+            expected
+        );
+    }
+
+    fn pair(len: usize) -> (Vec<f32>, Vec<f32>) {
+        (
+            random_vectors(len as u64, 1, len),
+            random_vectors(1000 + len as u64, 1, len),
+        )
+    }
+
+    fn scalar_dot(a: &[f32], b: &[f32]) -> f64 {
+        a.iter().zip(b).map(|(&x, &y)| x as f64 * y as f64).sum()
+    }
+
+    #[test]
+    fn reductions_match_scalar() {
+        for len in LENGTHS {
+            let (a, b) = pair(len);
+            let dot = scalar_dot(&a, &b);
+            let norm_a = scalar_dot(&a, &a);
+            let norm_b = scalar_dot(&b, &b);
+            let l2: f64 = a
+                .iter()
+                .zip(&b)
+                .map(|(&x, &y)| (x as f64 - y as f64).powi(2))
+                .sum();
+            let l1: f64 = a
+                .iter()
+                .zip(&b)
+                .map(|(&x, &y)| (x as f64 - y as f64).abs())
+                .sum();
+
+            assert_close(dot_product_simd_only(&a, &b), dot);
+            assert_close(compute_norm_squared_simd(&a), norm_a);
+            let (d, n) = dot_product_and_norm_simd(&a, &b);
+            assert_close(d, dot);
+            assert_close(n, norm_a);
+            let (d, na, nb) = dot_product_and_norms_simd(&a, &b);
+            assert_close(d, dot);
+            assert_close(na, norm_a);
+            assert_close(nb, norm_b);
+            assert_close(l2_squared_simd(&a, &b), l2);
+            assert_close(l1_simd(&a, &b), l1);
+        }
+    }
+
+    #[test]
+    fn element_wise_kernels_match_scalar() {
+        for len in LENGTHS {
+            let (a, b) = pair(len);
+
+            let mut acc = a.clone();
+            add_assign_simd(&mut acc, &b);
+            let expected: Vec<f32> = a.iter().zip(&b).map(|(x, y)| x + y).collect();
+            assert_eq!(acc, expected);
+
+            let mut acc = a.clone();
+            scale_in_place_simd(&mut acc, -0.5);
+            let expected: Vec<f32> = a.iter().map(|x| x * -0.5).collect();
+            assert_eq!(acc, expected);
+
+            let mut acc = a.clone();
+            add_scaled_simd(&mut acc, &b, 0.25);
+            for ((&out, &x), &y) in acc.iter().zip(&a).zip(&b) {
+                assert_close(out, x as f64 + 0.25 * y as f64);
+            }
+
+            let mut acc = a.clone();
+            max_assign_simd(&mut acc, &b);
+            let expected: Vec<f32> = a.iter().zip(&b).map(|(x, y)| x.max(*y)).collect();
+            assert_eq!(acc, expected);
+        }
+    }
+
+    #[test]
+    fn dot_i8_matches_scalar() {
+        for len in [0, 1, 7, 8, 9, 15, 16, 17, 300] {
+            let a: Vec<i8> = (0..len).map(|i| (i * 37 % 255) as u8 as i8).collect();
+            let b: Vec<i8> = (0..len).map(|i| (i * 91 % 253) as u8 as i8).collect();
+            let expected: i32 = a.iter().zip(&b).map(|(&x, &y)| x as i32 * y as i32).sum();
+            assert_eq!(dot_i8_simd(&a, &b), expected, "len {}", len);
+        }
+        // 极值不会在 i16 成对乘加中溢出
+        let a = vec![-128i8; 64];
+        assert_eq!(dot_i8_simd(&a, &a), 64 * 128 * 128);
+        let b = vec![127i8; 64];
+        assert_eq!(dot_i8_simd(&a, &b), -64 * 128 * 127);
+    }
+}
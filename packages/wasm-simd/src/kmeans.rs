@@ -0,0 +1,485 @@
+use wasm_bindgen::prelude::*;
+
+use crate::kernels::{
+    add_assign_simd, dot_product_simd_only, l2_squared_simd, scale_in_place_simd,
+};
+use crate::metric::packed_count;
+use crate::rng::Rng;
+use crate::unit::normalize_in_place;
+
+// 初始质心的选取方式
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KMeansInit {
+    // 随机选取 k 个不同样本
+    Random = 0,
+    // k-means++：按到已选质心距离的平方加权抽样
+    KMeansPlusPlus = 1,
+}
+
+// 迭代中出现空簇时的处理方式
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmptyClusterPolicy {
+    // 用当前离所属质心最远的样本重新播种
+    Farthest = 0,
+    // 随机样本重新播种
+    Random = 1,
+    // 保留上一轮的质心
+    Keep = 2,
+}
+
+// 返回最近的质心及其 L2 平方距离
+#[inline]
+pub(crate) fn nearest_centroid(vector: &[f32], centroids: &[f32], dim: usize) -> (usize, f32) {
+    let mut best = (0usize, f32::INFINITY);
+    for (c, centroid) in centroids.chunks_exact(dim).enumerate() {
+        let distance = l2_squared_simd(vector, centroid);
+        if distance < best.1 {
+            best = (c, distance);
+        }
+    }
+    best
+}
+
+// 球面 k-means 下的最近质心，距离为 1 - 余弦（向量和质心均已归一化）
+#[inline]
+fn nearest_centroid_spherical(vector: &[f32], centroids: &[f32], dim: usize) -> (usize, f32) {
+    let mut best = (0usize, f32::INFINITY);
+    for (c, centroid) in centroids.chunks_exact(dim).enumerate() {
+        let distance = 1.0 - dot_product_simd_only(vector, centroid);
+        if distance < best.1 {
+            best = (c, distance);
+        }
+    }
+    best
+}
+
+// k-means 聚类配置，new 之后用 set_* 调整参数再调用 fit
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct KMeans {
+    k: usize,
+    max_iterations: usize,
+    seed: u64,
+    spherical: bool,
+    init: KMeansInit,
+    empty_cluster_policy: EmptyClusterPolicy,
+    tolerance: f64,
+}
+
+#[wasm_bindgen]
+impl KMeans {
+    #[wasm_bindgen(constructor)]
+    pub fn new(k: usize) -> KMeans {
+        KMeans {
+            k,
+            max_iterations: 25,
+            seed: 0,
+            spherical: false,
+            init: KMeansInit::KMeansPlusPlus,
+            empty_cluster_policy: EmptyClusterPolicy::Farthest,
+            tolerance: 1e-4,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations.max(1);
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn spherical(&self) -> bool {
+        self.spherical
+    }
+
+    // 球面 k-means：样本和质心都归一化，按余弦相似度分配，适合归一化的 embedding
+    pub fn set_spherical(&mut self, spherical: bool) {
+        self.spherical = spherical;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn init(&self) -> KMeansInit {
+        self.init
+    }
+
+    pub fn set_init(&mut self, init: KMeansInit) {
+        self.init = init;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn empty_cluster_policy(&self) -> EmptyClusterPolicy {
+        self.empty_cluster_policy
+    }
+
+    pub fn set_empty_cluster_policy(&mut self, policy: EmptyClusterPolicy) {
+        self.empty_cluster_policy = policy;
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    // inertia 相对下降量低于 tolerance 时提前停止，0 表示只在分配不再变化时停止
+    pub fn set_tolerance(&mut self, tolerance: f64) {
+        self.tolerance = tolerance.max(0.0);
+    }
+
+    // 对打包向量聚类，样本数少于 k 或维度不符时返回 None
+    pub fn fit(&self, data: &[f32], dim: usize) -> Option<KMeansResult> {
+        let count = packed_count(data, dim)?;
+        if self.k == 0 || count < self.k {
+            return None;
+        }
+
+        let normalized;
+        let data = if self.spherical {
+            let mut copy = data.to_vec();
+            for vector in copy.chunks_exact_mut(dim) {
+                normalize_in_place(vector);
+            }
+            normalized = copy;
+            &normalized[..]
+        } else {
+            data
+        };
+
+        let k = self.k;
+        let mut rng = Rng::new(self.seed);
+        let mut centroids = match self.init {
+            KMeansInit::Random => self.init_random(data, dim, count, &mut rng),
+            KMeansInit::KMeansPlusPlus => self.init_plus_plus(data, dim, count, &mut rng),
+        };
+
+        let mut assignments = vec![u32::MAX; count];
+        let mut distances = vec![0.0f32; count];
+        let mut sums = vec![0.0f32; k * dim];
+        let mut counts = vec![0usize; k];
+        let mut previous_inertia = f64::INFINITY;
+        let mut inertia;
+        let mut iterations = 0;
+        let mut converged = false;
+
+        loop {
+            // 分配步骤
+            iterations += 1;
+            let mut changed = false;
+            inertia = 0.0;
+            for (i, vector) in data.chunks_exact(dim).enumerate() {
+                let (c, distance) = self.nearest(vector, &centroids, dim);
+                distances[i] = distance;
+                inertia += distance as f64;
+                if assignments[i] != c as u32 {
+                    assignments[i] = c as u32;
+                    changed = true;
+                }
+            }
+
+            let stalled = previous_inertia.is_finite()
+                && (previous_inertia - inertia).abs() <= self.tolerance * previous_inertia;
+            if !changed || stalled {
+                converged = true;
+                break;
+            }
+            if iterations >= self.max_iterations {
+                break;
+            }
+            previous_inertia = inertia;
+
+            // 更新步骤
+            sums.fill(0.0);
+            counts.fill(0);
+            for (vector, &c) in data.chunks_exact(dim).zip(&assignments) {
+                let c = c as usize;
+                add_assign_simd(&mut sums[c * dim..(c + 1) * dim], vector);
+                counts[c] += 1;
+            }
+            for c in 0..k {
+                let centroid = &mut centroids[c * dim..(c + 1) * dim];
+                if counts[c] > 0 {
+                    centroid.copy_from_slice(&sums[c * dim..(c + 1) * dim]);
+                    scale_in_place_simd(centroid, 1.0 / counts[c] as f32);
+                } else {
+                    let source = match self.empty_cluster_policy {
+                        EmptyClusterPolicy::Farthest => {
+                            let farthest = distances
+                                .iter()
+                                .enumerate()
+                                .max_by(|a, b| a.1.total_cmp(b.1))
+                                .map(|(i, _)| i)
+                                .unwrap_or(0);
+                            // 避免多个空簇选中同一个样本
+                            distances[farthest] = 0.0;
+                            Some(farthest)
+                        }
+                        EmptyClusterPolicy::Random => Some(rng.below(count)),
+                        EmptyClusterPolicy::Keep => None,
+                    };
+                    if let Some(i) = source {
+                        centroid.copy_from_slice(&data[i * dim..(i + 1) * dim]);
+                    }
+                }
+                if self.spherical {
+                    normalize_in_place(centroid);
+                }
+            }
+        }
+
+        Some(KMeansResult {
+            dim,
+            spherical: self.spherical,
+            assignments,
+            centroids,
+            inertia,
+            iterations,
+            converged,
+        })
+    }
+}
+
+impl KMeans {
+    #[inline]
+    fn nearest(&self, vector: &[f32], centroids: &[f32], dim: usize) -> (usize, f32) {
+        if self.spherical {
+            nearest_centroid_spherical(vector, centroids, dim)
+        } else {
+            nearest_centroid(vector, centroids, dim)
+        }
+    }
+
+    #[inline]
+    fn distance(&self, a: &[f32], b: &[f32]) -> f32 {
+        if self.spherical {
+            (1.0 - dot_product_simd_only(a, b)).max(0.0)
+        } else {
+            l2_squared_simd(a, b)
+        }
+    }
+
+    // 部分 Fisher-Yates 洗牌选出初始质心
+    fn init_random(&self, data: &[f32], dim: usize, count: usize, rng: &mut Rng) -> Vec<f32> {
+        let mut order: Vec<usize> = (0..count).collect();
+        for i in 0..self.k {
+            let j = i + rng.below(count - i);
+            order.swap(i, j);
+        }
+        order[..self.k]
+            .iter()
+            .flat_map(|&i| data[i * dim..(i + 1) * dim].iter().copied())
+            .collect()
+    }
+
+    fn init_plus_plus(&self, data: &[f32], dim: usize, count: usize, rng: &mut Rng) -> Vec<f32> {
+        let mut centroids = Vec::with_capacity(self.k * dim);
+        let first = rng.below(count);
+        centroids.extend_from_slice(&data[first * dim..(first + 1) * dim]);
+
+        let mut min_distances: Vec<f32> = data
+            .chunks_exact(dim)
+            .map(|vector| self.distance(vector, &centroids))
+            .collect();
+
+        for _ in 1..self.k {
+            // min_distances 已经是距离的平方（球面模式下 1 - 余弦 = ‖a - b‖² / 2），直接作为权重
+            let total: f64 = min_distances.iter().map(|&d| d as f64).sum();
+            let chosen = if total > 0.0 {
+                let mut target = rng.next_f64() * total;
+                let mut chosen = count - 1;
+                for (i, &d) in min_distances.iter().enumerate() {
+                    let w = d as f64;
+                    if target < w {
+                        chosen = i;
+                        break;
+                    }
+                    target -= w;
+                }
+                chosen
+            } else {
+                // 所有样本都与已选质心重合
+                rng.below(count)
+            };
+
+            let centroid = &data[chosen * dim..(chosen + 1) * dim];
+            for (d, vector) in min_distances.iter_mut().zip(data.chunks_exact(dim)) {
+                *d = d.min(self.distance(vector, centroid));
+            }
+            centroids.extend_from_slice(centroid);
+        }
+        centroids
+    }
+}
+
+// 聚类结果：assignments[i] 为第 i 个样本的簇号，centroids 为打包质心（k x dim）
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct KMeansResult {
+    dim: usize,
+    spherical: bool,
+    assignments: Vec<u32>,
+    centroids: Vec<f32>,
+    inertia: f64,
+    iterations: usize,
+    converged: bool,
+}
+
+#[wasm_bindgen]
+impl KMeansResult {
+    #[wasm_bindgen(getter)]
+    pub fn assignments(&self) -> Vec<u32> {
+        self.assignments.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn centroids(&self) -> Vec<f32> {
+        self.centroids.clone()
+    }
+
+    // 各样本到所属质心距离之和（L2 平方；球面模式下为 1 - 余弦）
+    #[wasm_bindgen(getter)]
+    pub fn inertia(&self) -> f64 {
+        self.inertia
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn k(&self) -> usize {
+        self.centroids.len().checked_div(self.dim).unwrap_or(0)
+    }
+
+    pub fn cluster_sizes(&self) -> Vec<u32> {
+        let mut sizes = vec![0u32; self.k()];
+        for &c in &self.assignments {
+            sizes[c as usize] += 1;
+        }
+        sizes
+    }
+
+    // 把新向量分配到最近的质心
+    pub fn predict(&self, vectors: &[f32]) -> Vec<u32> {
+        if packed_count(vectors, self.dim).is_none() {
+            return Vec::new();
+        }
+        vectors
+            .chunks_exact(self.dim)
+            .map(|vector| {
+                let (c, _) = if self.spherical {
+                    let mut unit = vector.to_vec();
+                    normalize_in_place(&mut unit);
+                    nearest_centroid_spherical(&unit, &self.centroids, self.dim)
+                } else {
+                    nearest_centroid(vector, &self.centroids, self.dim)
+                };
+                c as u32
+            })
+            .collect()
+    }
+}
+
+impl KMeansResult {
+    pub(crate) fn into_centroids(self) -> Vec<f32> {
+        self.centroids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 第二个质心的抽样概率应与到第一个质心的距离平方成正比
+    fn second_pick_frequency(data: &[f32], dim: usize, spherical: bool) -> [usize; 3] {
+        let mut counts = [0usize; 3];
+        let mut kmeans = KMeans::new(2);
+        kmeans.set_spherical(spherical);
+        for seed in 0..20_000 {
+            let mut rng = Rng::new(seed);
+            let centroids = kmeans.init_plus_plus(data, dim, 3, &mut rng);
+            if centroids[..dim] != data[..dim] {
+                continue;
+            }
+            let second = data
+                .chunks_exact(dim)
+                .position(|v| v == &centroids[dim..])
+                .unwrap();
+            counts[second] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn plus_plus_samples_proportional_to_squared_distance() {
+        // 以 0 为第一个质心时，1 和 3 的距离平方为 1 和 9，应以 1:9 被选中
+        let counts = second_pick_frequency(&[0.0, 1.0, 3.0], 1, false);
+        let total = (counts[1] + counts[2]) as f64;
+        assert_eq!(counts[0], 0);
+        let far = counts[2] as f64 / total;
+        assert!((far - 0.9).abs() < 0.02, "far point picked {:.3}", far);
+    }
+
+    #[test]
+    fn spherical_plus_plus_samples_proportional_to_one_minus_cosine() {
+        // 单位向量 (1, 0)、(0, 1)、(-1, 0)：1 - 余弦分别为 1 和 2
+        let counts = second_pick_frequency(&[1.0, 0.0, 0.0, 1.0, -1.0, 0.0], 2, true);
+        let total = (counts[1] + counts[2]) as f64;
+        assert_eq!(counts[0], 0);
+        let far = counts[2] as f64 / total;
+        assert!(
+            (far - 2.0 / 3.0).abs() < 0.02,
+            "far point picked {:.3}",
+            far
+        );
+    }
+
+    #[test]
+    fn separated_clusters_are_recovered() {
+        let centers = [[0.0f32, 0.0], [10.0, 0.0], [0.0, 10.0], [10.0, 10.0]];
+        let mut rng = Rng::new(7);
+        let mut data = Vec::new();
+        for i in 0..400 {
+            let center = centers[i % 4];
+            data.push(center[0] + rng.next_f64() as f32 - 0.5);
+            data.push(center[1] + rng.next_f64() as f32 - 0.5);
+        }
+
+        for seed in 0..20 {
+            let mut kmeans = KMeans::new(4);
+            kmeans.set_seed(seed);
+            let result = kmeans.fit(&data, 2).unwrap();
+            assert!(result.converged());
+            assert_eq!(result.cluster_sizes(), vec![100; 4]);
+            // 同一个真实簇的样本必须分到同一个簇
+            let assignments = result.assignments();
+            for i in 4..400 {
+                assert_eq!(assignments[i], assignments[i % 4]);
+            }
+            assert!(result.inertia() < 400.0 * 0.5);
+        }
+    }
+}
@@ -1,5 +1,63 @@
 use wasm_bindgen::prelude::*;
-use wide::f32x4;
+
+mod batch;
+mod binary;
+mod bm25;
+mod cache;
+mod chunker;
+mod codec;
+mod dedup;
+mod delta;
+mod documents;
+mod filter;
+mod fusion;
+mod half;
+mod hash;
+mod hnsw;
+mod ivf;
+mod kernels;
+mod kmeans;
+mod metric;
+mod mmr;
+mod pooling;
+mod pq;
+mod quantize;
+mod rng;
+mod snapshot;
+mod store;
+mod tokenizer;
+mod topk;
+mod unit;
+
+pub use batch::{BatchBuilder, TensorBatch};
+pub use binary::BinaryIndex;
+pub use bm25::Bm25Index;
+pub use cache::{CacheStats, EmbeddingCache};
+pub use chunker::{TextChunk, TextChunker};
+pub use dedup::NearDuplicateIndex;
+pub use delta::DeltaLog;
+pub use documents::{DocumentRecord, DocumentStore};
+pub use filter::{SearchFilter, VectorAttributes};
+pub use half::{HalfFormat, HalfVectors};
+pub use hnsw::HnswIndex;
+pub use ivf::IvfIndex;
+pub use kmeans::{EmptyClusterPolicy, KMeans, KMeansInit, KMeansResult};
+pub use metric::Metric;
+pub use pooling::PoolingMode;
+pub use pq::ProductQuantizer;
+pub use quantize::{Int8Quantizer, Int8Vectors, QuantizationGranularity};
+pub use snapshot::{Snapshot, SnapshotIndexKind};
+pub use store::VectorStore;
+pub use tokenizer::{
+    BatchEncoding, Encoding, PaddingSide, PaddingStrategy, Tokenizer, TruncationSide,
+    TruncationStrategy,
+};
+pub use topk::TopKResults;
+
+use kernels::{dot_product_and_norms_simd, dot_product_simd_only};
+use metric::{batch_scores, cosine_from_parts, packed_count, score_matrix};
+use topk::batch_top_k;
+use unit::{debug_check_unit_vectors, non_unit_indices, normalize_in_place, UNIT_NORM_TOLERANCE};
 
 // 设置 panic hook 以便在浏览器中调试
 #[wasm_bindgen(start)]
@@ -10,6 +68,12 @@ pub fn main() {
 #[wasm_bindgen]
 pub struct SIMDMath;
 
+impl Default for SIMDMath {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl SIMDMath {
     #[wasm_bindgen(constructor)]
@@ -17,229 +81,209 @@ impl SIMDMath {
         SIMDMath
     }
 
-    // 辅助函数：仅计算点积 (SIMD)
-    #[inline]
-    fn dot_product_simd_only(&self, vec_a: &[f32], vec_b: &[f32]) -> f32 {
-        let len = vec_a.len();
-        let simd_lanes = 4;
-        let simd_len = len - (len % simd_lanes);
-        let mut dot_sum_simd = f32x4::ZERO;
-
-        for i in (0..simd_len).step_by(simd_lanes) {
-            // 使用 try_from 和 new 方法，这是 wide 库的正确 API
-            let a_array: [f32; 4] = vec_a[i..i + simd_lanes].try_into().unwrap();
-            let b_array: [f32; 4] = vec_b[i..i + simd_lanes].try_into().unwrap();
-            let a_chunk = f32x4::new(a_array);
-            let b_chunk = f32x4::new(b_array);
-            dot_sum_simd = a_chunk.mul_add(b_chunk, dot_sum_simd);
-        }
-
-        let mut dot_product = dot_sum_simd.reduce_add();
-        for i in simd_len..len {
-            dot_product += vec_a[i] * vec_b[i];
-        }
-        dot_product
-    }
-
     #[wasm_bindgen]
     pub fn cosine_similarity(&self, vec_a: &[f32], vec_b: &[f32]) -> f32 {
         if vec_a.len() != vec_b.len() || vec_a.is_empty() {
             return 0.0;
         }
 
-        let len = vec_a.len();
-        let simd_lanes = 4;
-        let simd_len = len - (len % simd_lanes);
-
-        let mut dot_sum_simd = f32x4::ZERO;
-        let mut norm_a_sum_simd = f32x4::ZERO;
-        let mut norm_b_sum_simd = f32x4::ZERO;
-
-        // SIMD 处理
-        for i in (0..simd_len).step_by(simd_lanes) {
-            let a_array: [f32; 4] = vec_a[i..i + simd_lanes].try_into().unwrap();
-            let b_array: [f32; 4] = vec_b[i..i + simd_lanes].try_into().unwrap();
-            let a_chunk = f32x4::new(a_array);
-            let b_chunk = f32x4::new(b_array);
-
-            // 使用 Fused Multiply-Add (FMA)
-            dot_sum_simd = a_chunk.mul_add(b_chunk, dot_sum_simd);
-            norm_a_sum_simd = a_chunk.mul_add(a_chunk, norm_a_sum_simd);
-            norm_b_sum_simd = b_chunk.mul_add(b_chunk, norm_b_sum_simd);
-        }
-
-        // 水平求和
-        let mut dot_product = dot_sum_simd.reduce_add();
-        let mut norm_a_sq = norm_a_sum_simd.reduce_add();
-        let mut norm_b_sq = norm_b_sum_simd.reduce_add();
-
-        // 处理剩余元素
-        for i in simd_len..len {
-            dot_product += vec_a[i] * vec_b[i];
-            norm_a_sq += vec_a[i] * vec_a[i];
-            norm_b_sq += vec_b[i] * vec_b[i];
-        }
+        let (dot_product, norm_a_sq, norm_b_sq) = dot_product_and_norms_simd(vec_a, vec_b);
 
         // 优化的数值稳定性处理
-        let norm_a = norm_a_sq.sqrt();
-        let norm_b = norm_b_sq.sqrt();
+        cosine_from_parts(dot_product, norm_a_sq.sqrt(), norm_b_sq.sqrt())
+    }
 
-        if norm_a == 0.0 || norm_b == 0.0 {
+    // 任意度量下的单对向量分数
+    #[wasm_bindgen]
+    pub fn similarity(&self, vec_a: &[f32], vec_b: &[f32], metric: Metric) -> f32 {
+        if vec_a.len() != vec_b.len() || vec_a.is_empty() {
             return 0.0;
         }
-
-        let magnitude = norm_a * norm_b;
-        // 限制结果在 [-1.0, 1.0] 范围内，处理浮点精度误差
-        (dot_product / magnitude).max(-1.0).min(1.0)
+        metric.score(vec_a, vec_b)
     }
 
     #[wasm_bindgen]
     pub fn batch_similarity(&self, vectors: &[f32], query: &[f32], vector_dim: usize) -> Vec<f32> {
-        if vector_dim == 0 { return Vec::new(); }
-        if vectors.len() % vector_dim != 0 { return Vec::new(); }
-        if query.len() != vector_dim { return Vec::new(); }
+        batch_scores(Metric::Cosine, vectors, query, vector_dim)
+    }
 
-        let num_vectors = vectors.len() / vector_dim;
-        let mut results = Vec::with_capacity(num_vectors);
+    // 指定度量的批量计算，L2Squared / L1 返回距离
+    #[wasm_bindgen]
+    pub fn batch_similarity_with_metric(
+        &self,
+        vectors: &[f32],
+        query: &[f32],
+        vector_dim: usize,
+        metric: Metric,
+    ) -> Vec<f32> {
+        batch_scores(metric, vectors, query, vector_dim)
+    }
+
+    // 融合的 Top-K 搜索：在 WASM 内用有界堆筛选，只返回前 k 个索引和分数
+    #[wasm_bindgen]
+    pub fn batch_top_k(&self, vectors: &[f32], query: &[f32], vector_dim: usize, k: usize) -> TopKResults {
+        batch_top_k(Metric::Cosine, vectors, query, vector_dim, k)
+    }
+
+    #[wasm_bindgen]
+    pub fn batch_top_k_with_metric(
+        &self,
+        vectors: &[f32],
+        query: &[f32],
+        vector_dim: usize,
+        k: usize,
+        metric: Metric,
+    ) -> TopKResults {
+        batch_top_k(metric, vectors, query, vector_dim, k)
+    }
+
+    // L2 归一化单个向量
+    #[wasm_bindgen]
+    pub fn normalize(&self, vec: &[f32]) -> Vec<f32> {
+        let mut result = vec.to_vec();
+        normalize_in_place(&mut result);
+        result
+    }
 
-        // 预计算查询向量的范数
-        let query_norm_sq = self.compute_norm_squared_simd(query);
-        if query_norm_sq == 0.0 {
-            return vec![0.0; num_vectors];
+    // 批量 L2 归一化打包向量
+    #[wasm_bindgen]
+    pub fn normalize_batch(&self, vectors: &[f32], vector_dim: usize) -> Vec<f32> {
+        if packed_count(vectors, vector_dim).is_none() {
+            return Vec::new();
         }
-        let query_norm = query_norm_sq.sqrt();
-
-        for i in 0..num_vectors {
-            let start = i * vector_dim;
-            let vector_slice = &vectors[start..start + vector_dim];
-
-            // dot_product_and_norm_simd 计算 vector_slice (vec_a) 的范数
-            let (dot_product, vector_norm_sq) = self.dot_product_and_norm_simd(vector_slice, query);
-
-            if vector_norm_sq == 0.0 {
-                results.push(0.0);
-            } else {
-                let vector_norm = vector_norm_sq.sqrt();
-                let similarity = dot_product / (vector_norm * query_norm);
-                results.push(similarity.max(-1.0).min(1.0));
-            }
+        let mut result = vectors.to_vec();
+        for vector in result.chunks_exact_mut(vector_dim) {
+            normalize_in_place(vector);
         }
-        results
+        result
     }
 
-    // 辅助函数：SIMD 计算范数平方
-    #[inline]
-    fn compute_norm_squared_simd(&self, vec: &[f32]) -> f32 {
-        let len = vec.len();
-        let simd_lanes = 4;
-        let simd_len = len - (len % simd_lanes);
-        let mut norm_sum_simd = f32x4::ZERO;
-
-        for i in (0..simd_len).step_by(simd_lanes) {
-            let array: [f32; 4] = vec[i..i + simd_lanes].try_into().unwrap();
-            let chunk = f32x4::new(array);
-            norm_sum_simd = chunk.mul_add(chunk, norm_sum_simd);
+    // 单位向量快速路径：输入已归一化时余弦相似度即点积，跳过范数计算
+    #[wasm_bindgen]
+    pub fn dot_similarity_unit(&self, vec_a: &[f32], vec_b: &[f32]) -> f32 {
+        if vec_a.len() != vec_b.len() || vec_a.is_empty() {
+            return 0.0;
         }
+        debug_check_unit_vectors("dot_similarity_unit", vec_a, vec_a.len());
+        debug_check_unit_vectors("dot_similarity_unit", vec_b, vec_b.len());
+        dot_product_simd_only(vec_a, vec_b).clamp(-1.0, 1.0)
+    }
 
-        let mut norm_sq = norm_sum_simd.reduce_add();
-        for i in simd_len..len {
-            norm_sq += vec[i] * vec[i];
+    #[wasm_bindgen]
+    pub fn batch_similarity_unit(&self, vectors: &[f32], query: &[f32], vector_dim: usize) -> Vec<f32> {
+        debug_check_unit_vectors("batch_similarity_unit", vectors, vector_dim);
+        debug_check_unit_vectors("batch_similarity_unit", query, vector_dim);
+        let mut results = batch_scores(Metric::Dot, vectors, query, vector_dim);
+        for score in &mut results {
+            *score = score.clamp(-1.0, 1.0);
         }
-        norm_sq
+        results
     }
 
-    // 辅助函数：同时计算点积和vec_a的范数平方
-    #[inline]
-    fn dot_product_and_norm_simd(&self, vec_a: &[f32], vec_b: &[f32]) -> (f32, f32) {
-        let len = vec_a.len(); // 假设 vec_a.len() == vec_b.len()
-        let simd_lanes = 4;
-        let simd_len = len - (len % simd_lanes);
+    #[wasm_bindgen]
+    pub fn batch_top_k_unit(&self, vectors: &[f32], query: &[f32], vector_dim: usize, k: usize) -> TopKResults {
+        debug_check_unit_vectors("batch_top_k_unit", vectors, vector_dim);
+        debug_check_unit_vectors("batch_top_k_unit", query, vector_dim);
+        batch_top_k(Metric::Dot, vectors, query, vector_dim, k)
+    }
 
-        let mut dot_sum_simd = f32x4::ZERO;
-        let mut norm_a_sum_simd = f32x4::ZERO;
+    // 返回不是单位长度的向量索引，tolerance <= 0 时使用默认容差
+    #[wasm_bindgen]
+    pub fn find_non_unit_vectors(&self, vectors: &[f32], vector_dim: usize, tolerance: f32) -> Vec<u32> {
+        let tolerance = if tolerance > 0.0 { tolerance } else { UNIT_NORM_TOLERANCE };
+        non_unit_indices(vectors, vector_dim, tolerance)
+    }
 
-        for i in (0..simd_len).step_by(simd_lanes) {
-            let a_array: [f32; 4] = vec_a[i..i + simd_lanes].try_into().unwrap();
-            let b_array: [f32; 4] = vec_b[i..i + simd_lanes].try_into().unwrap();
-            let a_chunk = f32x4::new(a_array);
-            let b_chunk = f32x4::new(b_array);
+    // 对 transformer 的 last_hidden_state 按 attention_mask 做 mean pooling 并 L2 归一化，
+    // 返回打包的 [batch, hidden] 向量；形状不符时返回空数组
+    #[wasm_bindgen]
+    pub fn pool_embeddings(
+        &self,
+        hidden_state: &[f32],
+        attention_mask: &[u32],
+        batch: usize,
+        seq_len: usize,
+        hidden: usize,
+    ) -> Vec<f32> {
+        pooling::pool_embeddings(hidden_state, attention_mask, batch, seq_len, hidden)
+    }
 
-            dot_sum_simd = a_chunk.mul_add(b_chunk, dot_sum_simd);
-            norm_a_sum_simd = a_chunk.mul_add(a_chunk, norm_a_sum_simd);
-        }
+    // 指定池化策略，normalize 为 true 时对结果做 L2 归一化
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn pool_embeddings_with_mode(
+        &self,
+        hidden_state: &[f32],
+        attention_mask: &[u32],
+        batch: usize,
+        seq_len: usize,
+        hidden: usize,
+        mode: PoolingMode,
+        normalize: bool,
+    ) -> Vec<f32> {
+        pooling::pool_embeddings_with_mode(hidden_state, attention_mask, batch, seq_len, hidden, mode, normalize)
+    }
 
-        let mut dot_product = dot_sum_simd.reduce_add();
-        let mut norm_a_sq = norm_a_sum_simd.reduce_add();
+    // 符号位二值化：每个向量打包为 ceil(dim / 64) 个 u64
+    #[wasm_bindgen]
+    pub fn pack_sign_bits(&self, vectors: &[f32], vector_dim: usize) -> Vec<u64> {
+        binary::pack_sign_bits(vectors, vector_dim)
+    }
 
-        for i in simd_len..len {
-            dot_product += vec_a[i] * vec_b[i];
-            norm_a_sq += vec_a[i] * vec_a[i];
-        }
-        (dot_product, norm_a_sq)
+    // 半精度批量转换，用于导入导出
+    #[wasm_bindgen]
+    pub fn f32_to_half(&self, values: &[f32], format: HalfFormat) -> Vec<u16> {
+        half::encode_half(format, values)
+    }
+
+    #[wasm_bindgen]
+    pub fn half_to_f32(&self, bits: &[u16], format: HalfFormat) -> Vec<f32> {
+        half::decode_half(format, bits)
+    }
+
+    // MMR 多样性重排：candidates 为打包的候选向量，query_scores 为各候选与查询的相关性分数，
+    // lambda 越小结果越分散；返回按选中顺序排列的候选序号
+    #[wasm_bindgen]
+    pub fn mmr_rerank(&self, candidates: &[f32], query_scores: &[f32], vector_dim: usize, lambda: f32, k: usize) -> TopKResults {
+        mmr::mmr_rerank(candidates, query_scores, vector_dim, lambda, k)
+    }
+
+    // 混合检索：用倒数排名融合合并词法（BM25）和语义结果，ids 均按从好到差排序，
+    // rrf_k <= 0 时使用默认值 60
+    #[wasm_bindgen]
+    pub fn reciprocal_rank_fusion(&self, lexical_ids: &[u32], semantic_ids: &[u32], rrf_k: f32, k: usize) -> TopKResults {
+        fusion::reciprocal_rank_fusion(lexical_ids, semantic_ids, rrf_k, k)
+    }
+
+    // 混合检索：两组分数 min-max 归一化后按 semantic_weight 加权求和
+    #[wasm_bindgen]
+    pub fn weighted_score_fusion(
+        &self,
+        lexical_ids: &[u32],
+        lexical_scores: &[f32],
+        semantic_ids: &[u32],
+        semantic_scores: &[f32],
+        semantic_weight: f32,
+        k: usize,
+    ) -> TopKResults {
+        fusion::weighted_score_fusion(lexical_ids, lexical_scores, semantic_ids, semantic_scores, semantic_weight, k)
     }
 
     // 批量矩阵相似度计算 - 优化版
     #[wasm_bindgen]
     pub fn similarity_matrix(&self, vectors_a: &[f32], vectors_b: &[f32], vector_dim: usize) -> Vec<f32> {
-        if vector_dim == 0 || vectors_a.len() % vector_dim != 0 || vectors_b.len() % vector_dim != 0 {
-            return Vec::new();
-        }
-
-        let num_a = vectors_a.len() / vector_dim;
-        let num_b = vectors_b.len() / vector_dim;
-        let mut results = Vec::with_capacity(num_a * num_b);
-
-        // 1. 预计算 vectors_a 的范数
-        let norms_a: Vec<f32> = (0..num_a)
-            .map(|i| {
-                let start = i * vector_dim;
-                let vec_a_slice = &vectors_a[start..start + vector_dim];
-                self.compute_norm_squared_simd(vec_a_slice).sqrt()
-            })
-            .collect();
-
-        // 2. 预计算 vectors_b 的范数
-        let norms_b: Vec<f32> = (0..num_b)
-            .map(|j| {
-                let start = j * vector_dim;
-                let vec_b_slice = &vectors_b[start..start + vector_dim];
-                self.compute_norm_squared_simd(vec_b_slice).sqrt()
-            })
-            .collect();
-
-        for i in 0..num_a {
-            let start_a = i * vector_dim;
-            let vec_a = &vectors_a[start_a..start_a + vector_dim];
-            let norm_a = norms_a[i];
-
-            if norm_a == 0.0 {
-                // 如果 norm_a 为 0，所有相似度都为 0
-                for _ in 0..num_b {
-                    results.push(0.0);
-                }
-                continue;
-            }
-
-            for j in 0..num_b {
-                let start_b = j * vector_dim;
-                let vec_b = &vectors_b[start_b..start_b + vector_dim];
-                let norm_b = norms_b[j];
-
-                if norm_b == 0.0 {
-                    results.push(0.0);
-                    continue;
-                }
-
-                // 使用专用的点积函数
-                let dot_product = self.dot_product_simd_only(vec_a, vec_b);
-                let magnitude = norm_a * norm_b;
-
-                // magnitude 不应该为零，因为已经检查了 norm_a/norm_b
-                let similarity = (dot_product / magnitude).max(-1.0).min(1.0);
-                results.push(similarity);
-            }
-        }
+        score_matrix(Metric::Cosine, vectors_a, vectors_b, vector_dim)
+    }
 
-        results
+    // 指定度量的矩阵计算
+    #[wasm_bindgen]
+    pub fn similarity_matrix_with_metric(
+        &self,
+        vectors_a: &[f32],
+        vectors_b: &[f32],
+        vector_dim: usize,
+        metric: Metric,
+    ) -> Vec<f32> {
+        score_matrix(metric, vectors_a, vectors_b, vector_dim)
     }
 }
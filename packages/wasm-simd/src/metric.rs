@@ -0,0 +1,251 @@
+use wasm_bindgen::prelude::*;
+
+use crate::kernels::{
+    compute_norm_squared_simd, dot_product_and_norm_simd, dot_product_and_norms_simd,
+    dot_product_simd_only, l1_simd, l2_squared_simd,
+};
+
+// 距离/相似度度量，与 hnswlib 的 space 对应：
+// Dot / Cosine 为相似度（越大越相似），L2Squared / L1 为距离（越小越相似）
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Dot = 0,
+    Cosine = 1,
+    L2Squared = 2,
+    L1 = 3,
+}
+
+impl Metric {
+    #[inline]
+    pub(crate) fn higher_is_better(self) -> bool {
+        matches!(self, Metric::Dot | Metric::Cosine)
+    }
+
+    // 计算单对向量的分数，调用方保证长度一致
+    #[inline]
+    pub(crate) fn score(self, vec_a: &[f32], vec_b: &[f32]) -> f32 {
+        match self {
+            Metric::Dot => dot_product_simd_only(vec_a, vec_b),
+            Metric::Cosine => {
+                let (dot_product, norm_a_sq, norm_b_sq) = dot_product_and_norms_simd(vec_a, vec_b);
+                cosine_from_parts(dot_product, norm_a_sq.sqrt(), norm_b_sq.sqrt())
+            }
+            Metric::L2Squared => l2_squared_simd(vec_a, vec_b),
+            Metric::L1 => l1_simd(vec_a, vec_b),
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn cosine_from_parts(dot_product: f32, norm_a: f32, norm_b: f32) -> f32 {
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    // 限制结果在 [-1.0, 1.0] 范围内，处理浮点精度误差
+    (dot_product / (norm_a * norm_b)).clamp(-1.0, 1.0)
+}
+
+// 检查打包向量的维度是否合法，返回向量个数
+#[inline]
+pub(crate) fn packed_count(vectors: &[f32], vector_dim: usize) -> Option<usize> {
+    if vector_dim == 0 || !vectors.len().is_multiple_of(vector_dim) {
+        return None;
+    }
+    Some(vectors.len() / vector_dim)
+}
+
+// 逐个向量打分并回调，余弦相似度只预计算一次查询向量的范数
+pub(crate) fn for_each_score(
+    metric: Metric,
+    vectors: &[f32],
+    query: &[f32],
+    vector_dim: usize,
+    mut on_score: impl FnMut(usize, f32),
+) {
+    if packed_count(vectors, vector_dim).is_none() || query.len() != vector_dim {
+        return;
+    }
+    let chunks = vectors.chunks_exact(vector_dim);
+
+    match metric {
+        Metric::Cosine => {
+            // 预计算查询向量的范数
+            let query_norm = compute_norm_squared_simd(query).sqrt();
+            for (i, vector_slice) in chunks.enumerate() {
+                if query_norm == 0.0 {
+                    on_score(i, 0.0);
+                    continue;
+                }
+                // dot_product_and_norm_simd 计算 vector_slice (vec_a) 的范数
+                let (dot_product, vector_norm_sq) = dot_product_and_norm_simd(vector_slice, query);
+                on_score(
+                    i,
+                    cosine_from_parts(dot_product, vector_norm_sq.sqrt(), query_norm),
+                );
+            }
+        }
+        _ => {
+            for (i, vector_slice) in chunks.enumerate() {
+                on_score(i, metric.score(vector_slice, query));
+            }
+        }
+    }
+}
+
+// 查询向量对一组打包向量的批量打分
+pub(crate) fn batch_scores(
+    metric: Metric,
+    vectors: &[f32],
+    query: &[f32],
+    vector_dim: usize,
+) -> Vec<f32> {
+    let mut results = Vec::with_capacity(vectors.len() / vector_dim.max(1));
+    for_each_score(metric, vectors, query, vector_dim, |_, score| {
+        results.push(score)
+    });
+    results
+}
+
+// 两组打包向量之间的分数矩阵（行优先，num_a x num_b）
+pub(crate) fn score_matrix(
+    metric: Metric,
+    vectors_a: &[f32],
+    vectors_b: &[f32],
+    vector_dim: usize,
+) -> Vec<f32> {
+    let (num_a, num_b) = match (
+        packed_count(vectors_a, vector_dim),
+        packed_count(vectors_b, vector_dim),
+    ) {
+        (Some(a), Some(b)) => (a, b),
+        _ => return Vec::new(),
+    };
+    let mut results = Vec::with_capacity(num_a * num_b);
+
+    if metric != Metric::Cosine {
+        for vec_a in vectors_a.chunks_exact(vector_dim) {
+            results.extend(
+                vectors_b
+                    .chunks_exact(vector_dim)
+                    .map(|vec_b| metric.score(vec_a, vec_b)),
+            );
+        }
+        return results;
+    }
+
+    // 余弦相似度：预计算两组向量的范数，内层循环只做点积
+    let norms_a: Vec<f32> = vectors_a
+        .chunks_exact(vector_dim)
+        .map(|v| compute_norm_squared_simd(v).sqrt())
+        .collect();
+    let norms_b: Vec<f32> = vectors_b
+        .chunks_exact(vector_dim)
+        .map(|v| compute_norm_squared_simd(v).sqrt())
+        .collect();
+
+    for (vec_a, &norm_a) in vectors_a.chunks_exact(vector_dim).zip(&norms_a) {
+        if norm_a == 0.0 {
+            // 如果 norm_a 为 0，所有相似度都为 0
+            results.resize(results.len() + num_b, 0.0);
+            continue;
+        }
+
+        for (vec_b, &norm_b) in vectors_b.chunks_exact(vector_dim).zip(&norms_b) {
+            if norm_b == 0.0 {
+                results.push(0.0);
+                continue;
+            }
+            let dot_product = dot_product_simd_only(vec_a, vec_b);
+            results.push(cosine_from_parts(dot_product, norm_a, norm_b));
+        }
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    const METRICS: [Metric; 4] = [Metric::Dot, Metric::Cosine, Metric::L2Squared, Metric::L1];
+
+    // f64 标量参照实现
+    fn reference(metric: Metric, a: &[f32], b: &[f32]) -> f64 {
+        let pairs = || a.iter().zip(b).map(|(&x, &y)| (x as f64, y as f64));
+        match metric {
+            Metric::Dot => pairs().map(|(x, y)| x * y).sum(),
+            Metric::Cosine => {
+                let dot: f64 = pairs().map(|(x, y)| x * y).sum();
+                let norm_a: f64 = pairs().map(|(x, _)| x * x).sum::<f64>().sqrt();
+                let norm_b: f64 = pairs().map(|(_, y)| y * y).sum::<f64>().sqrt();
+                if norm_a == 0.0 || norm_b == 0.0 {
+                    0.0
+                } else {
+                    dot / (norm_a * norm_b)
+                }
+            }
+            Metric::L2Squared => pairs().map(|(x, y)| (x - y) * (x - y)).sum(),
+            Metric::L1 => pairs().map(|(x, y)| (x - y).abs()).sum(),
+        }
+    }
+
+    fn assert_close(actual: f32, expected: f64) {
+        assert!(
+            (actual as f64 - expected).abs() <= 1e-5 * expected.abs().max(1.0),
+            "{} vs {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn scores_match_scalar_reference() {
+        for dim in [1, 2, 3, 5, 6, 7, 9, 15, 33, 127] {
+            let vectors = random_vectors(dim as u64, 6, dim);
+            let query = random_vectors(500 + dim as u64, 1, dim);
+            for metric in METRICS {
+                let batch = batch_scores(metric, &vectors, &query, dim);
+                assert_eq!(batch.len(), 6);
+                for (vector, &score) in vectors.chunks_exact(dim).zip(&batch) {
+                    let expected = reference(metric, vector, &query);
+                    assert_close(metric.score(vector, &query), expected);
+                    assert_close(score, expected);
+                }
+
+                let matrix = score_matrix(metric, &vectors, &query, dim);
+                assert_eq!(matrix.len(), 6);
+                for (&score, &batched) in matrix.iter().zip(&batch) {
+                    assert_close(score, batched as f64);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cosine_with_zero_vectors_is_zero() {
+        let zero = [0.0f32; 5];
+        let other = [1.0, -2.0, 3.0, 0.5, 0.25];
+        assert_eq!(Metric::Cosine.score(&zero, &other), 0.0);
+        assert_eq!(Metric::Cosine.score(&other, &zero), 0.0);
+
+        let vectors: Vec<f32> = zero.iter().chain(&other).copied().collect();
+        assert_eq!(batch_scores(Metric::Cosine, &vectors, &zero, 5), [0.0, 0.0]);
+        let scores = batch_scores(Metric::Cosine, &vectors, &other, 5);
+        assert_eq!(scores[0], 0.0);
+        assert_close(scores[1], 1.0);
+        let matrix = score_matrix(Metric::Cosine, &vectors, &vectors, 5);
+        assert_eq!(&matrix[..3], &[0.0, 0.0, 0.0]);
+        assert_close(matrix[3], 1.0);
+    }
+
+    #[test]
+    fn misshaped_input_is_rejected() {
+        assert_eq!(packed_count(&[0.0; 6], 3), Some(2));
+        assert_eq!(packed_count(&[0.0; 6], 4), None);
+        assert_eq!(packed_count(&[0.0; 6], 0), None);
+        assert!(batch_scores(Metric::Dot, &[0.0; 6], &[0.0; 2], 3).is_empty());
+        assert!(score_matrix(Metric::L1, &[0.0; 6], &[0.0; 4], 3).is_empty());
+    }
+}
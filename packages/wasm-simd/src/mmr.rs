@@ -0,0 +1,113 @@
+use crate::kernels::{compute_norm_squared_simd, dot_product_simd_only};
+use crate::metric::{cosine_from_parts, packed_count};
+use crate::topk::TopKResults;
+
+// 最大边际相关性（MMR）重排：每轮选出 lambda * 相关性 - (1 - lambda) * 与已选结果的最大余弦相似度
+// 最高的候选。第一轮没有已选结果，直接取相关性最高的候选；
+// lambda = 1 退化为按 query_scores 排序，lambda = 0 之后的每轮只看多样性。
+// 返回的 indices 为候选序号（按选中顺序），scores 为对应的 query_scores
+pub(crate) fn mmr_rerank(
+    candidates: &[f32],
+    query_scores: &[f32],
+    dim: usize,
+    lambda: f32,
+    k: usize,
+) -> TopKResults {
+    let count = match packed_count(candidates, dim) {
+        Some(n) if n == query_scores.len() => n,
+        _ => return TopKResults::default(),
+    };
+    let lambda = lambda.clamp(0.0, 1.0);
+    let norms: Vec<f32> = candidates
+        .chunks_exact(dim)
+        .map(|v| compute_norm_squared_simd(v).sqrt())
+        .collect();
+    let vector = |i: usize| &candidates[i * dim..(i + 1) * dim];
+
+    // NaN 分数的候选不参与选择
+    let mut remaining: Vec<bool> = query_scores.iter().map(|s| !s.is_nan()).collect();
+    let mut max_similarity = vec![0.0f32; count];
+    let mut indices = Vec::with_capacity(k.min(count));
+    let mut scores = Vec::with_capacity(k.min(count));
+
+    for round in 0..k.min(count) {
+        let mut best: Option<(usize, f32)> = None;
+        for i in (0..count).filter(|&i| remaining[i]) {
+            let score = if round == 0 {
+                query_scores[i]
+            } else {
+                lambda * query_scores[i] - (1.0 - lambda) * max_similarity[i]
+            };
+            // 分数相同时保留序号较小的候选
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((i, score));
+            }
+        }
+        let selected = match best {
+            Some((i, _)) => i,
+            None => break,
+        };
+        remaining[selected] = false;
+        indices.push(selected as u32);
+        scores.push(query_scores[selected]);
+
+        for i in (0..count).filter(|&i| remaining[i]) {
+            let dot_product = dot_product_simd_only(vector(i), vector(selected));
+            let similarity = cosine_from_parts(dot_product, norms[i], norms[selected]);
+            if round == 0 || similarity > max_similarity[i] {
+                max_similarity[i] = similarity;
+            }
+        }
+    }
+    TopKResults::from_parts(indices, scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0、1 几乎重合，2 与它们正交，3 与 0 反向
+    const CANDIDATES: [f32; 8] = [1.0, 0.0, 0.99, 0.1, 0.0, 1.0, -1.0, 0.0];
+
+    #[test]
+    fn lambda_one_orders_by_relevance() {
+        let scores = [0.2, 0.9, 0.5, 0.7];
+        let results = mmr_rerank(&CANDIDATES, &scores, 2, 1.0, 4);
+        assert_eq!(results.indices(), [1, 3, 2, 0]);
+        assert_eq!(results.scores(), [0.9, 0.7, 0.5, 0.2]);
+    }
+
+    #[test]
+    fn lambda_zero_starts_from_the_most_relevant() {
+        // 第一个选相关性最高的 1，而不是序号 0；之后选与已选结果最不相似的
+        let scores = [0.8, 0.9, 0.1, 0.2];
+        let results = mmr_rerank(&CANDIDATES, &scores, 2, 0.0, 4);
+        assert_eq!(results.indices(), [1, 3, 2, 0]);
+
+        let scores = [0.1, 0.2, 0.3, 0.9];
+        assert_eq!(mmr_rerank(&CANDIDATES, &scores, 2, 0.0, 1).indices(), [3]);
+    }
+
+    #[test]
+    fn balanced_lambda_skips_near_duplicates() {
+        let scores = [0.9, 0.85, 0.6, -1.0];
+        assert_eq!(
+            mmr_rerank(&CANDIDATES, &scores, 2, 0.5, 2).indices(),
+            [0, 2]
+        );
+    }
+
+    #[test]
+    fn k_larger_than_candidates_returns_each_once() {
+        let scores = [0.2, f32::NAN, 0.5, 0.7];
+        let results = mmr_rerank(&CANDIDATES, &scores, 2, 0.7, 10);
+        // NaN 分数的候选不会被选中
+        let mut indices = results.indices();
+        assert_eq!(indices.len(), 3);
+        indices.sort_unstable();
+        assert_eq!(indices, [0, 2, 3]);
+
+        assert_eq!(mmr_rerank(&CANDIDATES, &scores, 2, 0.7, 0).length(), 0);
+        assert_eq!(mmr_rerank(&CANDIDATES, &scores[..3], 2, 0.7, 3).length(), 0);
+    }
+}
@@ -0,0 +1,268 @@
+use wasm_bindgen::prelude::*;
+
+use crate::kernels::{add_assign_simd, add_scaled_simd, max_assign_simd, scale_in_place_simd};
+use crate::unit::normalize_in_place;
+
+// 句向量池化策略，对应不同模型的 pooling 配置
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolingMode {
+    // 按 attention_mask 求平均（E5 / MiniLM）
+    Mean = 0,
+    // 取第一个 token（BERT [CLS] / BGE）
+    Cls = 1,
+    // 有效 token 逐维取最大值
+    Max = 2,
+    // 按位置加权平均，第 i 个 token 权重为 i + 1（SGPT 风格）
+    WeightedMean = 3,
+    // 取最后一个有效 token（decoder 类模型）
+    LastToken = 4,
+}
+
+// 校验 last_hidden_state [batch, seq_len, hidden] 与 attention_mask [batch, seq_len] 的形状
+fn shape_is_valid(
+    hidden_state: &[f32],
+    attention_mask: &[u32],
+    batch: usize,
+    seq_len: usize,
+    hidden: usize,
+) -> bool {
+    hidden != 0
+        && seq_len != 0
+        && attention_mask.len() == batch * seq_len
+        && hidden_state.len() == batch * seq_len * hidden
+}
+
+// 按 attention_mask 对 token 向量求平均
+fn mean_pool(tokens: &[f32], mask: &[u32], hidden: usize, out: &mut [f32]) {
+    let mut valid_tokens = 0usize;
+    for (token, &m) in tokens.chunks_exact(hidden).zip(mask) {
+        if m != 0 {
+            add_assign_simd(out, token);
+            valid_tokens += 1;
+        }
+    }
+    if valid_tokens > 0 {
+        scale_in_place_simd(out, 1.0 / valid_tokens as f32);
+    }
+}
+
+fn max_pool(tokens: &[f32], mask: &[u32], hidden: usize, out: &mut [f32]) {
+    let mut valid = tokens
+        .chunks_exact(hidden)
+        .zip(mask)
+        .filter(|(_, &m)| m != 0)
+        .map(|(token, _)| token);
+
+    // 没有有效 token 时保持全零
+    if let Some(first) = valid.next() {
+        out.copy_from_slice(first);
+        for token in valid {
+            max_assign_simd(out, token);
+        }
+    }
+}
+
+fn weighted_mean_pool(tokens: &[f32], mask: &[u32], hidden: usize, out: &mut [f32]) {
+    let mut weight_sum = 0.0f32;
+    for (position, (token, &m)) in tokens.chunks_exact(hidden).zip(mask).enumerate() {
+        if m != 0 {
+            let weight = (position + 1) as f32;
+            add_scaled_simd(out, token, weight);
+            weight_sum += weight;
+        }
+    }
+    if weight_sum > 0.0 {
+        scale_in_place_simd(out, 1.0 / weight_sum);
+    }
+}
+
+fn last_token_pool(tokens: &[f32], mask: &[u32], hidden: usize, out: &mut [f32]) {
+    // 同时兼容左填充和右填充：取最后一个 mask 非零的位置
+    if let Some(position) = mask.iter().rposition(|&m| m != 0) {
+        out.copy_from_slice(&tokens[position * hidden..(position + 1) * hidden]);
+    }
+}
+
+// 对整个 batch 做池化，可选 L2 归一化，返回打包的 [batch, hidden] 向量
+pub(crate) fn pool_embeddings_with_mode(
+    hidden_state: &[f32],
+    attention_mask: &[u32],
+    batch: usize,
+    seq_len: usize,
+    hidden: usize,
+    mode: PoolingMode,
+    normalize: bool,
+) -> Vec<f32> {
+    if !shape_is_valid(hidden_state, attention_mask, batch, seq_len, hidden) {
+        return Vec::new();
+    }
+
+    let mut pooled = vec![0.0f32; batch * hidden];
+    for ((tokens, mask), out) in hidden_state
+        .chunks_exact(seq_len * hidden)
+        .zip(attention_mask.chunks_exact(seq_len))
+        .zip(pooled.chunks_exact_mut(hidden))
+    {
+        match mode {
+            PoolingMode::Mean => mean_pool(tokens, mask, hidden, out),
+            PoolingMode::Cls => out.copy_from_slice(&tokens[..hidden]),
+            PoolingMode::Max => max_pool(tokens, mask, hidden, out),
+            PoolingMode::WeightedMean => weighted_mean_pool(tokens, mask, hidden, out),
+            PoolingMode::LastToken => last_token_pool(tokens, mask, hidden, out),
+        }
+        if normalize {
+            normalize_in_place(out);
+        }
+    }
+    pooled
+}
+
+// 对整个 batch 做 mean pooling + L2 归一化，返回打包的 [batch, hidden] 向量
+pub(crate) fn pool_embeddings(
+    hidden_state: &[f32],
+    attention_mask: &[u32],
+    batch: usize,
+    seq_len: usize,
+    hidden: usize,
+) -> Vec<f32> {
+    pool_embeddings_with_mode(
+        hidden_state,
+        attention_mask,
+        batch,
+        seq_len,
+        hidden,
+        PoolingMode::Mean,
+        true,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // batch = 2, seq_len = 3, hidden = 5；第一条序列有 2 个有效 token，第二条左填充 1 个
+    const BATCH: usize = 2;
+    const SEQ_LEN: usize = 3;
+    const HIDDEN: usize = 5;
+    const MASK: [u32; 6] = [1, 1, 0, 0, 1, 1];
+
+    fn hidden_state() -> Vec<f32> {
+        vec![
+            1.0, -2.0, 3.0, 0.5, 4.0, //
+            3.0, 2.0, -1.0, 1.5, -4.0, //
+            9.0, 9.0, 9.0, 9.0, 9.0, //
+            7.0, 7.0, 7.0, 7.0, 7.0, //
+            2.0, 0.0, -6.0, 1.0, 3.0, //
+            -1.0, 3.0, 6.0, 4.0, 0.0, //
+        ]
+    }
+
+    fn pool(mode: PoolingMode, normalize: bool) -> Vec<f32> {
+        pool_embeddings_with_mode(
+            &hidden_state(),
+            &MASK,
+            BATCH,
+            SEQ_LEN,
+            HIDDEN,
+            mode,
+            normalize,
+        )
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+            assert!((a - e).abs() < 1e-5, "index {}: {} != {}", i, a, e);
+        }
+    }
+
+    #[test]
+    fn mean_pooling_ignores_masked_tokens() {
+        assert_close(
+            &pool(PoolingMode::Mean, false),
+            &[
+                2.0, 0.0, 1.0, 1.0, 0.0, //
+                0.5, 1.5, 0.0, 2.5, 1.5, //
+            ],
+        );
+    }
+
+    #[test]
+    fn cls_pooling_takes_first_position() {
+        assert_close(
+            &pool(PoolingMode::Cls, false),
+            &[
+                1.0, -2.0, 3.0, 0.5, 4.0, //
+                7.0, 7.0, 7.0, 7.0, 7.0, //
+            ],
+        );
+    }
+
+    #[test]
+    fn max_pooling_takes_elementwise_max_of_valid_tokens() {
+        assert_close(
+            &pool(PoolingMode::Max, false),
+            &[
+                3.0, 2.0, 3.0, 1.5, 4.0, //
+                2.0, 3.0, 6.0, 4.0, 3.0, //
+            ],
+        );
+    }
+
+    #[test]
+    fn weighted_mean_pooling_weights_by_position() {
+        // 第一条：权重 1、2；第二条：权重 2、3
+        assert_close(
+            &pool(PoolingMode::WeightedMean, false),
+            &[
+                7.0 / 3.0, 2.0 / 3.0, 1.0 / 3.0, 3.5 / 3.0, -4.0 / 3.0, //
+                1.0 / 5.0, 9.0 / 5.0, 6.0 / 5.0, 14.0 / 5.0, 6.0 / 5.0, //
+            ],
+        );
+    }
+
+    #[test]
+    fn last_token_pooling_takes_last_valid_position() {
+        assert_close(
+            &pool(PoolingMode::LastToken, false),
+            &[
+                3.0, 2.0, -1.0, 1.5, -4.0, //
+                -1.0, 3.0, 6.0, 4.0, 0.0, //
+            ],
+        );
+    }
+
+    #[test]
+    fn normalized_output_matches_reference() {
+        let norm = (4.0f32 + 1.0 + 1.0).sqrt();
+        let pooled = pool(PoolingMode::Mean, true);
+        assert_close(
+            &pooled[..HIDDEN],
+            &[2.0 / norm, 0.0, 1.0 / norm, 1.0 / norm, 0.0],
+        );
+        assert_eq!(
+            pool_embeddings(&hidden_state(), &MASK, BATCH, SEQ_LEN, HIDDEN),
+            pooled
+        );
+    }
+
+    #[test]
+    fn fully_masked_sequence_pools_to_zero() {
+        let pooled = pool_embeddings_with_mode(
+            &hidden_state()[..SEQ_LEN * HIDDEN],
+            &[0, 0, 0],
+            1,
+            SEQ_LEN,
+            HIDDEN,
+            PoolingMode::Max,
+            true,
+        );
+        assert_eq!(pooled, vec![0.0; HIDDEN]);
+    }
+
+    #[test]
+    fn mismatched_shape_returns_empty() {
+        assert!(pool_embeddings(&hidden_state(), &MASK, BATCH, SEQ_LEN, HIDDEN + 1).is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+use wasm_bindgen::prelude::*;
+
+use crate::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::kernels::{compute_norm_squared_simd, dot_product_simd_only, l1_simd, l2_squared_simd};
+use crate::kmeans::{nearest_centroid, KMeans};
+use crate::metric::{cosine_from_parts, packed_count, Metric};
+use crate::topk::{TopKHeap, TopKResults};
+
+const PQ_MAGIC: &[u8; 4] = b"SMPQ";
+const PQ_VERSION: u16 = 1;
+const MAX_CENTROIDS: usize = 256;
+
+// 乘积量化：把向量切成 m 个子空间，每个子空间用 k-means 码本编码为 1 字节，
+// 查询时预计算查询子向量到各码字的查找表（ADC），按码求和即可得到分数
+#[wasm_bindgen]
+pub struct ProductQuantizer {
+    dim: usize,
+    subspaces: usize,
+    centroids: usize,
+    sub_dim: usize,
+    // [subspaces][centroids][sub_dim]
+    codebooks: Vec<f32>,
+    // 每个码字的范数平方，用于余弦 ADC
+    codeword_norms_sq: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl ProductQuantizer {
+    // dim 必须能被 subspaces 整除，centroids 取值 1..=256
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        dim: usize,
+        subspaces: usize,
+        centroids: usize,
+    ) -> Result<ProductQuantizer, JsValue> {
+        Self::validate(dim, subspaces, centroids).map_err(JsValue::from)?;
+        Ok(ProductQuantizer {
+            dim,
+            subspaces,
+            centroids,
+            sub_dim: dim / subspaces,
+            codebooks: Vec::new(),
+            codeword_norms_sq: Vec::new(),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn subspaces(&self) -> usize {
+        self.subspaces
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn centroids(&self) -> usize {
+        self.centroids
+    }
+
+    // 每个向量编码后的字节数
+    #[wasm_bindgen(getter)]
+    pub fn code_size(&self) -> usize {
+        self.subspaces
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_trained(&self) -> bool {
+        !self.codebooks.is_empty()
+    }
+
+    // 用样本向量训练各子空间码本，样本数少于 centroids 时返回 false
+    pub fn train(&mut self, sample: &[f32], iterations: usize, seed: u64) -> bool {
+        let count = match packed_count(sample, self.dim) {
+            Some(n) if n >= self.centroids => n,
+            _ => return false,
+        };
+
+        let mut codebooks = Vec::with_capacity(self.subspaces * self.centroids * self.sub_dim);
+        let mut sub_vectors = Vec::with_capacity(count * self.sub_dim);
+        for m in 0..self.subspaces {
+            sub_vectors.clear();
+            for vector in sample.chunks_exact(self.dim) {
+                sub_vectors.extend_from_slice(&vector[m * self.sub_dim..(m + 1) * self.sub_dim]);
+            }
+            // 每个子空间使用不同的派生种子
+            let sub_seed = seed.wrapping_add((m as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let mut kmeans = KMeans::new(self.centroids);
+            kmeans.set_max_iterations(iterations);
+            kmeans.set_seed(sub_seed);
+            match kmeans.fit(&sub_vectors, self.sub_dim) {
+                Some(result) => codebooks.extend_from_slice(&result.into_centroids()),
+                None => return false,
+            }
+        }
+        self.set_codebooks(codebooks);
+        true
+    }
+
+    // 编码打包向量，每个向量输出 subspaces 个字节
+    pub fn encode(&self, vectors: &[f32]) -> Vec<u8> {
+        let count = match packed_count(vectors, self.dim) {
+            Some(n) if self.is_trained() => n,
+            _ => return Vec::new(),
+        };
+        let mut codes = Vec::with_capacity(count * self.subspaces);
+        for vector in vectors.chunks_exact(self.dim) {
+            for m in 0..self.subspaces {
+                let sub_vector = &vector[m * self.sub_dim..(m + 1) * self.sub_dim];
+                let (c, _) = nearest_centroid(sub_vector, self.codebook(m), self.sub_dim);
+                codes.push(c as u8);
+            }
+        }
+        codes
+    }
+
+    // 由码重建近似向量，码字序号超出 centroids 时返回空
+    pub fn decode(&self, codes: &[u8]) -> Vec<f32> {
+        if !self.is_trained() || !self.valid_codes(codes) {
+            return Vec::new();
+        }
+        let mut vectors = Vec::with_capacity(codes.len() * self.sub_dim);
+        for code in codes.chunks_exact(self.subspaces) {
+            for (m, &c) in code.iter().enumerate() {
+                vectors.extend_from_slice(self.codeword(m, c as usize));
+            }
+        }
+        vectors
+    }
+
+    // 查询向量的 ADC 查找表 [subspaces][centroids]；余弦时为点积表
+    pub fn distance_table(&self, query: &[f32], metric: Metric) -> Vec<f32> {
+        if query.len() != self.dim || !self.is_trained() {
+            return Vec::new();
+        }
+        let mut table = Vec::with_capacity(self.subspaces * self.centroids);
+        for m in 0..self.subspaces {
+            let sub_query = &query[m * self.sub_dim..(m + 1) * self.sub_dim];
+            for codeword in self.codebook(m).chunks_exact(self.sub_dim) {
+                table.push(match metric {
+                    Metric::Dot | Metric::Cosine => dot_product_simd_only(sub_query, codeword),
+                    Metric::L2Squared => l2_squared_simd(sub_query, codeword),
+                    Metric::L1 => l1_simd(sub_query, codeword),
+                });
+            }
+        }
+        table
+    }
+
+    // 非对称距离搜索：codes 为 encode 的输出，返回结果的 indices 为向量序号；
+    // 码字序号超出 centroids（码已损坏或来自其他码本）时返回空结果
+    pub fn search(&self, codes: &[u8], query: &[f32], k: usize, metric: Metric) -> TopKResults {
+        let table = self.distance_table(query, metric);
+        if table.is_empty() || !self.valid_codes(codes) {
+            return TopKResults::default();
+        }
+        let query_norm = compute_norm_squared_simd(query).sqrt();
+
+        let mut heap = TopKHeap::for_metric(k, metric);
+        for (i, code) in codes.chunks_exact(self.subspaces).enumerate() {
+            let mut score = 0.0f32;
+            let mut norm_sq = 0.0f32;
+            for (m, &c) in code.iter().enumerate() {
+                let entry = m * self.centroids + c as usize;
+                score += table[entry];
+                if metric == Metric::Cosine {
+                    norm_sq += self.codeword_norms_sq[entry];
+                }
+            }
+            if metric == Metric::Cosine {
+                score = cosine_from_parts(score, norm_sq.sqrt(), query_norm);
+            }
+            heap.push(i as u32, score);
+        }
+        heap.into_results()
+    }
+
+    // 序列化码本，便于与索引一起存入 IndexedDB
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.bytes(PQ_MAGIC);
+        writer.u16(PQ_VERSION);
+        writer.u32(self.dim as u32);
+        writer.u32(self.subspaces as u32);
+        writer.u32(self.centroids as u32);
+        writer.u32(self.codebooks.len() as u32);
+        writer.f32_slice(&self.codebooks);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<ProductQuantizer, JsValue> {
+        Ok(Self::decode_bytes(bytes)?)
+    }
+}
+
+impl ProductQuantizer {
+    fn validate(dim: usize, subspaces: usize, centroids: usize) -> Result<(), DecodeError> {
+        if dim == 0 || subspaces == 0 || !dim.is_multiple_of(subspaces) {
+            return Err(DecodeError::Invalid(format!(
+                "dimension {} is not divisible into {} subspaces",
+                dim, subspaces
+            )));
+        }
+        if centroids == 0 || centroids > MAX_CENTROIDS {
+            return Err(DecodeError::Invalid(format!(
+                "centroids per subspace must be in 1..={}, got {}",
+                MAX_CENTROIDS, centroids
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn decode_bytes(bytes: &[u8]) -> Result<ProductQuantizer, DecodeError> {
+        let mut reader = ByteReader::new(bytes);
+        reader.expect_header(PQ_MAGIC, PQ_VERSION)?;
+        let dim = reader.u32()? as usize;
+        let subspaces = reader.u32()? as usize;
+        let centroids = reader.u32()? as usize;
+        Self::validate(dim, subspaces, centroids)?;
+
+        let expected_len = centroids.checked_mul(dim).ok_or_else(|| {
+            DecodeError::Invalid(format!("codebook size {} x {} overflows", centroids, dim))
+        })?;
+        let codebook_len = reader.u32()? as usize;
+        if codebook_len != 0 && codebook_len != expected_len {
+            return Err(DecodeError::Invalid(format!(
+                "codebook length {} does not match {} x {}",
+                codebook_len, centroids, dim
+            )));
+        }
+        let codebooks = reader.f32_vec(codebook_len)?;
+        reader.finish()?;
+
+        let mut pq = ProductQuantizer {
+            dim,
+            subspaces,
+            centroids,
+            sub_dim: dim / subspaces,
+            codebooks: Vec::new(),
+            codeword_norms_sq: Vec::new(),
+        };
+        if !codebooks.is_empty() {
+            pq.set_codebooks(codebooks);
+        }
+        Ok(pq)
+    }
+
+    // 长度为 subspaces 的整数倍，且每个字节都是合法的码字序号
+    fn valid_codes(&self, codes: &[u8]) -> bool {
+        codes.len().is_multiple_of(self.subspaces)
+            && codes.iter().all(|&c| (c as usize) < self.centroids)
+    }
+
+    fn set_codebooks(&mut self, codebooks: Vec<f32>) {
+        self.codeword_norms_sq = codebooks
+            .chunks_exact(self.sub_dim)
+            .map(compute_norm_squared_simd)
+            .collect();
+        self.codebooks = codebooks;
+    }
+
+    #[inline]
+    fn codebook(&self, m: usize) -> &[f32] {
+        let len = self.centroids * self.sub_dim;
+        &self.codebooks[m * len..(m + 1) * len]
+    }
+
+    #[inline]
+    fn codeword(&self, m: usize, c: usize) -> &[f32] {
+        let start = (m * self.centroids + c) * self.sub_dim;
+        &self.codebooks[start..start + self.sub_dim]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    const DIM: usize = 16;
+    const COUNT: usize = 200;
+
+    fn trained() -> (ProductQuantizer, Vec<f32>) {
+        let data = random_vectors(1, COUNT, DIM);
+        let mut pq = ProductQuantizer::new(DIM, 4, 16).unwrap();
+        assert!(pq.train(&data, 10, 7));
+        (pq, data)
+    }
+
+    #[test]
+    fn adc_scores_match_decoded_vectors() {
+        let (pq, data) = trained();
+        let codes = pq.encode(&data);
+        assert_eq!(codes.len(), COUNT * pq.code_size());
+        let decoded = pq.decode(&codes);
+        let query = random_vectors(2, 1, DIM);
+
+        for metric in [Metric::Dot, Metric::Cosine, Metric::L2Squared, Metric::L1] {
+            let results = pq.search(&codes, &query, COUNT, metric);
+            assert_eq!(results.length(), COUNT);
+            for (&i, &score) in results.indices().iter().zip(&results.scores()) {
+                let vector = &decoded[i as usize * DIM..(i as usize + 1) * DIM];
+                let expected = metric.score(vector, &query);
+                assert!(
+                    (score - expected).abs() <= 1e-4 * expected.abs().max(1.0),
+                    "{:?} vector {}: {} vs {}",
+                    metric,
+                    i,
+                    score,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let (pq, data) = trained();
+        let bytes = pq.to_bytes();
+        let restored = ProductQuantizer::decode_bytes(&bytes).unwrap();
+        assert_eq!(restored.to_bytes(), bytes);
+        assert_eq!(restored.encode(&data), pq.encode(&data));
+
+        let untrained = ProductQuantizer::new(DIM, 4, 16).unwrap();
+        let restored = ProductQuantizer::decode_bytes(&untrained.to_bytes()).unwrap();
+        assert!(!restored.is_trained());
+
+        assert!(ProductQuantizer::decode_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn out_of_range_codes_are_rejected() {
+        let (pq, data) = trained();
+        let query = random_vectors(2, 1, DIM);
+        let mut codes = pq.encode(&data[..2 * DIM]);
+        assert_eq!(pq.decode(&codes).len(), 2 * DIM);
+
+        // 最后一个子空间的码字越界时不能读到查找表之外
+        let last = codes.len() - 1;
+        codes[last] = 16;
+        assert!(pq.decode(&codes).is_empty());
+        assert_eq!(pq.search(&codes, &query, 2, Metric::Dot).length(), 0);
+        codes[last] = 255;
+        assert_eq!(pq.search(&codes, &query, 2, Metric::Cosine).length(), 0);
+    }
+}
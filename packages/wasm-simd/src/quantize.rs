@@ -0,0 +1,532 @@
+use wasm_bindgen::prelude::*;
+
+use crate::kernels::{compute_norm_squared_simd, dot_i8_simd, l1_simd};
+use crate::metric::{cosine_from_parts, packed_count, Metric};
+use crate::topk::{TopKHeap, TopKResults};
+
+const CODE_MAX: f32 = 127.0;
+
+// int8 量化的 scale/offset 粒度
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuantizationGranularity {
+    // 每个向量一组 scale/offset，无需校准
+    PerVector = 0,
+    // 每个维度一组 scale/offset，需要先用样本校准
+    PerDimension = 1,
+}
+
+// 由取值范围 [lo, hi] 得到仿射参数：x ≈ scale * q + offset，q ∈ [-127, 127]
+#[inline]
+fn affine_params(lo: f32, hi: f32) -> (f32, f32) {
+    let offset = (lo + hi) * 0.5;
+    let scale = (hi - lo) / (2.0 * CODE_MAX);
+    if scale > 0.0 && scale.is_finite() {
+        (scale, offset)
+    } else {
+        (1.0, offset)
+    }
+}
+
+#[inline]
+fn quantize_value(x: f32, scale: f32, offset: f32) -> i8 {
+    ((x - offset) / scale).round().clamp(-CODE_MAX, CODE_MAX) as i8
+}
+
+fn min_max(values: impl Iterator<Item = f32>) -> (f32, f32) {
+    values.fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), x| {
+        (lo.min(x), hi.max(x))
+    })
+}
+
+// 查询向量做对称量化：y ≈ t * p，返回 (p, t)
+fn quantize_query(query: &[f32]) -> (Vec<i8>, f32) {
+    let max_abs = query.iter().fold(0.0f32, |m, x| m.max(x.abs()));
+    if max_abs == 0.0 || !max_abs.is_finite() {
+        return (vec![0; query.len()], 0.0);
+    }
+    let t = max_abs / CODE_MAX;
+    (
+        query.iter().map(|&x| quantize_value(x, t, 0.0)).collect(),
+        t,
+    )
+}
+
+#[wasm_bindgen]
+pub struct Int8Quantizer {
+    dim: usize,
+    granularity: QuantizationGranularity,
+    // 仅 PerDimension 使用，长度为 dim
+    scales: Vec<f32>,
+    offsets: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl Int8Quantizer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(dim: usize, granularity: QuantizationGranularity) -> Int8Quantizer {
+        Int8Quantizer {
+            dim,
+            granularity,
+            scales: Vec::new(),
+            offsets: Vec::new(),
+        }
+    }
+
+    // 从持久化的逐维参数恢复，长度不符时返回 None
+    pub fn from_params(dim: usize, scales: Vec<f32>, offsets: Vec<f32>) -> Option<Int8Quantizer> {
+        if dim == 0 || scales.len() != dim || offsets.len() != dim {
+            return None;
+        }
+        Some(Int8Quantizer {
+            dim,
+            granularity: QuantizationGranularity::PerDimension,
+            scales,
+            offsets,
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn granularity(&self) -> QuantizationGranularity {
+        self.granularity
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_calibrated(&self) -> bool {
+        self.granularity == QuantizationGranularity::PerVector || self.scales.len() == self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn scales(&self) -> Vec<f32> {
+        self.scales.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn offsets(&self) -> Vec<f32> {
+        self.offsets.clone()
+    }
+
+    // 用样本向量校准逐维 min/max；PerVector 模式无需校准
+    pub fn calibrate(&mut self, sample: &[f32]) -> bool {
+        if self.granularity == QuantizationGranularity::PerVector {
+            return true;
+        }
+        match packed_count(sample, self.dim) {
+            Some(n) if n > 0 => {}
+            _ => return false,
+        }
+
+        let (scales, offsets) = (0..self.dim)
+            .map(|d| {
+                let (lo, hi) = min_max(sample.iter().skip(d).step_by(self.dim).copied());
+                affine_params(lo, hi)
+            })
+            .unzip();
+        self.scales = scales;
+        self.offsets = offsets;
+        true
+    }
+
+    // 量化打包向量，未校准或形状不符时返回 None
+    pub fn quantize(&self, vectors: &[f32]) -> Option<Int8Vectors> {
+        if !self.is_calibrated() {
+            return None;
+        }
+        let count = packed_count(vectors, self.dim)?;
+        let mut codes = Vec::with_capacity(vectors.len());
+
+        let (scales, offsets) = match self.granularity {
+            QuantizationGranularity::PerVector => {
+                let mut scales = Vec::with_capacity(count);
+                let mut offsets = Vec::with_capacity(count);
+                for vector in vectors.chunks_exact(self.dim) {
+                    let (lo, hi) = min_max(vector.iter().copied());
+                    let (scale, offset) = affine_params(lo, hi);
+                    codes.extend(vector.iter().map(|&x| quantize_value(x, scale, offset)));
+                    scales.push(scale);
+                    offsets.push(offset);
+                }
+                (scales, offsets)
+            }
+            QuantizationGranularity::PerDimension => {
+                for vector in vectors.chunks_exact(self.dim) {
+                    codes.extend(
+                        vector
+                            .iter()
+                            .zip(self.scales.iter().zip(&self.offsets))
+                            .map(|(&x, (&scale, &offset))| quantize_value(x, scale, offset)),
+                    );
+                }
+                (self.scales.clone(), self.offsets.clone())
+            }
+        };
+
+        Some(Int8Vectors::from_raw(
+            self.dim,
+            self.granularity,
+            codes,
+            scales,
+            offsets,
+        ))
+    }
+
+    // 将 int8 码反量化为 f32（PerDimension 模式）
+    pub fn dequantize(&self, codes: &[i8]) -> Vec<f32> {
+        if self.granularity != QuantizationGranularity::PerDimension
+            || !self.is_calibrated()
+            || !codes.len().is_multiple_of(self.dim)
+        {
+            return Vec::new();
+        }
+        codes
+            .chunks_exact(self.dim)
+            .flat_map(|row| {
+                row.iter()
+                    .zip(self.scales.iter().zip(&self.offsets))
+                    .map(|(&q, (&scale, &offset))| scale * q as f32 + offset)
+            })
+            .collect()
+    }
+}
+
+// int8 量化后的向量集合，可直接在量化数据上搜索
+#[wasm_bindgen]
+pub struct Int8Vectors {
+    dim: usize,
+    granularity: QuantizationGranularity,
+    codes: Vec<i8>,
+    // PerVector 时长度为向量数，PerDimension 时长度为 dim
+    scales: Vec<f32>,
+    offsets: Vec<f32>,
+    // 反量化后向量的范数平方，用于余弦和 L2
+    norms_sq: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl Int8Vectors {
+    // 从持久化的码和参数恢复，形状不符时返回 None
+    pub fn from_parts(
+        dim: usize,
+        granularity: QuantizationGranularity,
+        codes: Vec<i8>,
+        scales: Vec<f32>,
+        offsets: Vec<f32>,
+    ) -> Option<Int8Vectors> {
+        if dim == 0 || !codes.len().is_multiple_of(dim) {
+            return None;
+        }
+        let params_len = match granularity {
+            QuantizationGranularity::PerVector => codes.len() / dim,
+            QuantizationGranularity::PerDimension => dim,
+        };
+        if scales.len() != params_len || offsets.len() != params_len {
+            return None;
+        }
+        Some(Self::from_raw(dim, granularity, codes, scales, offsets))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn granularity(&self) -> QuantizationGranularity {
+        self.granularity
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.norms_sq.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn codes(&self) -> Vec<i8> {
+        self.codes.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn scales(&self) -> Vec<f32> {
+        self.scales.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn offsets(&self) -> Vec<f32> {
+        self.offsets.clone()
+    }
+
+    // 实际占用的字节数（码 + 参数 + 缓存的范数）
+    pub fn memory_bytes(&self) -> usize {
+        self.codes.len() + 4 * (self.scales.len() + self.offsets.len() + self.norms_sq.len())
+    }
+
+    pub fn dequantize(&self) -> Vec<f32> {
+        let mut result = vec![0.0f32; self.codes.len()];
+        for (i, out) in result.chunks_exact_mut(self.dim).enumerate() {
+            self.dequantize_into(i, out);
+        }
+        result
+    }
+
+    pub fn dequantize_vector(&self, index: usize) -> Option<Vec<f32>> {
+        if index >= self.length() {
+            return None;
+        }
+        let mut out = vec![0.0f32; self.dim];
+        self.dequantize_into(index, &mut out);
+        Some(out)
+    }
+
+    // 在量化数据上对所有向量打分，L2Squared / L1 返回距离
+    pub fn scores(&self, query: &[f32], metric: Metric) -> Vec<f32> {
+        let mut results = Vec::with_capacity(self.length());
+        self.for_each_score(query, metric, |_, score| results.push(score));
+        results
+    }
+
+    pub fn top_k(&self, query: &[f32], k: usize, metric: Metric) -> TopKResults {
+        let mut heap = TopKHeap::for_metric(k, metric);
+        self.for_each_score(query, metric, |i, score| heap.push(i as u32, score));
+        heap.into_results()
+    }
+}
+
+impl Int8Vectors {
+    fn from_raw(
+        dim: usize,
+        granularity: QuantizationGranularity,
+        codes: Vec<i8>,
+        scales: Vec<f32>,
+        offsets: Vec<f32>,
+    ) -> Int8Vectors {
+        let mut vectors = Int8Vectors {
+            dim,
+            granularity,
+            codes,
+            scales,
+            offsets,
+            norms_sq: Vec::new(),
+        };
+        let mut buffer = vec![0.0f32; dim];
+        vectors.norms_sq = (0..vectors.codes.len() / dim)
+            .map(|i| {
+                vectors.dequantize_into(i, &mut buffer);
+                compute_norm_squared_simd(&buffer)
+            })
+            .collect();
+        vectors
+    }
+
+    #[inline]
+    fn row(&self, index: usize) -> &[i8] {
+        &self.codes[index * self.dim..(index + 1) * self.dim]
+    }
+
+    fn dequantize_into(&self, index: usize, out: &mut [f32]) {
+        let row = self.row(index);
+        match self.granularity {
+            QuantizationGranularity::PerVector => {
+                let (scale, offset) = (self.scales[index], self.offsets[index]);
+                for (o, &q) in out.iter_mut().zip(row) {
+                    *o = scale * q as f32 + offset;
+                }
+            }
+            QuantizationGranularity::PerDimension => {
+                for ((o, &q), (&scale, &offset)) in out
+                    .iter_mut()
+                    .zip(row)
+                    .zip(self.scales.iter().zip(&self.offsets))
+                {
+                    *o = scale * q as f32 + offset;
+                }
+            }
+        }
+    }
+
+    // 非对称打分：查询做对称 int8 量化后与库向量走 int8 点积内核，
+    // 再用仿射参数还原出 f32 点积；L1 不可分解，逐个反量化计算
+    fn for_each_score(&self, query: &[f32], metric: Metric, mut on_score: impl FnMut(usize, f32)) {
+        if query.len() != self.dim || self.dim == 0 {
+            return;
+        }
+
+        if metric == Metric::L1 {
+            let mut buffer = vec![0.0f32; self.dim];
+            for i in 0..self.length() {
+                self.dequantize_into(i, &mut buffer);
+                on_score(i, l1_simd(&buffer, query));
+            }
+            return;
+        }
+
+        let query_sum: f32 = query.iter().sum();
+        let query_norm_sq = compute_norm_squared_simd(query);
+
+        // PerDimension：y·(s∘q + o) = (y∘s)·q + y·o
+        let (codes_query, t, constant) = match self.granularity {
+            QuantizationGranularity::PerVector => {
+                let (p, t) = quantize_query(query);
+                (p, t, 0.0)
+            }
+            QuantizationGranularity::PerDimension => {
+                let scaled: Vec<f32> = query.iter().zip(&self.scales).map(|(y, s)| y * s).collect();
+                let (p, t) = quantize_query(&scaled);
+                let constant = query.iter().zip(&self.offsets).map(|(y, o)| y * o).sum();
+                (p, t, constant)
+            }
+        };
+
+        for i in 0..self.length() {
+            let int_dot = dot_i8_simd(&codes_query, self.row(i)) as f32;
+            let dot_product = match self.granularity {
+                QuantizationGranularity::PerVector => {
+                    t * self.scales[i] * int_dot + self.offsets[i] * query_sum
+                }
+                QuantizationGranularity::PerDimension => t * int_dot + constant,
+            };
+            let score = match metric {
+                Metric::Dot => dot_product,
+                Metric::Cosine => {
+                    cosine_from_parts(dot_product, self.norms_sq[i].sqrt(), query_norm_sq.sqrt())
+                }
+                Metric::L2Squared => {
+                    (self.norms_sq[i] + query_norm_sq - 2.0 * dot_product).max(0.0)
+                }
+                Metric::L1 => unreachable!(),
+            };
+            on_score(i, score);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    const DIM: usize = 37;
+    const COUNT: usize = 64;
+
+    const METRICS: [Metric; 4] = [Metric::Dot, Metric::Cosine, Metric::L2Squared, Metric::L1];
+
+    fn quantized(granularity: QuantizationGranularity, vectors: &[f32]) -> Int8Vectors {
+        let mut quantizer = Int8Quantizer::new(DIM, granularity);
+        assert!(quantizer.calibrate(vectors));
+        quantizer.quantize(vectors).unwrap()
+    }
+
+    // 各度量允许的绝对误差：[-1, 1) 区间、37 维时 int8 量化的误差量级
+    fn tolerance(metric: Metric) -> f32 {
+        match metric {
+            Metric::Dot => 0.05,
+            Metric::Cosine => 0.01,
+            Metric::L2Squared => 0.1,
+            Metric::L1 => 0.1,
+        }
+    }
+
+    #[test]
+    fn quantized_scores_match_f32_scores() {
+        let vectors = random_vectors(1, COUNT, DIM);
+        let queries = random_vectors(2, 8, DIM);
+        for granularity in [
+            QuantizationGranularity::PerVector,
+            QuantizationGranularity::PerDimension,
+        ] {
+            let int8 = quantized(granularity, &vectors);
+            for query in queries.chunks_exact(DIM) {
+                for metric in METRICS {
+                    let scores = int8.scores(query, metric);
+                    assert_eq!(scores.len(), COUNT);
+                    for (vector, &score) in vectors.chunks_exact(DIM).zip(&scores) {
+                        let expected = metric.score(vector, query);
+                        assert!(
+                            (score - expected).abs() <= tolerance(metric),
+                            "{:?} {:?}: {} vs {}",
+                            granularity,
+                            metric,
+                            score,
+                            expected
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dequantization_error_is_within_half_a_step() {
+        let vectors = random_vectors(3, COUNT, DIM);
+
+        let per_vector = quantized(QuantizationGranularity::PerVector, &vectors);
+        let restored = per_vector.dequantize();
+        assert_eq!(restored.len(), vectors.len());
+        for (i, (original, restored)) in vectors
+            .chunks_exact(DIM)
+            .zip(restored.chunks_exact(DIM))
+            .enumerate()
+        {
+            let step = per_vector.scales()[i];
+            for (x, y) in original.iter().zip(restored) {
+                assert!((x - y).abs() <= step * 0.5 + 1e-6);
+            }
+        }
+
+        let mut quantizer = Int8Quantizer::new(DIM, QuantizationGranularity::PerDimension);
+        assert!(quantizer.calibrate(&vectors));
+        let per_dimension = quantizer.quantize(&vectors).unwrap();
+        let restored = per_dimension.dequantize();
+        assert_eq!(quantizer.dequantize(&per_dimension.codes()), restored);
+        for (original, restored) in vectors.chunks_exact(DIM).zip(restored.chunks_exact(DIM)) {
+            for ((x, y), &step) in original.iter().zip(restored).zip(&quantizer.scales()) {
+                assert!((x - y).abs() <= step * 0.5 + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn top_k_agrees_with_scores() {
+        let vectors = random_vectors(4, COUNT, DIM);
+        let query = &random_vectors(5, 1, DIM);
+        let int8 = quantized(QuantizationGranularity::PerDimension, &vectors);
+        for metric in METRICS {
+            let scores = int8.scores(query, metric);
+            let top = int8.top_k(query, 5, metric);
+            for (&index, &score) in top.indices().iter().zip(&top.scores()) {
+                assert_eq!(scores[index as usize], score);
+            }
+            let worst = *top.scores().last().unwrap();
+            let better = scores
+                .iter()
+                .filter(|&&s| {
+                    if metric.higher_is_better() {
+                        s > worst
+                    } else {
+                        s < worst
+                    }
+                })
+                .count();
+            assert!(better < 5);
+        }
+    }
+
+    #[test]
+    fn uncalibrated_and_misshaped_input_is_rejected() {
+        let quantizer = Int8Quantizer::new(DIM, QuantizationGranularity::PerDimension);
+        assert!(!quantizer.is_calibrated());
+        assert!(quantizer.quantize(&random_vectors(6, 2, DIM)).is_none());
+        assert!(Int8Quantizer::from_params(DIM, vec![1.0; DIM], vec![0.0; DIM - 1]).is_none());
+
+        let int8 = quantized(
+            QuantizationGranularity::PerVector,
+            &random_vectors(7, 4, DIM),
+        );
+        assert!(int8.scores(&[0.0; DIM - 1], Metric::Dot).is_empty());
+        assert!(int8.dequantize_vector(4).is_none());
+    }
+}
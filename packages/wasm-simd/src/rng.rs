@@ -0,0 +1,46 @@
+// 可设定种子的确定性伪随机数生成器（SplitMix64），保证训练和建图结果可复现
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    // 当前内部状态，Rng::new(state) 可从该状态继续生成相同序列
+    pub(crate) fn state(&self) -> u64 {
+        self.state
+    }
+
+    #[inline]
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // [0, 1) 区间的均匀分布
+    #[inline]
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    // [0, n) 区间的均匀整数，n 必须大于 0
+    #[inline]
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+// 测试用：[-1, 1) 区间的均匀随机打包向量
+#[cfg(test)]
+pub(crate) fn random_vectors(seed: u64, count: usize, dim: usize) -> Vec<f32> {
+    let mut rng = Rng::new(seed);
+    (0..count * dim)
+        .map(|_| (rng.next_f64() * 2.0 - 1.0) as f32)
+        .collect()
+}
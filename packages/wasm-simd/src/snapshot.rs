@@ -0,0 +1,707 @@
+use std::collections::{BTreeMap, HashMap};
+
+use wasm_bindgen::prelude::*;
+
+use crate::codec::{crc32, ByteReader, ByteWriter, DecodeError};
+use crate::documents::DocumentStore;
+use crate::hnsw::HnswIndex;
+use crate::ivf::IvfIndex;
+use crate::metric::Metric;
+use crate::store::VectorStore;
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"SMSN";
+const SNAPSHOT_VERSION: u16 = 1;
+
+// 段标签
+const SECTION_VECTORS: &[u8; 4] = b"VECT";
+const SECTION_FLAT: &[u8; 4] = b"FLAT";
+const SECTION_HNSW: &[u8; 4] = b"HNSW";
+const SECTION_IVF: &[u8; 4] = b"IVFL";
+const SECTION_METADATA: &[u8; 4] = b"META";
+const SECTION_DOCUMENTS: &[u8; 4] = b"DOCS";
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotIndexKind {
+    Flat = 0,
+    Hnsw = 1,
+    Ivf = 2,
+}
+
+#[derive(Clone)]
+enum SnapshotIndex {
+    Flat(VectorStore),
+    Hnsw(HnswIndex),
+    Ivf(IvfIndex),
+}
+
+impl SnapshotIndex {
+    fn kind(&self) -> SnapshotIndexKind {
+        match self {
+            SnapshotIndex::Flat(_) => SnapshotIndexKind::Flat,
+            SnapshotIndex::Hnsw(_) => SnapshotIndexKind::Hnsw,
+            SnapshotIndex::Ivf(_) => SnapshotIndexKind::Ivf,
+        }
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (u32, &[f32])> + '_> {
+        match self {
+            SnapshotIndex::Flat(store) => Box::new(store.entries()),
+            SnapshotIndex::Hnsw(index) => Box::new(index.entries()),
+            SnapshotIndex::Ivf(index) => Box::new(index.entries()),
+        }
+    }
+
+    fn contains(&self, label: u32) -> bool {
+        match self {
+            SnapshotIndex::Flat(store) => store.contains(label),
+            SnapshotIndex::Hnsw(index) => index.contains(label),
+            SnapshotIndex::Ivf(index) => index.contains(label),
+        }
+    }
+}
+
+fn metric_from_u32(value: u32) -> Result<Metric, DecodeError> {
+    match value {
+        0 => Ok(Metric::Dot),
+        1 => Ok(Metric::Cosine),
+        2 => Ok(Metric::L2Squared),
+        3 => Ok(Metric::L1),
+        other => Err(DecodeError::Invalid(format!("unknown metric {}", other))),
+    }
+}
+
+// 先校验魔数和版本，再用末尾的校验和排除损坏，返回该校验和
+pub(crate) fn verify_checksum(bytes: &[u8]) -> Result<u32, DecodeError> {
+    let mut reader = ByteReader::new(bytes);
+    reader.expect_header(SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+    if bytes.len() < 10 {
+        return Err(DecodeError::UnexpectedEof);
+    }
+    let (body, checksum) = bytes.split_at(bytes.len() - 4);
+    let expected = u32::from_le_bytes(checksum.try_into().unwrap());
+    let found = crc32(body);
+    if expected != found {
+        return Err(DecodeError::ChecksumMismatch { expected, found });
+    }
+    Ok(expected)
+}
+
+// 向量索引的单文件快照：头部（模型 id、维度、度量、元素数）、向量、索引结构、
+// 每个 label 的元数据和可选的文档记录，末尾附 CRC32 校验和。
+//
+// 布局（小端）：magic "SMSN" | u16 版本 | u16 索引类型 | u32 维度 | u32 度量 | u32 元素数 |
+// 模型 id | u32 段数 | 段（4 字节标签 + u32 长度 + 内容）... | u32 CRC32
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct Snapshot {
+    model_id: String,
+    index: SnapshotIndex,
+    metadata: BTreeMap<u32, String>,
+    documents: Option<DocumentStore>,
+}
+
+#[wasm_bindgen]
+impl Snapshot {
+    pub fn from_store(model_id: &str, store: &VectorStore) -> Snapshot {
+        Snapshot::with_index(model_id, SnapshotIndex::Flat(store.clone()))
+    }
+
+    pub fn from_hnsw(model_id: &str, index: &HnswIndex) -> Snapshot {
+        Snapshot::with_index(model_id, SnapshotIndex::Hnsw(index.clone()))
+    }
+
+    pub fn from_ivf(model_id: &str, index: &IvfIndex) -> Snapshot {
+        Snapshot::with_index(model_id, SnapshotIndex::Ivf(index.clone()))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn model_id(&self) -> String {
+        self.model_id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        match &self.index {
+            SnapshotIndex::Flat(store) => store.dim(),
+            SnapshotIndex::Hnsw(index) => index.dim(),
+            SnapshotIndex::Ivf(index) => index.dim(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn metric(&self) -> Metric {
+        match &self.index {
+            SnapshotIndex::Flat(store) => store.metric(),
+            SnapshotIndex::Hnsw(index) => index.metric(),
+            SnapshotIndex::Ivf(index) => index.metric(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        match &self.index {
+            SnapshotIndex::Flat(store) => store.length(),
+            SnapshotIndex::Hnsw(index) => index.length(),
+            SnapshotIndex::Ivf(index) => index.length(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn index_kind(&self) -> SnapshotIndexKind {
+        self.index.kind()
+    }
+
+    pub fn labels(&self) -> Vec<u32> {
+        self.index.entries().map(|(label, _)| label).collect()
+    }
+
+    // 元数据（如文档映射的 JSON）与 label 绑定，label 不在索引中时返回 false
+    pub fn set_metadata(&mut self, label: u32, value: String) -> bool {
+        if !self.index.contains(label) {
+            return false;
+        }
+        self.metadata.insert(label, value);
+        true
+    }
+
+    pub fn metadata(&self, label: u32) -> Option<String> {
+        self.metadata.get(&label).cloned()
+    }
+
+    pub fn remove_metadata(&mut self, label: u32) -> bool {
+        self.metadata.remove(&label).is_some()
+    }
+
+    pub fn metadata_labels(&self) -> Vec<u32> {
+        self.metadata.keys().copied().collect()
+    }
+
+    // 与向量一起保存文档记录，存在不在索引中的 label 时返回 false
+    pub fn set_documents(&mut self, documents: &DocumentStore) -> bool {
+        if !documents
+            .labels()
+            .into_iter()
+            .all(|label| self.index.contains(label))
+        {
+            return false;
+        }
+        self.documents = Some(documents.clone());
+        true
+    }
+
+    // 快照中没有文档段时返回 undefined
+    pub fn documents(&self) -> Option<DocumentStore> {
+        self.documents.clone()
+    }
+
+    pub fn clear_documents(&mut self) {
+        self.documents = None;
+    }
+
+    // 恢复为平铺向量库（任何索引类型都可以）
+    pub fn to_store(&self) -> VectorStore {
+        match &self.index {
+            SnapshotIndex::Flat(store) => store.clone(),
+            index => {
+                let mut store = VectorStore::new(self.dim(), self.metric());
+                for (label, vector) in index.entries() {
+                    store.add(label, vector);
+                }
+                store
+            }
+        }
+    }
+
+    // 索引类型不是 HNSW 时返回 undefined
+    pub fn to_hnsw(&self) -> Option<HnswIndex> {
+        match &self.index {
+            SnapshotIndex::Hnsw(index) => Some(index.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn to_ivf(&self) -> Option<IvfIndex> {
+        match &self.index {
+            SnapshotIndex::Ivf(index) => Some(index.clone()),
+            _ => None,
+        }
+    }
+
+    // 校验快照是否属于当前模型配置，不一致时抛出说明原因的错误
+    pub fn ensure_compatible(
+        &self,
+        model_id: &str,
+        dim: usize,
+        metric: Metric,
+    ) -> Result<(), JsValue> {
+        Ok(self.check_compatible(model_id, dim, metric)?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut sections: Vec<(&[u8; 4], ByteWriter)> = Vec::new();
+
+        let mut vectors = ByteWriter::new();
+        let entries: Vec<(u32, &[f32])> = self.index.entries().collect();
+        vectors.u32(entries.len() as u32);
+        for (label, _) in &entries {
+            vectors.u32(*label);
+        }
+        for (_, vector) in &entries {
+            vectors.f32_slice(vector);
+        }
+        sections.push((SECTION_VECTORS, vectors));
+
+        let mut index = ByteWriter::new();
+        let tag = match &self.index {
+            SnapshotIndex::Flat(store) => {
+                store.write_flat(&mut index);
+                SECTION_FLAT
+            }
+            SnapshotIndex::Hnsw(graph) => {
+                graph.write_graph(&mut index);
+                SECTION_HNSW
+            }
+            SnapshotIndex::Ivf(lists) => {
+                lists.write_lists(&mut index);
+                SECTION_IVF
+            }
+        };
+        sections.push((tag, index));
+
+        let mut metadata = ByteWriter::new();
+        metadata.u32(self.metadata.len() as u32);
+        for (label, value) in &self.metadata {
+            metadata.u32(*label);
+            metadata.str(value);
+        }
+        sections.push((SECTION_METADATA, metadata));
+
+        if let Some(documents) = &self.documents {
+            let mut section = ByteWriter::new();
+            documents.write(&mut section);
+            sections.push((SECTION_DOCUMENTS, section));
+        }
+
+        let mut writer = ByteWriter::new();
+        writer.bytes(SNAPSHOT_MAGIC);
+        writer.u16(SNAPSHOT_VERSION);
+        writer.u16(self.index.kind() as u16);
+        writer.u32(self.dim() as u32);
+        writer.u32(self.metric() as u32);
+        writer.u32(entries.len() as u32);
+        writer.str(&self.model_id);
+        writer.u32(sections.len() as u32);
+        for (tag, section) in sections {
+            writer.bytes(tag);
+            writer.u32(section.len() as u32);
+            writer.bytes(&section.into_bytes());
+        }
+        let mut bytes = writer.into_bytes();
+        let checksum = crc32(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    // 校验和不符、格式错误或段之间不一致时抛出错误
+    pub fn from_bytes(bytes: &[u8]) -> Result<Snapshot, JsValue> {
+        Ok(Self::decode_bytes(bytes)?)
+    }
+}
+
+impl Snapshot {
+    fn with_index(model_id: &str, index: SnapshotIndex) -> Snapshot {
+        Snapshot {
+            model_id: model_id.to_string(),
+            index,
+            metadata: BTreeMap::new(),
+            documents: None,
+        }
+    }
+
+    // 回放增量日志时使用：label 已存在时覆盖
+    pub(crate) fn apply_add(&mut self, label: u32, vector: &[f32]) -> Result<(), DecodeError> {
+        if vector.len() != self.dim() {
+            return Err(DecodeError::Mismatch(format!(
+                "vector for label {} has dimension {}, snapshot has {}",
+                label,
+                vector.len(),
+                self.dim()
+            )));
+        }
+        let applied = match &mut self.index {
+            SnapshotIndex::Flat(store) => store.update(label, vector) || store.add(label, vector),
+            SnapshotIndex::Hnsw(index) => index.insert(label, vector),
+            SnapshotIndex::Ivf(index) => {
+                index.remove(label);
+                index.add(label, vector)
+            }
+        };
+        if applied {
+            Ok(())
+        } else {
+            Err(DecodeError::Invalid(format!(
+                "label {} could not be added (untrained IVF index?)",
+                label
+            )))
+        }
+    }
+
+    // 删除不存在的 label 视为已删除
+    pub(crate) fn apply_remove(&mut self, label: u32) {
+        match &mut self.index {
+            SnapshotIndex::Flat(store) => store.remove(label),
+            SnapshotIndex::Hnsw(index) => index.remove(label),
+            SnapshotIndex::Ivf(index) => index.remove(label),
+        };
+        self.metadata.remove(&label);
+        if let Some(documents) = &mut self.documents {
+            documents.remove(label);
+        }
+    }
+
+    // 写入或覆盖文档记录，快照还没有文档段时新建
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn apply_document(
+        &mut self,
+        label: u32,
+        tab_id: i32,
+        url: &str,
+        title: &str,
+        chunk_index: u32,
+        text: &str,
+        timestamp: f64,
+    ) -> Result<(), DecodeError> {
+        if !self.index.contains(label) {
+            return Err(DecodeError::Invalid(format!(
+                "document for unknown label {}",
+                label
+            )));
+        }
+        self.documents
+            .get_or_insert_with(DocumentStore::new)
+            .insert(label, tab_id, url, title, chunk_index, text, timestamp);
+        Ok(())
+    }
+
+    // 删除某个 tab 的全部文档，连同向量和元数据
+    pub(crate) fn apply_remove_tab(&mut self, tab_id: i32) {
+        let labels = self
+            .documents
+            .as_ref()
+            .map_or_else(Vec::new, |documents| documents.labels_for_tab(tab_id));
+        for label in labels {
+            self.apply_remove(label);
+        }
+    }
+
+    pub(crate) fn apply_remove_url(&mut self, url: &str) {
+        let labels = self
+            .documents
+            .as_ref()
+            .map_or_else(Vec::new, |documents| documents.labels_for_url(url));
+        for label in labels {
+            self.apply_remove(label);
+        }
+    }
+
+    pub(crate) fn apply_metadata(
+        &mut self,
+        label: u32,
+        value: Option<String>,
+    ) -> Result<(), DecodeError> {
+        match value {
+            Some(value) => {
+                if self.set_metadata(label, value) {
+                    Ok(())
+                } else {
+                    Err(DecodeError::Invalid(format!(
+                        "metadata for unknown label {}",
+                        label
+                    )))
+                }
+            }
+            None => {
+                self.metadata.remove(&label);
+                Ok(())
+            }
+        }
+    }
+
+    fn check_compatible(
+        &self,
+        model_id: &str,
+        dim: usize,
+        metric: Metric,
+    ) -> Result<(), DecodeError> {
+        if self.model_id != model_id {
+            return Err(DecodeError::Mismatch(format!(
+                "snapshot was built with model {:?}, expected {:?}",
+                self.model_id, model_id
+            )));
+        }
+        if self.dim() != dim {
+            return Err(DecodeError::Mismatch(format!(
+                "snapshot has dimension {}, expected {}",
+                self.dim(),
+                dim
+            )));
+        }
+        if self.metric() != metric {
+            return Err(DecodeError::Mismatch(format!(
+                "snapshot uses metric {:?}, expected {:?}",
+                self.metric(),
+                metric
+            )));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn decode_bytes(bytes: &[u8]) -> Result<Snapshot, DecodeError> {
+        verify_checksum(bytes)?;
+        let body = &bytes[..bytes.len() - 4];
+        let mut reader = ByteReader::new(&body[6..]);
+        let kind = match reader.u16()? {
+            0 => SnapshotIndexKind::Flat,
+            1 => SnapshotIndexKind::Hnsw,
+            2 => SnapshotIndexKind::Ivf,
+            other => {
+                return Err(DecodeError::Invalid(format!(
+                    "unknown index type {}",
+                    other
+                )))
+            }
+        };
+        let dim = reader.u32()? as usize;
+        let metric = metric_from_u32(reader.u32()?)?;
+        let count = reader.u32()? as usize;
+        let model_id = reader.str()?.to_string();
+        if dim == 0 {
+            return Err(DecodeError::Invalid("dimension is 0".into()));
+        }
+
+        let mut sections: HashMap<[u8; 4], &[u8]> = HashMap::new();
+        let section_count = reader.u32()?;
+        for _ in 0..section_count {
+            let tag: [u8; 4] = reader.take(4)?.try_into().unwrap();
+            let len = reader.u32()? as usize;
+            let payload = reader.take(len)?;
+            // 未知段留给更新的版本，直接忽略
+            if sections.insert(tag, payload).is_some() {
+                return Err(DecodeError::Invalid(format!(
+                    "duplicate section {}",
+                    String::from_utf8_lossy(&tag)
+                )));
+            }
+        }
+        reader.finish()?;
+        let section = |tag: &[u8; 4]| {
+            sections
+                .get(tag)
+                .map(|p| ByteReader::new(p))
+                .ok_or_else(|| {
+                    DecodeError::Invalid(format!(
+                        "missing section {}",
+                        String::from_utf8_lossy(tag)
+                    ))
+                })
+        };
+
+        let mut vectors = section(SECTION_VECTORS)?;
+        let stored = vectors.u32()? as usize;
+        if stored != count {
+            return Err(DecodeError::Invalid(format!(
+                "header declares {} elements but the vector section has {}",
+                count, stored
+            )));
+        }
+        let labels = vectors.u32_vec(count)?;
+        let data = vectors.f32_vec(count.checked_mul(dim).ok_or(DecodeError::UnexpectedEof)?)?;
+        vectors.finish()?;
+        let entries: Vec<(u32, &[f32])> =
+            labels.iter().copied().zip(data.chunks_exact(dim)).collect();
+        let by_label: HashMap<u32, &[f32]> = entries.iter().copied().collect();
+        if by_label.len() != count {
+            return Err(DecodeError::Invalid(
+                "vector section has duplicate labels".into(),
+            ));
+        }
+
+        let index = match kind {
+            SnapshotIndexKind::Flat => {
+                let mut reader = section(SECTION_FLAT)?;
+                let store = VectorStore::read_flat(&mut reader, dim, metric, &entries)?;
+                reader.finish()?;
+                SnapshotIndex::Flat(store)
+            }
+            SnapshotIndexKind::Hnsw => {
+                let mut reader = section(SECTION_HNSW)?;
+                let graph = HnswIndex::read_graph(&mut reader, dim, metric, &by_label)?;
+                reader.finish()?;
+                SnapshotIndex::Hnsw(graph)
+            }
+            SnapshotIndexKind::Ivf => {
+                let mut reader = section(SECTION_IVF)?;
+                let lists = IvfIndex::read_lists(&mut reader, dim, metric, &by_label)?;
+                reader.finish()?;
+                SnapshotIndex::Ivf(lists)
+            }
+        };
+
+        let mut metadata = BTreeMap::new();
+        let mut reader = section(SECTION_METADATA)?;
+        for _ in 0..reader.u32()? {
+            let label = reader.u32()?;
+            let value = reader.str()?.to_string();
+            if !by_label.contains_key(&label) {
+                return Err(DecodeError::Invalid(format!(
+                    "metadata for unknown label {}",
+                    label
+                )));
+            }
+            metadata.insert(label, value);
+        }
+        reader.finish()?;
+
+        let documents = match sections.get(SECTION_DOCUMENTS) {
+            Some(payload) => {
+                let mut reader = ByteReader::new(payload);
+                let documents = DocumentStore::read(&mut reader)?;
+                reader.finish()?;
+                if let Some(label) = documents
+                    .labels()
+                    .into_iter()
+                    .find(|label| !by_label.contains_key(label))
+                {
+                    return Err(DecodeError::Invalid(format!(
+                        "document for unknown label {}",
+                        label
+                    )));
+                }
+                Some(documents)
+            }
+            None => None,
+        };
+
+        Ok(Snapshot {
+            model_id,
+            index,
+            metadata,
+            documents,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    const DIM: usize = 8;
+    const COUNT: usize = 64;
+
+    fn with_sections(mut snapshot: Snapshot) -> Snapshot {
+        let labels = snapshot.labels();
+        assert!(snapshot.set_metadata(labels[0], "{\"title\":\"first\"}".into()));
+        assert!(snapshot.set_metadata(labels[5], "second".into()));
+        let mut documents = DocumentStore::new();
+        for (i, &label) in labels.iter().enumerate().take(10) {
+            let url = format!("https://example.com/{}", i % 3);
+            documents.insert(
+                label,
+                i as i32 % 2,
+                &url,
+                "title",
+                i as u32,
+                "text",
+                i as f64,
+            );
+        }
+        assert!(snapshot.set_documents(&documents));
+        snapshot
+    }
+
+    fn snapshots() -> Vec<Snapshot> {
+        let data = random_vectors(1, COUNT, DIM);
+        let labels: Vec<u32> = (0..COUNT as u32).map(|i| i * 3 + 1).collect();
+
+        let mut store = VectorStore::new(DIM, Metric::Cosine);
+        let mut hnsw = HnswIndex::new(DIM, Metric::Cosine, 8, 32, 5);
+        let mut ivf = IvfIndex::new(DIM, Metric::L2Squared, 4);
+        assert!(ivf.train(&data, 10, 3));
+        for (&label, vector) in labels.iter().zip(data.chunks_exact(DIM)) {
+            assert!(store.add(label, vector));
+            assert!(hnsw.insert(label, vector));
+            assert!(ivf.add(label, vector));
+        }
+        // 删除后留下空闲槽位
+        for label in [4, 10, 61] {
+            store.remove(label);
+            hnsw.remove(label);
+            ivf.remove(label);
+        }
+        vec![
+            with_sections(Snapshot::from_store("model", &store)),
+            with_sections(Snapshot::from_hnsw("model", &hnsw)),
+            with_sections(Snapshot::from_ivf("model", &ivf)),
+        ]
+    }
+
+    #[test]
+    fn every_section_round_trips() {
+        let query = random_vectors(2, 1, DIM);
+        for snapshot in snapshots() {
+            let bytes = snapshot.to_bytes();
+            let restored = Snapshot::decode_bytes(&bytes).unwrap();
+            assert_eq!(restored.to_bytes(), bytes);
+            assert_eq!(restored.index_kind(), snapshot.index_kind());
+            assert_eq!(restored.model_id(), "model");
+            assert_eq!(restored.labels(), snapshot.labels());
+            assert_eq!(restored.metadata_labels(), snapshot.metadata_labels());
+            assert_eq!(restored.metadata(1), snapshot.metadata(1));
+            assert_eq!(
+                restored.documents().unwrap().to_bytes(),
+                snapshot.documents().unwrap().to_bytes()
+            );
+            let (before, after) = match (&snapshot.index, &restored.index) {
+                (SnapshotIndex::Flat(a), SnapshotIndex::Flat(b)) => {
+                    (a.search(&query, 5), b.search(&query, 5))
+                }
+                (SnapshotIndex::Hnsw(a), SnapshotIndex::Hnsw(b)) => {
+                    (a.search(&query, 5), b.search(&query, 5))
+                }
+                (SnapshotIndex::Ivf(a), SnapshotIndex::Ivf(b)) => {
+                    (a.search(&query, 5), b.search(&query, 5))
+                }
+                _ => unreachable!(),
+            };
+            assert_eq!(before.indices(), after.indices());
+            assert_eq!(before.scores(), after.scores());
+        }
+    }
+
+    #[test]
+    fn corrupted_bytes_fail_the_checksum() {
+        for snapshot in snapshots() {
+            let mut bytes = snapshot.to_bytes();
+            let middle = bytes.len() / 2;
+            bytes[middle] ^= 0x40;
+            assert!(matches!(
+                Snapshot::decode_bytes(&bytes),
+                Err(DecodeError::ChecksumMismatch { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn truncated_bytes_are_rejected() {
+        let bytes = snapshots()[1].to_bytes();
+        for len in [0, 3, 10, bytes.len() / 2, bytes.len() - 4, bytes.len() - 1] {
+            assert!(
+                Snapshot::decode_bytes(&bytes[..len]).is_err(),
+                "length {}",
+                len
+            );
+        }
+    }
+}
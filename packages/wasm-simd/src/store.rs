@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::codec::{ByteReader, ByteWriter, DecodeError};
+use crate::filter::{SearchFilter, VectorAttributes};
+use crate::metric::{for_each_score, Metric};
+use crate::topk::{TopKHeap, TopKResults};
+use crate::unit::normalize_in_place;
+
+// 常驻 WASM 线性内存的向量库：向量连续存放，查询时只需传入查询向量
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct VectorStore {
+    dim: usize,
+    metric: Metric,
+    data: Vec<f32>,
+    ids: Vec<u32>,
+    slots: HashMap<u32, usize>,
+    // 单位向量模式：写入时归一化，余弦搜索退化为纯点积
+    unit_vectors: bool,
+}
+
+#[wasm_bindgen]
+impl VectorStore {
+    #[wasm_bindgen(constructor)]
+    pub fn new(dim: usize, metric: Metric) -> VectorStore {
+        VectorStore {
+            dim,
+            metric,
+            data: Vec::new(),
+            ids: Vec::new(),
+            slots: HashMap::new(),
+            unit_vectors: false,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.ids.len()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn unit_vectors(&self) -> bool {
+        self.unit_vectors
+    }
+
+    // 开启单位向量模式时会立即归一化已有向量
+    pub fn set_unit_vectors(&mut self, enabled: bool) {
+        if enabled && !self.unit_vectors {
+            self.normalize_all();
+        }
+        self.unit_vectors = enabled;
+    }
+
+    // 按需归一化所有已存向量
+    pub fn normalize_all(&mut self) {
+        if self.dim == 0 {
+            return;
+        }
+        for vector in self.data.chunks_exact_mut(self.dim) {
+            normalize_in_place(vector);
+        }
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        self.slots.contains_key(&id)
+    }
+
+    // 新增向量，id 已存在或维度不符时返回 false
+    pub fn add(&mut self, id: u32, vector: &[f32]) -> bool {
+        if vector.len() != self.dim || self.dim == 0 || self.slots.contains_key(&id) {
+            return false;
+        }
+        self.slots.insert(id, self.ids.len());
+        self.ids.push(id);
+        let start = self.data.len();
+        self.data.extend_from_slice(vector);
+        if self.unit_vectors {
+            normalize_in_place(&mut self.data[start..]);
+        }
+        true
+    }
+
+    // 批量新增，返回成功添加的数量
+    pub fn add_batch(&mut self, ids: &[u32], vectors: &[f32]) -> usize {
+        if self.dim == 0 || vectors.len() != ids.len() * self.dim {
+            return 0;
+        }
+        self.data.reserve(vectors.len());
+        ids.iter()
+            .zip(vectors.chunks_exact(self.dim))
+            .filter(|(&id, vector)| self.add(id, vector))
+            .count()
+    }
+
+    // 覆盖已有向量，id 不存在或维度不符时返回 false
+    pub fn update(&mut self, id: u32, vector: &[f32]) -> bool {
+        if vector.len() != self.dim {
+            return false;
+        }
+        match self.slots.get(&id) {
+            Some(&slot) => {
+                let unit_vectors = self.unit_vectors;
+                let target = self.vector_at_mut(slot);
+                target.copy_from_slice(vector);
+                if unit_vectors {
+                    normalize_in_place(target);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    // 删除向量：用最后一个向量填补空位，保持存储连续
+    pub fn remove(&mut self, id: u32) -> bool {
+        let slot = match self.slots.remove(&id) {
+            Some(slot) => slot,
+            None => return false,
+        };
+        let last = self.ids.len() - 1;
+        if slot != last {
+            let moved_id = self.ids[last];
+            self.data
+                .copy_within(last * self.dim..(last + 1) * self.dim, slot * self.dim);
+            self.ids[slot] = moved_id;
+            self.slots.insert(moved_id, slot);
+        }
+        self.ids.truncate(last);
+        self.data.truncate(last * self.dim);
+        true
+    }
+
+    pub fn get(&self, id: u32) -> Option<Vec<f32>> {
+        self.slots
+            .get(&id)
+            .map(|&slot| self.vector_at(slot).to_vec())
+    }
+
+    pub fn ids(&self) -> Vec<u32> {
+        self.ids.clone()
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.ids.clear();
+        self.slots.clear();
+    }
+
+    // 精确搜索，返回结果的 indices 为向量 id
+    pub fn search(&self, query: &[f32], k: usize) -> TopKResults {
+        let mut heap = TopKHeap::for_metric(k, self.metric);
+
+        // 单位向量模式下余弦相似度等价于与归一化查询的点积
+        let normalized_query;
+        let (metric, query) = if self.unit_vectors && self.metric == Metric::Cosine {
+            let mut q = query.to_vec();
+            normalize_in_place(&mut q);
+            normalized_query = q;
+            (Metric::Dot, normalized_query.as_slice())
+        } else {
+            (self.metric, query)
+        };
+
+        for_each_score(metric, &self.data, query, self.dim, |slot, score| {
+            heap.push(self.ids[slot], score)
+        });
+        heap.into_results()
+    }
+
+    // 只在满足 filter 的向量中做精确搜索，不匹配的向量不参与打分
+    pub fn search_filtered(
+        &self,
+        query: &[f32],
+        k: usize,
+        attributes: &VectorAttributes,
+        filter: &SearchFilter,
+    ) -> TopKResults {
+        if query.len() != self.dim || self.dim == 0 {
+            return TopKResults::default();
+        }
+        if filter.is_empty() {
+            return self.search(query, k);
+        }
+        let mut query = query.to_vec();
+        let metric = if self.unit_vectors && self.metric == Metric::Cosine {
+            normalize_in_place(&mut query);
+            Metric::Dot
+        } else {
+            self.metric
+        };
+        let mut heap = TopKHeap::for_metric(k, metric);
+        for (slot, &id) in self.ids.iter().enumerate() {
+            if filter.matches(attributes, id) {
+                heap.push(id, metric.score(self.vector_at(slot), &query));
+            }
+        }
+        heap.into_results()
+    }
+}
+
+impl VectorStore {
+    pub(crate) fn vector(&self, id: u32) -> Option<&[f32]> {
+        self.slots.get(&id).map(|&slot| self.vector_at(slot))
+    }
+
+    #[inline]
+    fn vector_at(&self, slot: usize) -> &[f32] {
+        &self.data[slot * self.dim..(slot + 1) * self.dim]
+    }
+
+    #[inline]
+    fn vector_at_mut(&mut self, slot: usize) -> &mut [f32] {
+        &mut self.data[slot * self.dim..(slot + 1) * self.dim]
+    }
+}
+
+// 快照读写：向量按存储顺序由快照的向量段提供，这里只记录单位向量模式
+impl VectorStore {
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (u32, &[f32])> + '_ {
+        self.ids
+            .iter()
+            .copied()
+            .zip(self.data.chunks_exact(self.dim.max(1)))
+    }
+
+    pub(crate) fn write_flat(&self, writer: &mut ByteWriter) {
+        writer.u8(self.unit_vectors as u8);
+    }
+
+    // entries 按存储顺序排列；label 重复或维度不符时返回错误
+    pub(crate) fn read_flat(
+        reader: &mut ByteReader,
+        dim: usize,
+        metric: Metric,
+        entries: &[(u32, &[f32])],
+    ) -> Result<VectorStore, DecodeError> {
+        let mut store = VectorStore::new(dim, metric);
+        for &(id, vector) in entries {
+            if !store.add(id, vector) {
+                return Err(DecodeError::Invalid(format!(
+                    "flat store: vector {} is duplicated or has {} values for dimension {}",
+                    id,
+                    vector.len(),
+                    dim
+                )));
+            }
+        }
+        // 向量写入快照前已归一化，恢复时不再重复处理
+        store.unit_vectors = reader.u8()? != 0;
+        Ok(store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::random_vectors;
+
+    const DIM: usize = 6;
+
+    fn filled(metric: Metric, count: u32) -> (VectorStore, Vec<f32>) {
+        let data = random_vectors(21, count as usize, DIM);
+        let ids: Vec<u32> = (0..count).map(|i| i + 100).collect();
+        let mut store = VectorStore::new(DIM, metric);
+        assert_eq!(store.add_batch(&ids, &data), count as usize);
+        (store, data)
+    }
+
+    fn brute_force(metric: Metric, entries: &[(u32, &[f32])], query: &[f32], k: usize) -> Vec<u32> {
+        let mut heap = TopKHeap::for_metric(k, metric);
+        for &(id, vector) in entries {
+            heap.push(id, metric.score(vector, query));
+        }
+        heap.into_results().indices()
+    }
+
+    #[test]
+    fn swap_remove_keeps_ids_and_vectors_paired() {
+        let (mut store, data) = filled(Metric::L2Squared, 10);
+        assert!(!store.add(100, &data[..DIM]));
+        assert!(!store.add(1, &data[..DIM - 1]));
+
+        // 删除中间、末尾和开头的向量
+        for id in [104, 109, 100] {
+            assert!(store.remove(id));
+            assert!(!store.remove(id));
+            assert!(store.get(id).is_none());
+        }
+        assert_eq!(store.length(), 7);
+        assert_eq!(store.ids(), [107, 101, 102, 103, 108, 105, 106]);
+        for (i, vector) in data.chunks_exact(DIM).enumerate() {
+            let id = i as u32 + 100;
+            if store.contains(id) {
+                assert_eq!(store.get(id).as_deref(), Some(vector));
+            }
+        }
+
+        let query = random_vectors(22, 1, DIM);
+        let entries: Vec<(u32, &[f32])> = store.entries().collect();
+        assert_eq!(
+            store.search(&query, 7).indices(),
+            brute_force(Metric::L2Squared, &entries, &query, 7)
+        );
+
+        // 删除后可以用同一 id 重新加入
+        assert!(store.add(104, &query));
+        assert_eq!(store.search(&query, 1).indices(), [104]);
+        assert!(store.update(104, &data[..DIM]));
+        assert_eq!(store.get(104).as_deref(), Some(&data[..DIM]));
+        assert!(!store.update(999, &data[..DIM]));
+    }
+
+    #[test]
+    fn unit_mode_matches_cosine_search() {
+        let (plain, data) = filled(Metric::Cosine, 40);
+        let mut unit = plain.clone();
+        unit.set_unit_vectors(true);
+        for vector in unit.entries().map(|(_, v)| v) {
+            let norm: f32 = vector.iter().map(|x| x * x).sum();
+            assert!((norm - 1.0).abs() < 1e-5);
+        }
+
+        // 新增和覆盖的向量同样被归一化
+        assert!(unit.add(1, &[2.0, 0.0, 0.0, 0.0, 0.0, 0.0]));
+        assert_eq!(unit.get(1).unwrap()[0], 1.0);
+        assert!(unit.update(1, &[0.0, -3.0, 0.0, 0.0, 0.0, 0.0]));
+        assert_eq!(unit.get(1).unwrap()[1], -1.0);
+        assert!(unit.remove(1));
+
+        for query in random_vectors(23, 5, DIM).chunks_exact(DIM) {
+            let expected = plain.search(query, 10);
+            let actual = unit.search(query, 10);
+            assert_eq!(actual.indices(), expected.indices());
+            for (a, b) in actual.scores().iter().zip(expected.scores()) {
+                assert!((a - b).abs() < 1e-5);
+            }
+        }
+        assert_eq!(plain.get(100).as_deref(), Some(&data[..DIM]));
+    }
+
+    #[test]
+    fn search_filtered_only_scores_matching_vectors() {
+        for unit_vectors in [false, true] {
+            let (mut store, _) = filled(Metric::Cosine, 30);
+            store.set_unit_vectors(unit_vectors);
+            let mut attributes = VectorAttributes::new();
+            for id in 100..130 {
+                attributes.set(id, &[id % 3], 0.0, "https://example.com/");
+            }
+            let mut filter = SearchFilter::new();
+            filter.set_tags(&[1]);
+
+            let query = random_vectors(24, 1, DIM);
+            let matching: Vec<(u32, &[f32])> =
+                store.entries().filter(|(id, _)| id % 3 == 1).collect();
+            let results = store.search_filtered(&query, 5, &attributes, &filter);
+            assert_eq!(
+                results.indices(),
+                brute_force(Metric::Cosine, &matching, &query, 5)
+            );
+
+            // k 大于匹配数时只返回匹配的向量
+            let results = store.search_filtered(&query, 100, &attributes, &filter);
+            assert_eq!(results.length(), 10);
+            assert!(results.indices().iter().all(|id| id % 3 == 1));
+
+            // 空 filter 等同于普通搜索
+            let results = store.search_filtered(&query, 5, &attributes, &SearchFilter::new());
+            assert_eq!(results.indices(), store.search(&query, 5).indices());
+        }
+    }
+
+    #[test]
+    fn read_flat_rejects_invalid_entries() {
+        let data = random_vectors(25, 3, DIM);
+        let read = |entries: &[(u32, &[f32])]| {
+            VectorStore::read_flat(&mut ByteReader::new(&[1]), DIM, Metric::Dot, entries)
+        };
+
+        let store = read(&[(1, &data[..DIM]), (2, &data[DIM..2 * DIM])]).unwrap();
+        assert_eq!(store.ids(), [1, 2]);
+        assert!(store.unit_vectors());
+
+        let duplicate = read(&[(1, &data[..DIM]), (1, &data[DIM..2 * DIM])]);
+        assert!(matches!(duplicate, Err(DecodeError::Invalid(_))));
+        let short = read(&[(1, &data[..DIM - 1])]);
+        assert!(matches!(short, Err(DecodeError::Invalid(_))));
+    }
+}
@@ -0,0 +1,192 @@
+use serde_json::Value;
+
+use super::normalizer::Normalizer;
+use crate::codec::DecodeError;
+
+// tokenizer.json 中 added_tokens 的一项
+pub(super) struct AddedToken {
+    pub(super) id: u32,
+    pub(super) content: String,
+    single_word: bool,
+    lstrip: bool,
+    rstrip: bool,
+    // 为 true 时在归一化之后的文本上匹配
+    normalized: bool,
+}
+
+impl AddedToken {
+    fn from_json(node: &Value) -> Result<AddedToken, DecodeError> {
+        let flag = |key: &str| node.get(key).and_then(Value::as_bool).unwrap_or(false);
+        Ok(AddedToken {
+            id: node
+                .get("id")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| DecodeError::Invalid("added token needs an id".into()))?
+                as u32,
+            content: node
+                .get("content")
+                .and_then(Value::as_str)
+                .ok_or_else(|| DecodeError::Invalid("added token needs content".into()))?
+                .to_string(),
+            single_word: flag("single_word"),
+            lstrip: flag("lstrip"),
+            rstrip: flag("rstrip"),
+            normalized: flag("normalized"),
+        })
+    }
+}
+
+// 切分结果：普通文本片段（附带原始偏移）或命中的 added token
+pub(super) enum Segment {
+    Text { text: String, offset: usize },
+    Added { id: u32, text: String },
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+pub(super) struct AddedVocabulary {
+    tokens: Vec<AddedToken>,
+    // 归一化模式的 token 在归一化之后的内容
+    normalized_contents: Vec<String>,
+}
+
+impl AddedVocabulary {
+    pub(super) fn from_json(
+        node: &Value,
+        normalizer: Option<&Normalizer>,
+    ) -> Result<AddedVocabulary, DecodeError> {
+        let tokens: Vec<AddedToken> = match node.as_array() {
+            Some(entries) => entries
+                .iter()
+                .map(AddedToken::from_json)
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let normalized_contents = tokens
+            .iter()
+            .map(|t| match normalizer {
+                Some(n) if t.normalized => n.normalize(&t.content),
+                _ => t.content.clone(),
+            })
+            .collect();
+        Ok(AddedVocabulary {
+            tokens,
+            normalized_contents,
+        })
+    }
+
+    pub(super) fn tokens(&self) -> &[AddedToken] {
+        &self.tokens
+    }
+
+    pub(super) fn token_to_id(&self, content: &str) -> Option<u32> {
+        self.tokens
+            .iter()
+            .find(|t| t.content == content)
+            .map(|t| t.id)
+    }
+
+    pub(super) fn id_to_token(&self, id: u32) -> Option<&str> {
+        self.tokens
+            .iter()
+            .find(|t| t.id == id)
+            .map(|t| t.content.as_str())
+    }
+
+    // 先在原始文本上切出非归一化 token，再对剩余片段归一化并切出归一化 token
+    pub(super) fn split(&self, text: &str, normalizer: Option<&Normalizer>) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        for (range, id) in self.find_matches(text, false) {
+            let piece = &text[range.0..range.1];
+            if let Some(id) = id {
+                segments.push(Segment::Added {
+                    id,
+                    text: piece.to_string(),
+                });
+                continue;
+            }
+            let normalized = match normalizer {
+                Some(n) => n.normalize(piece),
+                None => piece.to_string(),
+            };
+            for (sub_range, sub_id) in self.find_matches(&normalized, true) {
+                let sub_piece = &normalized[sub_range.0..sub_range.1];
+                match sub_id {
+                    Some(id) => segments.push(Segment::Added {
+                        id,
+                        text: sub_piece.to_string(),
+                    }),
+                    None if !sub_piece.is_empty() => segments.push(Segment::Text {
+                        text: sub_piece.to_string(),
+                        // 归一化后的偏移无法精确映射回原文，只保留片段起点
+                        offset: range.0 + sub_range.0.min(piece.len()),
+                    }),
+                    None => {}
+                }
+            }
+        }
+        segments
+    }
+
+    // 最左最长匹配，返回覆盖整个输入的 (字节区间, 命中的 token id)
+    fn find_matches(&self, text: &str, normalized: bool) -> Vec<((usize, usize), Option<u32>)> {
+        let candidates: Vec<(&AddedToken, &str)> = self
+            .tokens
+            .iter()
+            .zip(&self.normalized_contents)
+            .filter(|(t, c)| t.normalized == normalized && !c.is_empty())
+            .map(|(t, c)| (t, c.as_str()))
+            .collect();
+        if candidates.is_empty() || text.is_empty() {
+            return vec![((0, text.len()), None)];
+        }
+
+        let mut splits = Vec::new();
+        let mut start_offset = 0;
+        let mut pos = 0;
+        while pos < text.len() {
+            let rest = &text[pos..];
+            let matched = candidates
+                .iter()
+                .filter(|(_, content)| rest.starts_with(content))
+                .max_by_key(|(_, content)| content.len());
+            let (token, content) = match matched {
+                Some(&m) => m,
+                None => {
+                    pos += rest.chars().next().map_or(1, char::len_utf8);
+                    continue;
+                }
+            };
+            let mut start = pos;
+            let mut stop = pos + content.len();
+            pos = stop;
+
+            if token.single_word {
+                let start_space = !text[..start].chars().next_back().is_some_and(is_word_char);
+                let stop_space = !text[stop..].chars().next().is_some_and(is_word_char);
+                if !start_space || !stop_space {
+                    continue;
+                }
+            }
+            if token.lstrip {
+                let stripped = text[..start].trim_end_matches(char::is_whitespace).len();
+                start = stripped.max(start_offset);
+            }
+            if token.rstrip {
+                stop +=
+                    text[stop..].len() - text[stop..].trim_start_matches(char::is_whitespace).len();
+            }
+            if start_offset < start {
+                splits.push(((start_offset, start), None));
+            }
+            splits.push(((start, stop), Some(token.id)));
+            start_offset = stop;
+        }
+        if start_offset < text.len() {
+            splits.push(((start_offset, text.len()), None));
+        }
+        splits
+    }
+}
@@ -0,0 +1,639 @@
+// 加载 Hugging Face tokenizer.json 的分词器，输出与 transformers.js 一致的
+// input_ids / attention_mask / token_type_ids
+mod added;
+mod normalizer;
+mod post_processor;
+mod pre_tokenizer;
+mod precompiled;
+#[cfg(test)]
+mod tests;
+mod unigram;
+mod wordpiece;
+
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+use crate::codec::DecodeError;
+use added::{AddedVocabulary, Segment};
+use normalizer::Normalizer;
+use post_processor::PostProcessor;
+use pre_tokenizer::PreTokenizer;
+use unigram::Unigram;
+use wordpiece::WordPiece;
+
+pub(super) struct Token {
+    id: u32,
+    text: String,
+}
+
+fn node_type(node: &Value) -> Result<&str, DecodeError> {
+    node.get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| DecodeError::Invalid("component is missing its type".into()))
+}
+
+fn str_field<'a>(node: &'a Value, key: &str) -> Result<&'a str, DecodeError> {
+    node.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| DecodeError::Invalid(format!("missing string field {}", key)))
+}
+
+fn bool_or(node: &Value, key: &str, default: bool) -> bool {
+    node.get(key).and_then(Value::as_bool).unwrap_or(default)
+}
+
+// 截断策略，与 Hugging Face tokenizers 相同
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TruncationStrategy {
+    // 每次从较长的序列截掉
+    LongestFirst = 0,
+    OnlyFirst = 1,
+    OnlySecond = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TruncationSide {
+    Right = 0,
+    Left = 1,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaddingStrategy {
+    None = 0,
+    // 补齐到批内最长序列
+    Longest = 1,
+    // 补齐到固定长度
+    Fixed = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaddingSide {
+    Right = 0,
+    Left = 1,
+}
+
+struct Truncation {
+    max_length: usize,
+    strategy: TruncationStrategy,
+    side: TruncationSide,
+}
+
+struct Padding {
+    strategy: PaddingStrategy,
+    length: usize,
+    multiple_of: usize,
+    side: PaddingSide,
+    pad_id: u32,
+    pad_type_id: u32,
+    pad_token: String,
+}
+
+// 未加特殊 token 前后的中间结果
+#[derive(Clone, Debug, Default)]
+pub(super) struct RawEncoding {
+    ids: Vec<u32>,
+    type_ids: Vec<u32>,
+    tokens: Vec<String>,
+    attention_mask: Vec<u32>,
+}
+
+impl RawEncoding {
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn push(&mut self, id: u32, token: String, type_id: u32) {
+        self.ids.push(id);
+        self.type_ids.push(type_id);
+        self.tokens.push(token);
+        self.attention_mask.push(1);
+    }
+
+    fn append(&mut self, other: &RawEncoding, type_id: u32) {
+        self.ids.extend_from_slice(&other.ids);
+        self.type_ids
+            .resize(self.type_ids.len() + other.len(), type_id);
+        self.tokens.extend_from_slice(&other.tokens);
+        self.attention_mask.extend_from_slice(&other.attention_mask);
+    }
+
+    fn truncate(&mut self, max_length: usize, side: TruncationSide) {
+        if self.len() <= max_length {
+            return;
+        }
+        match side {
+            TruncationSide::Right => {
+                self.ids.truncate(max_length);
+                self.type_ids.truncate(max_length);
+                self.tokens.truncate(max_length);
+                self.attention_mask.truncate(max_length);
+            }
+            TruncationSide::Left => {
+                let skip = self.len() - max_length;
+                self.ids.drain(..skip);
+                self.type_ids.drain(..skip);
+                self.tokens.drain(..skip);
+                self.attention_mask.drain(..skip);
+            }
+        }
+    }
+
+    fn pad(&mut self, target: usize, padding: &Padding) {
+        if self.len() >= target {
+            return;
+        }
+        let count = target - self.len();
+        let at = match padding.side {
+            PaddingSide::Right => self.len(),
+            PaddingSide::Left => 0,
+        };
+        let repeat = |v| std::iter::repeat_n(v, count);
+        self.ids.splice(at..at, repeat(padding.pad_id));
+        self.type_ids.splice(at..at, repeat(padding.pad_type_id));
+        self.attention_mask.splice(at..at, repeat(0));
+        self.tokens.splice(
+            at..at,
+            std::iter::repeat_n(padding.pad_token.clone(), count),
+        );
+    }
+}
+
+enum Model {
+    Unigram(Unigram),
+    WordPiece(WordPiece),
+}
+
+impl Model {
+    fn from_json(node: &Value) -> Result<Model, DecodeError> {
+        match node_type(node)? {
+            "Unigram" => Ok(Model::Unigram(Unigram::from_json(node)?)),
+            "WordPiece" => Ok(Model::WordPiece(WordPiece::from_json(node)?)),
+            other => Err(DecodeError::Invalid(format!("unsupported model {}", other))),
+        }
+    }
+
+    fn vocab_size(&self) -> usize {
+        match self {
+            Model::Unigram(model) => model.vocab_size(),
+            Model::WordPiece(model) => model.vocab_size(),
+        }
+    }
+
+    fn token_to_id(&self, token: &str) -> Option<u32> {
+        match self {
+            Model::Unigram(model) => model.token_to_id(token),
+            Model::WordPiece(model) => model.token_to_id(token),
+        }
+    }
+
+    fn id_to_token(&self, id: u32) -> Option<&str> {
+        match self {
+            Model::Unigram(model) => model.id_to_token(id),
+            Model::WordPiece(model) => model.id_to_token(id),
+        }
+    }
+
+    fn tokenize(&self, word: &str, tokens: &mut Vec<Token>) {
+        match self {
+            Model::Unigram(model) => model.tokenize(word, tokens),
+            Model::WordPiece(model) => model.tokenize(word, tokens),
+        }
+    }
+}
+
+// 单条编码结果
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Encoding {
+    inner: RawEncoding,
+}
+
+#[wasm_bindgen]
+impl Encoding {
+    #[wasm_bindgen(getter)]
+    pub fn input_ids(&self) -> Vec<u32> {
+        self.inner.ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attention_mask(&self) -> Vec<u32> {
+        self.inner.attention_mask.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn token_type_ids(&self) -> Vec<u32> {
+        self.inner.type_ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn tokens(&self) -> Vec<String> {
+        self.inner.tokens.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+// 批量编码结果：按行优先展开的 [batch_size, sequence_length] 矩阵，
+// lengths 为每行补齐前的真实长度
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct BatchEncoding {
+    batch_size: usize,
+    sequence_length: usize,
+    input_ids: Vec<u32>,
+    attention_mask: Vec<u32>,
+    token_type_ids: Vec<u32>,
+    lengths: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl BatchEncoding {
+    #[wasm_bindgen(getter)]
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sequence_length(&self) -> usize {
+        self.sequence_length
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn input_ids(&self) -> Vec<u32> {
+        self.input_ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn attention_mask(&self) -> Vec<u32> {
+        self.attention_mask.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn token_type_ids(&self) -> Vec<u32> {
+        self.token_type_ids.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lengths(&self) -> Vec<u32> {
+        self.lengths.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub struct Tokenizer {
+    normalizer: Option<Normalizer>,
+    pre_tokenizer: Option<PreTokenizer>,
+    model: Model,
+    post_processor: Option<PostProcessor>,
+    added: AddedVocabulary,
+    truncation: Option<Truncation>,
+    padding: Padding,
+}
+
+#[wasm_bindgen]
+impl Tokenizer {
+    // 从 tokenizer.json 的内容构造；tokenizer.json 中的 truncation / padding 作为默认配置
+    pub fn from_json(json: &str) -> Result<Tokenizer, JsValue> {
+        Ok(Self::parse(json)?)
+    }
+
+    // 包含 added_tokens 的词表大小
+    #[wasm_bindgen(getter)]
+    pub fn vocab_size(&self) -> usize {
+        let extra = self
+            .added
+            .tokens()
+            .iter()
+            .filter(|t| self.model.token_to_id(&t.content).is_none())
+            .count();
+        self.model.vocab_size() + extra
+    }
+
+    pub fn token_to_id(&self, token: &str) -> Option<u32> {
+        self.added
+            .token_to_id(token)
+            .or_else(|| self.model.token_to_id(token))
+    }
+
+    pub fn id_to_token(&self, id: u32) -> Option<String> {
+        self.added
+            .id_to_token(id)
+            .or_else(|| self.model.id_to_token(id))
+            .map(str::to_string)
+    }
+
+    // 截断到 max_length（包含特殊 token），对应 transformers.js 的 truncation: true
+    pub fn set_truncation(
+        &mut self,
+        max_length: usize,
+        strategy: TruncationStrategy,
+        side: TruncationSide,
+    ) {
+        self.truncation = Some(Truncation {
+            max_length,
+            strategy,
+            side,
+        });
+    }
+
+    pub fn disable_truncation(&mut self) {
+        self.truncation = None;
+    }
+
+    // length 只在 Fixed 策略下使用
+    pub fn set_padding(&mut self, strategy: PaddingStrategy, length: usize) {
+        self.padding.strategy = strategy;
+        self.padding.length = length;
+    }
+
+    pub fn set_padding_side(&mut self, side: PaddingSide) {
+        self.padding.side = side;
+    }
+
+    // 补齐后的长度向上取整到该值的倍数，0 表示不取整
+    pub fn set_pad_to_multiple_of(&mut self, multiple_of: usize) {
+        self.padding.multiple_of = multiple_of;
+    }
+
+    // 指定补齐 token，词表中不存在时返回 false
+    pub fn set_pad_token(&mut self, token: &str) -> bool {
+        match self.token_to_id(token) {
+            Some(id) => {
+                self.padding.pad_id = id;
+                self.padding.pad_token = token.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn encode(&self, text: &str, add_special_tokens: bool) -> Encoding {
+        let mut inner = self.encode_raw(text, None, add_special_tokens);
+        if let Some(target) = self.pad_target(inner.len()) {
+            inner.pad(target, &self.padding);
+        }
+        Encoding { inner }
+    }
+
+    pub fn encode_pair(&self, text: &str, pair: &str, add_special_tokens: bool) -> Encoding {
+        let mut inner = self.encode_raw(text, Some(pair), add_special_tokens);
+        if let Some(target) = self.pad_target(inner.len()) {
+            inner.pad(target, &self.padding);
+        }
+        Encoding { inner }
+    }
+
+    // 批量编码，结果总是补齐为矩阵（未开启补齐时按 Longest 处理）
+    pub fn encode_batch(&self, texts: Vec<String>, add_special_tokens: bool) -> BatchEncoding {
+        let encodings: Vec<RawEncoding> = texts
+            .iter()
+            .map(|text| self.encode_raw(text, None, add_special_tokens))
+            .collect();
+        self.collate(encodings)
+    }
+
+    pub fn encode_pair_batch(
+        &self,
+        texts: Vec<String>,
+        pairs: Vec<String>,
+        add_special_tokens: bool,
+    ) -> BatchEncoding {
+        let encodings: Vec<RawEncoding> = texts
+            .iter()
+            .zip(&pairs)
+            .map(|(text, pair)| self.encode_raw(text, Some(pair), add_special_tokens))
+            .collect();
+        self.collate(encodings)
+    }
+}
+
+impl Tokenizer {
+    fn parse(json: &str) -> Result<Tokenizer, DecodeError> {
+        let root: Value = serde_json::from_str(json)
+            .map_err(|e| DecodeError::Invalid(format!("tokenizer.json: {}", e)))?;
+        let null = Value::Null;
+        let component = |key: &str| root.get(key).unwrap_or(&null);
+
+        let normalizer = Normalizer::from_json(component("normalizer"))?;
+        let added = AddedVocabulary::from_json(component("added_tokens"), normalizer.as_ref())?;
+        let mut tokenizer = Tokenizer {
+            pre_tokenizer: PreTokenizer::from_json(component("pre_tokenizer"))?,
+            model: Model::from_json(component("model"))?,
+            post_processor: PostProcessor::from_json(component("post_processor"))?,
+            normalizer,
+            added,
+            truncation: None,
+            padding: Padding {
+                strategy: PaddingStrategy::None,
+                length: 0,
+                multiple_of: 0,
+                side: PaddingSide::Right,
+                pad_id: 0,
+                pad_type_id: 0,
+                pad_token: "[PAD]".to_string(),
+            },
+        };
+
+        // 没有 padding 配置时按常见约定寻找补齐 token
+        for candidate in ["<pad>", "[PAD]"] {
+            if tokenizer.set_pad_token(candidate) {
+                break;
+            }
+        }
+        tokenizer.apply_truncation_config(component("truncation"));
+        tokenizer.apply_padding_config(component("padding"));
+        Ok(tokenizer)
+    }
+
+    fn apply_truncation_config(&mut self, node: &Value) {
+        let max_length = match node.get("max_length").and_then(Value::as_u64) {
+            Some(max_length) => max_length as usize,
+            None => return,
+        };
+        let strategy = match node.get("strategy").and_then(Value::as_str) {
+            Some("OnlyFirst") => TruncationStrategy::OnlyFirst,
+            Some("OnlySecond") => TruncationStrategy::OnlySecond,
+            _ => TruncationStrategy::LongestFirst,
+        };
+        let side = match node.get("direction").and_then(Value::as_str) {
+            Some("Left") => TruncationSide::Left,
+            _ => TruncationSide::Right,
+        };
+        self.set_truncation(max_length, strategy, side);
+    }
+
+    fn apply_padding_config(&mut self, node: &Value) {
+        if !node.is_object() {
+            return;
+        }
+        let strategy = node.get("strategy");
+        match strategy
+            .and_then(|s| s.get("Fixed"))
+            .and_then(Value::as_u64)
+        {
+            Some(length) => self.set_padding(PaddingStrategy::Fixed, length as usize),
+            None => self.set_padding(PaddingStrategy::Longest, 0),
+        }
+        if node.get("direction").and_then(Value::as_str) == Some("Left") {
+            self.padding.side = PaddingSide::Left;
+        }
+        if let Some(multiple_of) = node.get("pad_to_multiple_of").and_then(Value::as_u64) {
+            self.padding.multiple_of = multiple_of as usize;
+        }
+        if let Some(pad_id) = node.get("pad_id").and_then(Value::as_u64) {
+            self.padding.pad_id = pad_id as u32;
+        }
+        if let Some(pad_type_id) = node.get("pad_type_id").and_then(Value::as_u64) {
+            self.padding.pad_type_id = pad_type_id as u32;
+        }
+        if let Some(pad_token) = node.get("pad_token").and_then(Value::as_str) {
+            self.padding.pad_token = pad_token.to_string();
+        }
+    }
+
+    // 归一化、预分词和模型切分，不含特殊 token
+    fn encode_sequence(&self, text: &str) -> RawEncoding {
+        let mut tokens = Vec::new();
+        for segment in self.added.split(text, self.normalizer.as_ref()) {
+            match segment {
+                Segment::Added { id, text } => tokens.push(Token { id, text }),
+                Segment::Text { text, offset } => match &self.pre_tokenizer {
+                    Some(pre_tokenizer) => {
+                        for word in pre_tokenizer.pre_tokenize(&text, offset == 0) {
+                            self.model.tokenize(&word, &mut tokens);
+                        }
+                    }
+                    None => self.model.tokenize(&text, &mut tokens),
+                },
+            }
+        }
+        let mut encoding = RawEncoding::default();
+        for token in tokens {
+            encoding.push(token.id, token.text, 0);
+        }
+        encoding
+    }
+
+    fn encode_raw(&self, text: &str, pair: Option<&str>, add_special_tokens: bool) -> RawEncoding {
+        let mut first = self.encode_sequence(text);
+        let mut second = pair.map(|p| self.encode_sequence(p));
+
+        if let Some(truncation) = &self.truncation {
+            let reserved = match &self.post_processor {
+                Some(processor) if add_special_tokens => processor.added_tokens(second.is_some()),
+                _ => 0,
+            };
+            let max_length = truncation.max_length.saturating_sub(reserved);
+            truncate_pair(&mut first, second.as_mut(), max_length, truncation);
+        }
+
+        match &self.post_processor {
+            Some(processor) => processor.process(first, second, add_special_tokens),
+            None => {
+                let mut output = first;
+                if let Some(second) = &second {
+                    output.append(second, 1);
+                }
+                output
+            }
+        }
+    }
+
+    fn pad_target(&self, longest: usize) -> Option<usize> {
+        let target = match self.padding.strategy {
+            PaddingStrategy::None => return None,
+            PaddingStrategy::Longest => longest,
+            PaddingStrategy::Fixed => self.padding.length,
+        };
+        let multiple_of = self.padding.multiple_of;
+        if multiple_of > 0 && !target.is_multiple_of(multiple_of) {
+            Some(target + multiple_of - target % multiple_of)
+        } else {
+            Some(target)
+        }
+    }
+
+    fn collate(&self, mut encodings: Vec<RawEncoding>) -> BatchEncoding {
+        let longest = encodings.iter().map(RawEncoding::len).max().unwrap_or(0);
+        let target = self.pad_target(longest).unwrap_or(longest).max(longest);
+        let lengths = encodings.iter().map(|e| e.len() as u32).collect();
+
+        let mut batch = BatchEncoding {
+            batch_size: encodings.len(),
+            sequence_length: target,
+            input_ids: Vec::with_capacity(encodings.len() * target),
+            attention_mask: Vec::with_capacity(encodings.len() * target),
+            token_type_ids: Vec::with_capacity(encodings.len() * target),
+            lengths,
+        };
+        for encoding in &mut encodings {
+            encoding.pad(target, &self.padding);
+            batch.input_ids.extend_from_slice(&encoding.ids);
+            batch
+                .attention_mask
+                .extend_from_slice(&encoding.attention_mask);
+            batch.token_type_ids.extend_from_slice(&encoding.type_ids);
+        }
+        batch
+    }
+}
+
+// 与 Hugging Face tokenizers 的 truncate_encodings 相同
+fn truncate_pair(
+    first: &mut RawEncoding,
+    second: Option<&mut RawEncoding>,
+    max_length: usize,
+    truncation: &Truncation,
+) {
+    let side = truncation.side;
+    let total = first.len() + second.as_ref().map_or(0, |s| s.len());
+    if total <= max_length {
+        return;
+    }
+    let to_remove = total - max_length;
+
+    match (truncation.strategy, second) {
+        (TruncationStrategy::LongestFirst, Some(second)) => {
+            let (mut n1, mut n2) = (first.len(), second.len());
+            let swap = n1 > n2;
+            if swap {
+                std::mem::swap(&mut n1, &mut n2);
+            }
+            n2 = if n1 > max_length {
+                n1
+            } else {
+                n2.min(max_length - n1).max(n1)
+            };
+            if n1 + n2 > max_length {
+                n1 = max_length / 2;
+                n2 = n1 + max_length % 2;
+            }
+            if swap {
+                std::mem::swap(&mut n1, &mut n2);
+            }
+            first.truncate(n1, side);
+            second.truncate(n2, side);
+        }
+        (TruncationStrategy::LongestFirst, None) => first.truncate(max_length, side),
+        (TruncationStrategy::OnlyFirst, _) => {
+            if first.len() > to_remove {
+                let keep = first.len() - to_remove;
+                first.truncate(keep, side);
+            }
+        }
+        (TruncationStrategy::OnlySecond, Some(second)) => {
+            if second.len() > to_remove {
+                let keep = second.len() - to_remove;
+                second.truncate(keep, side);
+            }
+        }
+        (TruncationStrategy::OnlySecond, None) => {}
+    }
+}
@@ -0,0 +1,326 @@
+use serde_json::Value;
+use unicode_general_category::{get_general_category, GeneralCategory};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use super::precompiled::Precompiled;
+use super::{bool_or, node_type, str_field};
+use crate::codec::DecodeError;
+
+// Replace 支持的模式：普通字符串，或“单个字符（类）+ 量词”形式的简单正则（如 " {2,}"）
+pub(super) enum Pattern {
+    Literal(String),
+    Repeat {
+        class: CharClass,
+        min: usize,
+        max: Option<usize>,
+    },
+}
+
+pub(super) enum CharClass {
+    Char(char),
+    Whitespace,
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Char(expected) => c == *expected,
+            CharClass::Whitespace => c.is_whitespace(),
+        }
+    }
+}
+
+impl Pattern {
+    pub(super) fn from_json(node: &Value) -> Result<Pattern, DecodeError> {
+        if let Some(literal) = node.get("String").and_then(Value::as_str) {
+            return Ok(Pattern::Literal(literal.to_string()));
+        }
+        match node.get("Regex").and_then(Value::as_str) {
+            Some(regex) => Self::parse_regex(regex),
+            None => Err(DecodeError::Invalid(
+                "pattern must be String or Regex".into(),
+            )),
+        }
+    }
+
+    fn parse_regex(regex: &str) -> Result<Pattern, DecodeError> {
+        const META: &str = "\\.^$|?*+()[]{}";
+        if !regex.chars().any(|c| META.contains(c)) {
+            return Ok(Pattern::Literal(regex.to_string()));
+        }
+        let unsupported = || DecodeError::Invalid(format!("unsupported regex pattern {:?}", regex));
+
+        let mut chars = regex.chars();
+        let class = match chars.next().ok_or_else(unsupported)? {
+            '\\' => match chars.next().ok_or_else(unsupported)? {
+                's' => CharClass::Whitespace,
+                'n' => CharClass::Char('\n'),
+                't' => CharClass::Char('\t'),
+                c if META.contains(c) => CharClass::Char(c),
+                _ => return Err(unsupported()),
+            },
+            c if META.contains(c) => return Err(unsupported()),
+            c => CharClass::Char(c),
+        };
+        let quantifier: String = chars.collect();
+        let (min, max) = match quantifier.as_str() {
+            "" => (1, Some(1)),
+            "+" => (1, None),
+            q if q.starts_with('{') && q.ends_with('}') => {
+                let body = &q[1..q.len() - 1];
+                let parse = |s: &str| s.trim().parse::<usize>().map_err(|_| unsupported());
+                match body.split_once(',') {
+                    None => {
+                        let n = parse(body)?;
+                        (n, Some(n))
+                    }
+                    Some((lo, "")) => (parse(lo)?, None),
+                    Some((lo, hi)) => (parse(lo)?, Some(parse(hi)?)),
+                }
+            }
+            _ => return Err(unsupported()),
+        };
+        if min == 0 || max.is_some_and(|max| max < min) {
+            return Err(unsupported());
+        }
+        Ok(Pattern::Repeat { class, min, max })
+    }
+
+    // 从左到右贪婪地替换所有不重叠的匹配
+    pub(super) fn replace_all(&self, text: &str, content: &str) -> String {
+        let (class, min, max) = match self {
+            Pattern::Literal(literal) if literal.is_empty() => return text.to_string(),
+            Pattern::Literal(literal) => return text.replace(literal.as_str(), content),
+            Pattern::Repeat { class, min, max } => (class, *min, max.unwrap_or(usize::MAX)),
+        };
+
+        let mut output = String::with_capacity(text.len());
+        let mut run = String::new();
+        let mut run_len = 0;
+        let flush = |run: &mut String, run_len: &mut usize, output: &mut String| {
+            let mut chars = run.chars();
+            let mut remaining = *run_len;
+            while remaining >= min {
+                let take = remaining.min(max);
+                chars.by_ref().take(take).for_each(drop);
+                output.push_str(content);
+                remaining -= take;
+            }
+            output.extend(chars);
+            run.clear();
+            *run_len = 0;
+        };
+        for c in text.chars() {
+            if class.matches(c) {
+                run.push(c);
+                run_len += 1;
+            } else {
+                flush(&mut run, &mut run_len, &mut output);
+                output.push(c);
+            }
+        }
+        flush(&mut run, &mut run_len, &mut output);
+        output
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum UnicodeForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+// BERT 的 BasicTokenizer 清洗规则，与 Hugging Face tokenizers 一致
+fn is_bert_whitespace(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r') || c.is_whitespace()
+}
+
+fn is_bert_control(c: char) -> bool {
+    !matches!(c, '\t' | '\n' | '\r')
+        && matches!(
+            get_general_category(c),
+            GeneralCategory::Control | GeneralCategory::Format | GeneralCategory::PrivateUse
+        )
+}
+
+// CJK 统一表意文字区块，不包括假名和谚文
+fn is_chinese_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x4E00..=0x9FFF
+            | 0x3400..=0x4DBF
+            | 0x20000..=0x2A6DF
+            | 0x2A700..=0x2B73F
+            | 0x2B740..=0x2B81F
+            | 0x2B920..=0x2CEAF
+            | 0xF900..=0xFAFF
+            | 0x2F800..=0x2FA1F
+    )
+}
+
+// tokenizer.json 中的 normalizer
+pub(super) enum Normalizer {
+    Sequence(Vec<Normalizer>),
+    Precompiled(Precompiled),
+    Bert {
+        clean_text: bool,
+        handle_chinese_chars: bool,
+        strip_accents: bool,
+        lowercase: bool,
+    },
+    Unicode(UnicodeForm),
+    // 去掉所有组合附加符号
+    StripAccents,
+    Replace {
+        pattern: Pattern,
+        content: String,
+    },
+    Strip {
+        left: bool,
+        right: bool,
+    },
+    Prepend(String),
+    Lowercase,
+}
+
+impl Normalizer {
+    pub(super) fn from_json(node: &Value) -> Result<Option<Normalizer>, DecodeError> {
+        if node.is_null() {
+            return Ok(None);
+        }
+        let normalizer = match node_type(node)? {
+            "Sequence" => Normalizer::Sequence(
+                node.get("normalizers")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| {
+                        DecodeError::Invalid("Sequence normalizer needs normalizers".into())
+                    })?
+                    .iter()
+                    .filter_map(|n| Normalizer::from_json(n).transpose())
+                    .collect::<Result<_, _>>()?,
+            ),
+            "Precompiled" => Normalizer::Precompiled(Precompiled::from_base64(
+                node.get("precompiled_charsmap")
+                    .and_then(Value::as_str)
+                    .unwrap_or(""),
+            )?),
+            "BertNormalizer" => {
+                let lowercase = bool_or(node, "lowercase", true);
+                Normalizer::Bert {
+                    clean_text: bool_or(node, "clean_text", true),
+                    handle_chinese_chars: bool_or(node, "handle_chinese_chars", true),
+                    // 未指定时跟随 lowercase
+                    strip_accents: bool_or(node, "strip_accents", lowercase),
+                    lowercase,
+                }
+            }
+            "NFC" => Normalizer::Unicode(UnicodeForm::Nfc),
+            "NFD" => Normalizer::Unicode(UnicodeForm::Nfd),
+            "NFKC" => Normalizer::Unicode(UnicodeForm::Nfkc),
+            "NFKD" => Normalizer::Unicode(UnicodeForm::Nfkd),
+            "StripAccents" => Normalizer::StripAccents,
+            "Replace" => Normalizer::Replace {
+                pattern: Pattern::from_json(node.get("pattern").ok_or_else(|| {
+                    DecodeError::Invalid("Replace normalizer needs a pattern".into())
+                })?)?,
+                content: str_field(node, "content")?.to_string(),
+            },
+            "Strip" => Normalizer::Strip {
+                left: bool_or(node, "strip_left", true),
+                right: bool_or(node, "strip_right", true),
+            },
+            "Prepend" => Normalizer::Prepend(str_field(node, "prepend")?.to_string()),
+            "Lowercase" => Normalizer::Lowercase,
+            other => {
+                return Err(DecodeError::Invalid(format!(
+                    "unsupported normalizer {}",
+                    other
+                )))
+            }
+        };
+        Ok(Some(normalizer))
+    }
+
+    pub(super) fn normalize(&self, text: &str) -> String {
+        match self {
+            Normalizer::Sequence(normalizers) => normalizers
+                .iter()
+                .fold(text.to_string(), |text, n| n.normalize(&text)),
+            Normalizer::Precompiled(precompiled) => precompiled.normalize(text),
+            Normalizer::Bert {
+                clean_text,
+                handle_chinese_chars,
+                strip_accents,
+                lowercase: lower,
+            } => {
+                let mut output = String::with_capacity(text.len());
+                for c in text.chars() {
+                    if *clean_text && (c == '\0' || c == '\u{fffd}' || is_bert_control(c)) {
+                        continue;
+                    }
+                    let c = if *clean_text && is_bert_whitespace(c) {
+                        ' '
+                    } else {
+                        c
+                    };
+                    if *handle_chinese_chars && is_chinese_char(c) {
+                        output.push(' ');
+                        output.push(c);
+                        output.push(' ');
+                    } else {
+                        output.push(c);
+                    }
+                }
+                if *strip_accents {
+                    output = output
+                        .nfd()
+                        .filter(|&c| get_general_category(c) != GeneralCategory::NonspacingMark)
+                        .collect();
+                }
+                if *lower {
+                    // 与 JS 的 toLowerCase 一致，词尾的 Σ 转为 ς
+                    output = output.to_lowercase();
+                }
+                output
+            }
+            Normalizer::Unicode(form) => match form {
+                UnicodeForm::Nfc => text.nfc().collect(),
+                UnicodeForm::Nfd => text.nfd().collect(),
+                UnicodeForm::Nfkc => text.nfkc().collect(),
+                UnicodeForm::Nfkd => text.nfkd().collect(),
+            },
+            Normalizer::StripAccents => text.chars().filter(|&c| !is_combining_mark(c)).collect(),
+            Normalizer::Replace { pattern, content } => pattern.replace_all(text, content),
+            Normalizer::Strip { left, right } => {
+                let text = if *left { text.trim_start() } else { text };
+                let text = if *right { text.trim_end() } else { text };
+                text.to_string()
+            }
+            Normalizer::Prepend(prefix) if !text.is_empty() => format!("{}{}", prefix, text),
+            Normalizer::Prepend(_) => String::new(),
+            Normalizer::Lowercase => text.to_lowercase(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // transformers.js 用 JS 的 toLowerCase，词尾的 Σ 转为 ς
+    #[test]
+    fn lowercase_handles_final_sigma() {
+        assert_eq!(Normalizer::Lowercase.normalize("ΣΑΣ"), "σας");
+        assert_eq!(Normalizer::Lowercase.normalize("ΟΔΥΣΣΕΥΣ ΣΟΦΟΣ"), "οδυσσευς σοφος");
+        let bert = Normalizer::Bert {
+            clean_text: true,
+            handle_chinese_chars: true,
+            strip_accents: true,
+            lowercase: true,
+        };
+        assert_eq!(bert.normalize("Ελληνικά ΣΟΦΟΣ"), "ελληνικα σοφος");
+    }
+}
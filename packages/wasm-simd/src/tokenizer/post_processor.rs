@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::{node_type, str_field, RawEncoding};
+use crate::codec::DecodeError;
+
+// 模板中的一项：特殊 token 或输入序列（A / B）
+pub(super) enum TemplatePiece {
+    Special { name: String, type_id: u32 },
+    Sequence { second: bool, type_id: u32 },
+}
+
+impl TemplatePiece {
+    fn from_json(node: &Value) -> Result<TemplatePiece, DecodeError> {
+        let invalid = || DecodeError::Invalid("invalid TemplateProcessing piece".into());
+        if let Some(special) = node.get("SpecialToken") {
+            return Ok(TemplatePiece::Special {
+                name: str_field(special, "id")?.to_string(),
+                type_id: type_id(special),
+            });
+        }
+        let sequence = node.get("Sequence").ok_or_else(invalid)?;
+        Ok(TemplatePiece::Sequence {
+            second: match str_field(sequence, "id")? {
+                "A" => false,
+                "B" => true,
+                _ => return Err(invalid()),
+            },
+            type_id: type_id(sequence),
+        })
+    }
+}
+
+fn type_id(node: &Value) -> u32 {
+    node.get("type_id").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+// ["<s>", 0] 形式的 (token, id)
+fn token_pair(node: &Value, key: &str) -> Result<(String, u32), DecodeError> {
+    let pair = node.get(key).and_then(Value::as_array);
+    match pair.map(|p| {
+        (
+            p.first().and_then(Value::as_str),
+            p.get(1).and_then(Value::as_u64),
+        )
+    }) {
+        Some((Some(token), Some(id))) => Ok((token.to_string(), id as u32)),
+        _ => Err(DecodeError::Invalid(format!(
+            "post_processor needs {} as [token, id]",
+            key
+        ))),
+    }
+}
+
+// tokenizer.json 中的 post_processor，负责添加 [CLS] / <s> 等特殊 token 并设置 token_type_ids
+pub(super) enum PostProcessor {
+    Template {
+        single: Vec<TemplatePiece>,
+        pair: Vec<TemplatePiece>,
+        special_tokens: HashMap<String, (Vec<u32>, Vec<String>)>,
+    },
+    // cls A sep / cls A sep sep B sep，type id 全为 0
+    Roberta {
+        cls: (String, u32),
+        sep: (String, u32),
+    },
+    // cls A sep / cls A sep B sep，B 部分 type id 为 1
+    Bert {
+        cls: (String, u32),
+        sep: (String, u32),
+    },
+}
+
+impl PostProcessor {
+    pub(super) fn from_json(node: &Value) -> Result<Option<PostProcessor>, DecodeError> {
+        if node.is_null() {
+            return Ok(None);
+        }
+        let processor = match node_type(node)? {
+            "TemplateProcessing" => {
+                let pieces = |key: &str| -> Result<Vec<TemplatePiece>, DecodeError> {
+                    node.get(key)
+                        .and_then(Value::as_array)
+                        .ok_or_else(|| {
+                            DecodeError::Invalid(format!("TemplateProcessing needs {}", key))
+                        })?
+                        .iter()
+                        .map(TemplatePiece::from_json)
+                        .collect()
+                };
+                let mut special_tokens = HashMap::new();
+                if let Some(map) = node.get("special_tokens").and_then(Value::as_object) {
+                    for (name, special) in map {
+                        let ids = special
+                            .get("ids")
+                            .and_then(Value::as_array)
+                            .map(|ids| {
+                                ids.iter()
+                                    .filter_map(Value::as_u64)
+                                    .map(|id| id as u32)
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        let tokens = special
+                            .get("tokens")
+                            .and_then(Value::as_array)
+                            .map(|t| {
+                                t.iter()
+                                    .filter_map(Value::as_str)
+                                    .map(str::to_string)
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        special_tokens.insert(name.clone(), (ids, tokens));
+                    }
+                }
+                PostProcessor::Template {
+                    single: pieces("single")?,
+                    pair: pieces("pair")?,
+                    special_tokens,
+                }
+            }
+            "RobertaProcessing" => PostProcessor::Roberta {
+                cls: token_pair(node, "cls")?,
+                sep: token_pair(node, "sep")?,
+            },
+            "BertProcessing" => PostProcessor::Bert {
+                cls: token_pair(node, "cls")?,
+                sep: token_pair(node, "sep")?,
+            },
+            other => {
+                return Err(DecodeError::Invalid(format!(
+                    "unsupported post_processor {}",
+                    other
+                )))
+            }
+        };
+        Ok(Some(processor))
+    }
+
+    // 添加的特殊 token 数，截断时需要预留
+    pub(super) fn added_tokens(&self, is_pair: bool) -> usize {
+        match self {
+            PostProcessor::Template {
+                single,
+                pair,
+                special_tokens,
+            } => {
+                let template = if is_pair { pair } else { single };
+                template
+                    .iter()
+                    .map(|piece| match piece {
+                        TemplatePiece::Special { name, .. } => {
+                            special_tokens.get(name).map_or(0, |(ids, _)| ids.len())
+                        }
+                        TemplatePiece::Sequence { .. } => 0,
+                    })
+                    .sum()
+            }
+            PostProcessor::Roberta { .. } => {
+                if is_pair {
+                    4
+                } else {
+                    2
+                }
+            }
+            PostProcessor::Bert { .. } => {
+                if is_pair {
+                    3
+                } else {
+                    2
+                }
+            }
+        }
+    }
+
+    // add_special 为 false 时只设置 type id，不插入特殊 token
+    pub(super) fn process(
+        &self,
+        first: RawEncoding,
+        second: Option<RawEncoding>,
+        add_special: bool,
+    ) -> RawEncoding {
+        let mut output = RawEncoding::default();
+        match self {
+            PostProcessor::Template {
+                single,
+                pair,
+                special_tokens,
+            } => {
+                let template = if second.is_some() { pair } else { single };
+                for piece in template {
+                    match piece {
+                        TemplatePiece::Special { .. } if !add_special => {}
+                        TemplatePiece::Special { name, type_id } => {
+                            if let Some((ids, tokens)) = special_tokens.get(name) {
+                                for (&id, token) in ids.iter().zip(tokens) {
+                                    output.push(id, token.clone(), *type_id);
+                                }
+                            }
+                        }
+                        TemplatePiece::Sequence {
+                            second: false,
+                            type_id,
+                        } => output.append(&first, *type_id),
+                        TemplatePiece::Sequence {
+                            second: true,
+                            type_id,
+                        } => {
+                            if let Some(second) = &second {
+                                output.append(second, *type_id);
+                            }
+                        }
+                    }
+                }
+            }
+            PostProcessor::Roberta { cls, sep } | PostProcessor::Bert { cls, sep } => {
+                let second_type = if matches!(self, PostProcessor::Bert { .. }) {
+                    1
+                } else {
+                    0
+                };
+                if !add_special {
+                    output.append(&first, 0);
+                    if let Some(second) = &second {
+                        output.append(second, second_type);
+                    }
+                    return output;
+                }
+                output.push(cls.1, cls.0.clone(), 0);
+                output.append(&first, 0);
+                output.push(sep.1, sep.0.clone(), 0);
+                if let Some(second) = &second {
+                    if second_type == 0 {
+                        output.push(sep.1, sep.0.clone(), 0);
+                    }
+                    output.append(second, second_type);
+                    output.push(sep.1, sep.0.clone(), second_type);
+                }
+            }
+        }
+        output
+    }
+}
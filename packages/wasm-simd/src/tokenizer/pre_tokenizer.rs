@@ -0,0 +1,204 @@
+use serde_json::Value;
+use unicode_general_category::{get_general_category, GeneralCategory};
+
+use super::{bool_or, node_type};
+use crate::codec::DecodeError;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum PrependScheme {
+    Always,
+    First,
+    Never,
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(
+            get_general_category(c),
+            GeneralCategory::ConnectorPunctuation
+                | GeneralCategory::DashPunctuation
+                | GeneralCategory::OpenPunctuation
+                | GeneralCategory::ClosePunctuation
+                | GeneralCategory::InitialPunctuation
+                | GeneralCategory::FinalPunctuation
+                | GeneralCategory::OtherPunctuation
+        )
+}
+
+// 正则 \w 的 Unicode 定义：Alphabetic、Mark、Nd、Pc 和 Join_Control
+fn is_word_char(c: char) -> bool {
+    c.is_alphabetic()
+        || matches!(c, '\u{200C}' | '\u{200D}')
+        || matches!(
+            get_general_category(c),
+            GeneralCategory::NonspacingMark
+                | GeneralCategory::SpacingMark
+                | GeneralCategory::EnclosingMark
+                | GeneralCategory::DecimalNumber
+                | GeneralCategory::ConnectorPunctuation
+        )
+}
+
+// tokenizer.json 中的 pre_tokenizer，把一段文本切成交给模型的“词”
+pub(super) enum PreTokenizer {
+    Sequence(Vec<PreTokenizer>),
+    Metaspace {
+        replacement: char,
+        prepend_scheme: PrependScheme,
+        split: bool,
+    },
+    WhitespaceSplit,
+    // 按空白切分后再把每个标点单独切出（BertPreTokenizer）
+    Bert,
+    // \w+|[^\w\s]+
+    Whitespace,
+}
+
+impl PreTokenizer {
+    pub(super) fn from_json(node: &Value) -> Result<Option<PreTokenizer>, DecodeError> {
+        if node.is_null() {
+            return Ok(None);
+        }
+        let pre_tokenizer = match node_type(node)? {
+            "Sequence" => PreTokenizer::Sequence(
+                node.get("pretokenizers")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| {
+                        DecodeError::Invalid("Sequence pre_tokenizer needs pretokenizers".into())
+                    })?
+                    .iter()
+                    .filter_map(|p| PreTokenizer::from_json(p).transpose())
+                    .collect::<Result<_, _>>()?,
+            ),
+            "Metaspace" => {
+                let replacement = node
+                    .get("replacement")
+                    .and_then(Value::as_str)
+                    .and_then(|s| s.chars().next())
+                    .unwrap_or('\u{2581}');
+                // 旧版本只有 add_prefix_space
+                let prepend_scheme = match node.get("prepend_scheme").and_then(Value::as_str) {
+                    Some("first") => PrependScheme::First,
+                    Some("never") => PrependScheme::Never,
+                    Some(_) => PrependScheme::Always,
+                    None if bool_or(node, "add_prefix_space", true) => PrependScheme::Always,
+                    None => PrependScheme::Never,
+                };
+                PreTokenizer::Metaspace {
+                    replacement,
+                    prepend_scheme,
+                    split: bool_or(node, "split", true),
+                }
+            }
+            "WhitespaceSplit" => PreTokenizer::WhitespaceSplit,
+            "BertPreTokenizer" => PreTokenizer::Bert,
+            "Whitespace" => PreTokenizer::Whitespace,
+            other => {
+                return Err(DecodeError::Invalid(format!(
+                    "unsupported pre_tokenizer {}",
+                    other
+                )))
+            }
+        };
+        Ok(Some(pre_tokenizer))
+    }
+
+    // is_first 表示该片段从原始输入的开头开始（Metaspace 的 first 模式需要）
+    pub(super) fn pre_tokenize(&self, text: &str, is_first: bool) -> Vec<String> {
+        let mut words = Vec::new();
+        self.split_into(text, is_first, &mut words);
+        words
+    }
+
+    fn split_into(&self, text: &str, is_first: bool, words: &mut Vec<String>) {
+        match self {
+            PreTokenizer::Sequence(pre_tokenizers) => {
+                let mut pieces = vec![text.to_string()];
+                for pre_tokenizer in pre_tokenizers {
+                    let mut next = Vec::new();
+                    for (i, piece) in pieces.iter().enumerate() {
+                        pre_tokenizer.split_into(piece, is_first && i == 0, &mut next);
+                    }
+                    pieces = next;
+                }
+                words.extend(pieces);
+            }
+            PreTokenizer::Metaspace {
+                replacement,
+                prepend_scheme,
+                split,
+            } => {
+                if text.is_empty() {
+                    return;
+                }
+                let mut replaced: String = text
+                    .chars()
+                    .map(|c| if c == ' ' { *replacement } else { c })
+                    .collect();
+                let prepend = match prepend_scheme {
+                    PrependScheme::Always => true,
+                    PrependScheme::First => is_first,
+                    PrependScheme::Never => false,
+                };
+                if prepend && !replaced.starts_with(*replacement) {
+                    replaced.insert(0, *replacement);
+                }
+                if !split {
+                    words.push(replaced);
+                    return;
+                }
+                // 每个替换符开始一个新词
+                let mut current = String::new();
+                for c in replaced.chars() {
+                    if c == *replacement && !current.is_empty() {
+                        words.push(std::mem::take(&mut current));
+                    }
+                    current.push(c);
+                }
+                if !current.is_empty() {
+                    words.push(current);
+                }
+            }
+            PreTokenizer::WhitespaceSplit => {
+                words.extend(text.split_whitespace().map(str::to_string));
+            }
+            PreTokenizer::Bert => {
+                for word in text.split_whitespace() {
+                    let mut start = 0;
+                    for (i, c) in word.char_indices().filter(|&(_, c)| is_punctuation(c)) {
+                        if start < i {
+                            words.push(word[start..i].to_string());
+                        }
+                        words.push(c.to_string());
+                        start = i + c.len_utf8();
+                    }
+                    if start < word.len() {
+                        words.push(word[start..].to_string());
+                    }
+                }
+            }
+            PreTokenizer::Whitespace => {
+                // 按字符类别（词字符 / 其他非空白字符）合并连续的同类字符
+                let mut current = String::new();
+                let mut current_is_word = false;
+                for c in text.chars() {
+                    if c.is_whitespace() {
+                        if !current.is_empty() {
+                            words.push(std::mem::take(&mut current));
+                        }
+                        continue;
+                    }
+                    let is_word = is_word_char(c);
+                    if !current.is_empty() && is_word != current_is_word {
+                        words.push(std::mem::take(&mut current));
+                    }
+                    current_is_word = is_word;
+                    current.push(c);
+                }
+                if !current.is_empty() {
+                    words.push(current);
+                }
+            }
+        }
+    }
+}
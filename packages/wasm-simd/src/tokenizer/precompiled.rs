@@ -0,0 +1,143 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::codec::DecodeError;
+
+// SentencePiece 的 precompiled_charsmap：[u32 trie 字节数][darts 双数组 trie][以 \0 分隔的替换串]。
+// trie 以 UTF-8 字节为键，值为替换串在 normalized 中的起始偏移
+pub(super) struct Precompiled {
+    trie: Vec<u32>,
+    normalized: Vec<u8>,
+}
+
+// 标准 base64 解码（忽略空白，允许省略末尾的 '='）
+fn decode_base64(input: &str) -> Result<Vec<u8>, DecodeError> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let mut output = Vec::with_capacity(input.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.bytes() {
+        if c == b'=' {
+            break;
+        }
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        let v = value(c).ok_or_else(|| {
+            DecodeError::Invalid("precompiled_charsmap is not valid base64".into())
+        })?;
+        buffer = (buffer << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(output)
+}
+
+impl Precompiled {
+    pub(super) fn from_base64(charsmap: &str) -> Result<Precompiled, DecodeError> {
+        let bytes = decode_base64(charsmap)?;
+        if bytes.is_empty() {
+            // 空 charsmap 等价于不做任何替换
+            return Ok(Precompiled {
+                trie: Vec::new(),
+                normalized: Vec::new(),
+            });
+        }
+        let invalid = || DecodeError::Invalid("precompiled_charsmap is truncated".into());
+        let trie_size =
+            u32::from_le_bytes(bytes.get(..4).ok_or_else(invalid)?.try_into().unwrap()) as usize;
+        let trie_bytes = bytes.get(4..4 + trie_size).ok_or_else(invalid)?;
+        let trie = trie_bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        Ok(Precompiled {
+            trie,
+            normalized: bytes[4 + trie_size..].to_vec(),
+        })
+    }
+
+    // darts-clone 的 common prefix search，只需要第一个（最短）匹配
+    fn first_prefix_match(&self, key: &[u8]) -> Option<usize> {
+        let unit = *self.trie.first()? as usize;
+        let mut node_pos = offset(unit);
+        for &c in key {
+            if c == 0 {
+                break;
+            }
+            node_pos ^= c as usize;
+            let unit = *self.trie.get(node_pos)? as usize;
+            if label(unit) != c as usize {
+                return None;
+            }
+            node_pos ^= offset(unit);
+            if has_leaf(unit) {
+                return Some(value(*self.trie.get(node_pos)? as usize));
+            }
+        }
+        None
+    }
+
+    fn transform(&self, chunk: &str) -> Option<&str> {
+        let start = self.first_prefix_match(chunk.as_bytes())?;
+        let rest = self.normalized.get(start..)?;
+        let len = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        std::str::from_utf8(&rest[..len]).ok()
+    }
+
+    // 与 SentencePiece 相同：短字素簇整体查表，否则逐字符查表
+    pub(super) fn normalize(&self, original: &str) -> String {
+        if self.trie.is_empty() {
+            return original.to_string();
+        }
+        let mut output = String::with_capacity(original.len());
+        for grapheme in original.graphemes(true) {
+            if grapheme.len() < 6 {
+                if let Some(normalized) = self.transform(grapheme) {
+                    output.push_str(normalized);
+                    continue;
+                }
+            }
+            for (i, c) in grapheme.char_indices() {
+                match self.transform(&grapheme[i..i + c.len_utf8()]) {
+                    Some(normalized) => output.push_str(normalized),
+                    None => output.push(c),
+                }
+            }
+        }
+        output
+    }
+}
+
+#[inline]
+fn has_leaf(unit: usize) -> bool {
+    (unit >> 8) & 1 == 1
+}
+
+#[inline]
+fn value(unit: usize) -> usize {
+    unit & ((1 << 31) - 1)
+}
+
+#[inline]
+fn label(unit: usize) -> usize {
+    unit & ((1 << 31) | 0xFF)
+}
+
+#[inline]
+fn offset(unit: usize) -> usize {
+    (unit >> 10) << ((unit & (1 << 9)) >> 6)
+}
@@ -0,0 +1,733 @@
+// 黄金测试。testdata 中的 tokenizer.json 按 multilingual-e5-small（XLM-R Unigram：
+// Precompiled 字符映射 + Metaspace + `<s> A </s></s> B </s>`）以及 BERT uncased / cased
+// （WordPiece，cased 版 max_input_chars_per_word 为 10）的结构构造，词表经过裁剪；
+// 期望的 id 和 token 由 Hugging Face tokenizers 0.21 对同一文件编码得到
+use super::*;
+
+const UNIGRAM: &str = include_str!("testdata/unigram.json");
+const WORDPIECE: &str = include_str!("testdata/wordpiece.json");
+const WORDPIECE_CASED: &str = include_str!("testdata/wordpiece_cased.json");
+
+fn load(json: &str) -> Tokenizer {
+    Tokenizer::from_json(json).unwrap_or_else(|_| panic!("tokenizer.json failed to load"))
+}
+
+fn unigram() -> Tokenizer {
+    load(UNIGRAM)
+}
+
+fn wordpiece() -> Tokenizer {
+    load(WORDPIECE)
+}
+
+fn wordpiece_cased() -> Tokenizer {
+    load(WORDPIECE_CASED)
+}
+
+fn wordpiece_cased_with_max_chars(max_chars: u64) -> Tokenizer {
+    let mut json: Value = serde_json::from_str(WORDPIECE_CASED).unwrap();
+    json["model"]["max_input_chars_per_word"] = max_chars.into();
+    load(&json.to_string())
+}
+
+fn assert_encoding(encoding: &Encoding, tokens: &[&str], ids: &[u32]) {
+    assert_eq!(encoding.tokens(), tokens);
+    assert_eq!(encoding.input_ids(), ids);
+}
+
+#[test]
+fn unigram_ascii() {
+    let tokenizer = unigram();
+    assert_encoding(
+        &tokenizer.encode("Hello world, this is a test of the tokenizer.", true),
+        &[
+            "<s>", "▁Hell", "o", "▁w", "orld,", "▁this", "▁is", "▁a", "▁test", "▁of", "▁t", "he",
+            "▁t", "oke", "nizer", ".", "</s>",
+        ],
+        &[
+            0, 624, 349, 730, 365, 726, 666, 636, 722, 693, 716, 221, 716, 356, 344, 29, 2,
+        ],
+    );
+    assert_encoding(
+        &tokenizer.encode("The quick brown fox jumps over the lazy dog!", true),
+        &[
+            "<s>", "▁The", "▁qu", "ick", "▁brow", "n", "▁f", "ox", "▁jump", "s", "▁over", "▁t",
+            "he", "▁lazy", "▁dog!", "</s>",
+        ],
+        &[
+            0, 631, 698, 228, 642, 323, 657, 371, 673, 403, 696, 716, 221, 677, 650, 2,
+        ],
+    );
+    assert_encoding(
+        &tokenizer.encode("  leading and   multiple    spaces  ", true),
+        &[
+            "<s>", "▁lead", "ing", "▁and", "▁mu", "ltipl", "e", "▁spac", "es", "▁", "</s>",
+        ],
+        &[0, 680, 240, 638, 685, 295, 167, 710, 195, 598, 2],
+    );
+}
+
+#[test]
+fn unigram_without_special_tokens() {
+    let tokenizer = unigram();
+    assert_encoding(
+        &tokenizer.encode("Hello world, this is a test of the tokenizer.", false),
+        &[
+            "▁Hell", "o", "▁w", "orld,", "▁this", "▁is", "▁a", "▁test", "▁of", "▁t", "he", "▁t",
+            "oke", "nizer", ".",
+        ],
+        &[
+            624, 349, 730, 365, 726, 666, 636, 722, 693, 716, 221, 716, 356, 344, 29,
+        ],
+    );
+}
+
+#[test]
+fn unigram_cjk() {
+    let tokenizer = unigram();
+    assert_encoding(
+        &tokenizer.encode("中文分词测试，我们来看看结果。机器学习很有趣", true),
+        &[
+            "<s>",
+            "▁中文分词",
+            "测",
+            "试,我们来",
+            "看看结果。",
+            "机器学",
+            "习很有趣",
+            "</s>",
+        ],
+        &[0, 764, 930, 969, 939, 913, 860, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode("日本語のテキストも試してみましょう", true),
+        &[
+            "<s>",
+            "▁日本語の",
+            "テキス",
+            "トも試して",
+            "みましょう",
+            "</s>",
+        ],
+        &[0, 768, 844, 851, 816, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode("한국어 문장도 포함합니다", true),
+        &["<s>", "▁한국어", "▁문장도", "▁포함합", "니다", "</s>"],
+        &[0, 782, 775, 778, 974, 2],
+    );
+}
+
+#[test]
+fn unigram_nfkc_and_emoji() {
+    let tokenizer = unigram();
+    assert_encoding(
+        &tokenizer.encode("emoji 😀🎉 and symbols © ® ™ ½", true),
+        &[
+            "<s>",
+            "▁emoj",
+            "i",
+            "▁😀🎉",
+            "▁and",
+            "▁sy",
+            "m",
+            "bols",
+            "▁©",
+            "▁®",
+            "▁TM",
+            "▁1⁄2",
+            "</s>",
+        ],
+        &[0, 654, 224, 784, 638, 713, 300, 128, 734, 735, 629, 604, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode("Ｆｕｌｌｗｉｄｔｈ ＡＢＣ １２３ ｶﾀｶﾅ", true),
+        &[
+            "<s>",
+            "▁Ful",
+            "lw",
+            "idth",
+            "▁ABC",
+            "▁123",
+            "▁カタカナ",
+            "</s>",
+        ],
+        &[0, 619, 296, 231, 616, 601, 760, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode("ﬁ ligature ﬀ and ㍿ and ①②", true),
+        &[
+            "<s>",
+            "▁f",
+            "i",
+            "▁liga",
+            "tur",
+            "e",
+            "▁f",
+            "f",
+            "▁and",
+            "▁株式会社",
+            "▁and",
+            "▁12",
+            "</s>",
+        ],
+        &[0, 657, 224, 683, 452, 167, 657, 204, 638, 772, 638, 600, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode("Ünïcödé façade naïve café résumé", true),
+        &[
+            "<s>",
+            "▁Ünïc",
+            "ödé",
+            "▁f",
+            "a",
+            "çade",
+            "▁naïv",
+            "e",
+            "▁ca",
+            "f",
+            "é",
+            "▁ré",
+            "sumé",
+            "</s>",
+        ],
+        &[
+            0, 739, 528, 657, 88, 513, 691, 167, 644, 204, 514, 703, 426, 2,
+        ],
+    );
+}
+
+#[test]
+fn unigram_unknown_chars() {
+    let tokenizer = unigram();
+    assert_encoding(
+        &tokenizer.encode("Zebra quiz jazz QZX", true),
+        &[
+            "<s>", "▁Zebr", "a", "▁qu", "iz", "▁ja", "zz", "▁QZX", "</s>",
+        ],
+        &[0, 635, 88, 698, 248, 668, 502, 627, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode("文中 x😀 q", true),
+        &["<s>", "▁", "文", "中", "▁", "x", "😀", "▁q", "</s>"],
+        &[0, 598, 894, 3, 598, 489, 3, 697, 2],
+    );
+}
+
+#[test]
+fn unigram_added_tokens() {
+    let tokenizer = unigram();
+    assert_encoding(
+        &tokenizer.encode("<mask> is a special token, so is <s> and </s>", true),
+        &[
+            "<s>", "<mask>", "▁is", "▁a", "▁spe", "c", "i", "al", "▁t", "oken,", "▁so", "▁is", "▁",
+            "<s>", "▁and", "▁", "</s>", "</s>",
+        ],
+        &[
+            0, 999, 666, 636, 711, 138, 224, 103, 716, 358, 707, 666, 598, 0, 638, 598, 2, 2,
+        ],
+    );
+    assert_encoding(
+        &tokenizer.encode("x<mask>y", true),
+        &["<s>", "▁", "x", "<mask>", "▁", "y", "</s>"],
+        &[0, 598, 489, 999, 598, 492, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode("   <mask>   z", true),
+        &["<s>", "   <mask>", "▁", "z", "</s>"],
+        &[0, 999, 598, 497, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode("the end <mask>", true),
+        &["<s>", "▁t", "he", "▁end", " <mask>", "</s>"],
+        &[0, 716, 221, 656, 999, 2],
+    );
+}
+
+#[test]
+fn unigram_pair() {
+    let tokenizer = unigram();
+    assert_encoding(
+        &tokenizer.encode_pair(
+            "Hello world, this is a test of the tokenizer.",
+            "The quick brown fox jumps over the lazy dog!",
+            true,
+        ),
+        &[
+            "<s>", "▁Hell", "o", "▁w", "orld,", "▁this", "▁is", "▁a", "▁test", "▁of", "▁t", "he",
+            "▁t", "oke", "nizer", ".", "</s>", "</s>", "▁The", "▁qu", "ick", "▁brow", "n", "▁f",
+            "ox", "▁jump", "s", "▁over", "▁t", "he", "▁lazy", "▁dog!", "</s>",
+        ],
+        &[
+            0, 624, 349, 730, 365, 726, 666, 636, 722, 693, 716, 221, 716, 356, 344, 29, 2, 2, 631,
+            698, 228, 642, 323, 657, 371, 673, 403, 696, 716, 221, 677, 650, 2,
+        ],
+    );
+}
+
+#[test]
+fn unigram_truncate_right() {
+    let mut tokenizer = unigram();
+    tokenizer.set_truncation(12, TruncationStrategy::LongestFirst, TruncationSide::Right);
+    assert_encoding(
+        &tokenizer.encode("The quick brown fox jumps over the lazy dog!", true),
+        &[
+            "<s>", "▁The", "▁qu", "ick", "▁brow", "n", "▁f", "ox", "▁jump", "s", "▁over", "</s>",
+        ],
+        &[0, 631, 698, 228, 642, 323, 657, 371, 673, 403, 696, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode_pair(
+            "Hello world, this is a test of the tokenizer.",
+            "The quick brown fox jumps over the lazy dog!",
+            true,
+        ),
+        &[
+            "<s>", "▁Hell", "o", "▁w", "orld,", "</s>", "</s>", "▁The", "▁qu", "ick", "▁brow",
+            "</s>",
+        ],
+        &[0, 624, 349, 730, 365, 2, 2, 631, 698, 228, 642, 2],
+    );
+}
+
+#[test]
+fn unigram_truncate_left() {
+    let mut tokenizer = unigram();
+    tokenizer.set_truncation(12, TruncationStrategy::LongestFirst, TruncationSide::Left);
+    assert_encoding(
+        &tokenizer.encode("The quick brown fox jumps over the lazy dog!", true),
+        &[
+            "<s>", "n", "▁f", "ox", "▁jump", "s", "▁over", "▁t", "he", "▁lazy", "▁dog!", "</s>",
+        ],
+        &[0, 323, 657, 371, 673, 403, 696, 716, 221, 677, 650, 2],
+    );
+    assert_encoding(
+        &tokenizer.encode_pair(
+            "Hello world, this is a test of the tokenizer.",
+            "The quick brown fox jumps over the lazy dog!",
+            true,
+        ),
+        &[
+            "<s>", "▁t", "oke", "nizer", ".", "</s>", "</s>", "▁t", "he", "▁lazy", "▁dog!", "</s>",
+        ],
+        &[0, 716, 356, 344, 29, 2, 2, 716, 221, 677, 650, 2],
+    );
+}
+
+#[test]
+fn unigram_truncate_only_second() {
+    let mut tokenizer = unigram();
+    tokenizer.set_truncation(24, TruncationStrategy::OnlySecond, TruncationSide::Left);
+    assert_encoding(
+        &tokenizer.encode_pair(
+            "Hello world, this is a test of the tokenizer.",
+            "The quick brown fox jumps over the lazy dog!",
+            true,
+        ),
+        &[
+            "<s>", "▁Hell", "o", "▁w", "orld,", "▁this", "▁is", "▁a", "▁test", "▁of", "▁t", "he",
+            "▁t", "oke", "nizer", ".", "</s>", "</s>", "▁over", "▁t", "he", "▁lazy", "▁dog!",
+            "</s>",
+        ],
+        &[
+            0, 624, 349, 730, 365, 726, 666, 636, 722, 693, 716, 221, 716, 356, 344, 29, 2, 2, 696,
+            716, 221, 677, 650, 2,
+        ],
+    );
+}
+
+#[test]
+fn unigram_pad_right() {
+    let mut tokenizer = unigram();
+    tokenizer.set_padding(PaddingStrategy::Fixed, 16);
+    tokenizer.set_padding_side(PaddingSide::Right);
+    assert!(tokenizer.set_pad_token("<pad>"));
+    let encoding = tokenizer.encode("the end <mask>", true);
+    assert_encoding(
+        &encoding,
+        &[
+            "<s>", "▁t", "he", "▁end", " <mask>", "</s>", "<pad>", "<pad>", "<pad>", "<pad>",
+            "<pad>", "<pad>", "<pad>", "<pad>", "<pad>", "<pad>",
+        ],
+        &[0, 716, 221, 656, 999, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+    );
+    assert_eq!(
+        encoding.attention_mask(),
+        [1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn unigram_pad_left() {
+    let mut tokenizer = unigram();
+    tokenizer.set_padding(PaddingStrategy::Fixed, 16);
+    tokenizer.set_padding_side(PaddingSide::Left);
+    assert!(tokenizer.set_pad_token("<pad>"));
+    let encoding = tokenizer.encode("the end <mask>", true);
+    assert_encoding(
+        &encoding,
+        &[
+            "<pad>", "<pad>", "<pad>", "<pad>", "<pad>", "<pad>", "<pad>", "<pad>", "<pad>",
+            "<pad>", "<s>", "▁t", "he", "▁end", " <mask>", "</s>",
+        ],
+        &[1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 716, 221, 656, 999, 2],
+    );
+    assert_eq!(
+        encoding.attention_mask(),
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1]
+    );
+}
+
+#[test]
+fn unigram_batch() {
+    let mut tokenizer = unigram();
+    tokenizer.set_padding(PaddingStrategy::Longest, 0);
+    tokenizer.set_padding_side(PaddingSide::Right);
+    assert!(tokenizer.set_pad_token("<pad>"));
+    let batch = tokenizer.encode_batch(
+        vec![
+            "Hello world, this is a test of the tokenizer.".into(),
+            "a".into(),
+            "한국어 문장도 포함합니다".into(),
+        ],
+        true,
+    );
+    assert_eq!(batch.sequence_length(), 17);
+    assert_eq!(
+        batch.input_ids(),
+        [
+            0, 624, 349, 730, 365, 726, 666, 636, 722, 693, 716, 221, 716, 356, 344, 29, 2, 0, 636,
+            2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 782, 775, 778, 974, 2, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1
+        ]
+    );
+    assert_eq!(
+        batch.attention_mask(),
+        [
+            1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0
+        ]
+    );
+}
+
+#[test]
+fn unigram_batch_pad_left_multiple() {
+    let mut tokenizer = unigram();
+    tokenizer.set_padding(PaddingStrategy::Longest, 0);
+    tokenizer.set_padding_side(PaddingSide::Left);
+    tokenizer.set_pad_to_multiple_of(8);
+    assert!(tokenizer.set_pad_token("<pad>"));
+    let batch = tokenizer.encode_batch(
+        vec![
+            "Hello world, this is a test of the tokenizer.".into(),
+            "a".into(),
+            "한국어 문장도 포함합니다".into(),
+        ],
+        true,
+    );
+    assert_eq!(batch.sequence_length(), 24);
+    assert_eq!(
+        batch.input_ids(),
+        [
+            1, 1, 1, 1, 1, 1, 1, 0, 624, 349, 730, 365, 726, 666, 636, 722, 693, 716, 221, 716,
+            356, 344, 29, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 636,
+            2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 782, 775, 778, 974, 2
+        ]
+    );
+    assert_eq!(
+        batch.attention_mask(),
+        [
+            0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1
+        ]
+    );
+}
+
+#[test]
+fn unigram_batch_truncated() {
+    let mut tokenizer = unigram();
+    tokenizer.set_truncation(8, TruncationStrategy::LongestFirst, TruncationSide::Right);
+    tokenizer.set_padding(PaddingStrategy::Longest, 0);
+    tokenizer.set_padding_side(PaddingSide::Right);
+    assert!(tokenizer.set_pad_token("<pad>"));
+    let batch = tokenizer.encode_batch(
+        vec![
+            "The quick brown fox jumps over the lazy dog!".into(),
+            "the end <mask>".into(),
+        ],
+        true,
+    );
+    assert_eq!(batch.sequence_length(), 8);
+    assert_eq!(
+        batch.input_ids(),
+        [0, 631, 698, 228, 642, 323, 657, 2, 0, 716, 221, 656, 999, 2, 1, 1]
+    );
+    assert_eq!(
+        batch.attention_mask(),
+        [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0]
+    );
+}
+
+#[test]
+fn wordpiece_basic() {
+    let tokenizer = wordpiece();
+    assert_encoding(
+        &tokenizer.encode("Hello world, this is a test of the tokenizer.", true),
+        &[
+            "[CLS]", "hello", "world", ",", "th", "##is", "i", "##s", "a", "test", "o", "##f",
+            "the", "[UNK]", ".", "[SEP]",
+        ],
+        &[
+            2, 346, 347, 16, 31, 21, 23, 20, 24, 27, 29, 30, 36, 1, 35, 3,
+        ],
+    );
+    assert_encoding(
+        &tokenizer.encode("don't stop-believing (ok)? [yes]!", true),
+        &[
+            "[CLS]", "don", "'", "t", "stop", "-", "b", "##el", "##i", "##eving", "(", "ok", ")",
+            "?", "[", "yes", "]", "!", "[SEP]",
+        ],
+        &[
+            2, 324, 325, 17, 328, 329, 40, 331, 19, 330, 333, 334, 335, 336, 337, 338, 339, 70, 3,
+        ],
+    );
+}
+
+#[test]
+fn wordpiece_accents() {
+    let tokenizer = wordpiece();
+    assert_encoding(
+        &tokenizer.encode("Ünïcödé façade naïve café résumé", true),
+        &[
+            "[CLS]", "unicod", "##e", "facade", "naive", "cafe", "re", "##sume", "[SEP]",
+        ],
+        &[2, 86, 6, 90, 95, 97, 101, 103, 3],
+    );
+    assert_encoding(
+        &tokenizer.encode("naïve ﬁnancé", true),
+        &["[CLS]", "naive", "ﬁnance", "[SEP]"],
+        &[2, 95, 317, 3],
+    );
+    assert_encoding(
+        &tokenizer.encode("İstanbul ŞEHİR", true),
+        &["[CLS]", "istanbul", "sehir", "[SEP]"],
+        &[2, 354, 356, 3],
+    );
+}
+
+#[test]
+fn wordpiece_cjk() {
+    let tokenizer = wordpiece();
+    assert_encoding(
+        &tokenizer.encode("你好，世界！Hello,World", true),
+        &[
+            "[CLS]", "你", "好", "，", "世", "界", "！", "hello", ",", "world", "[SEP]",
+        ],
+        &[2, 340, 341, 139, 342, 343, 344, 346, 16, 347, 3],
+    );
+    assert_encoding(
+        &tokenizer.encode("中文分词测试，我们来看看结果。", true),
+        &[
+            "[CLS]", "[UNK]", "文", "分", "词", "测", "试", "，", "我", "们", "来", "看", "看",
+            "结", "果", "。", "[SEP]",
+        ],
+        &[
+            2, 1, 134, 135, 136, 137, 138, 139, 140, 141, 142, 143, 143, 144, 145, 146, 3,
+        ],
+    );
+}
+
+#[test]
+fn wordpiece_control_chars() {
+    let tokenizer = wordpiece();
+    assert_encoding(
+        &tokenizer.encode("x\u{0}y�ź", true),
+        &["[CLS]", "[UNK]", "[SEP]"],
+        &[2, 1, 3],
+    );
+    assert_encoding(
+        &tokenizer.encode("tabs\tand\nnewlines\r\nmixed", true),
+        &[
+            "[CLS]", "tab", "##s", "and", "n", "##e", "##w", "##line", "##s", "mix", "##e", "##d",
+            "[SEP]",
+        ],
+        &[2, 260, 20, 76, 91, 6, 41, 263, 20, 265, 6, 13, 3],
+    );
+}
+
+#[test]
+fn wordpiece_pair() {
+    let tokenizer = wordpiece();
+    let encoding = tokenizer.encode_pair(
+        "Hello world, this is a test of the tokenizer.",
+        "The quick brown fox jumps over the lazy dog!",
+        true,
+    );
+    assert_encoding(
+        &encoding,
+        &[
+            "[CLS]", "hello", "world", ",", "th", "##is", "i", "##s", "a", "test", "o", "##f",
+            "the", "[UNK]", ".", "[SEP]", "the", "[UNK]", "brown", "fo", "##x", "ju", "##m",
+            "##ps", "over", "the", "[UNK]", "dog", "!", "[SEP]",
+        ],
+        &[
+            2, 346, 347, 16, 31, 21, 23, 20, 24, 27, 29, 30, 36, 1, 35, 3, 36, 1, 45, 49, 47, 53,
+            51, 54, 60, 36, 1, 69, 70, 3,
+        ],
+    );
+    assert_eq!(
+        encoding.token_type_ids(),
+        [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+            1
+        ]
+    );
+}
+
+#[test]
+fn wordpiece_pair_truncated() {
+    let mut tokenizer = wordpiece();
+    tokenizer.set_truncation(14, TruncationStrategy::LongestFirst, TruncationSide::Right);
+    tokenizer.set_padding(PaddingStrategy::Fixed, 16);
+    tokenizer.set_padding_side(PaddingSide::Right);
+    assert!(tokenizer.set_pad_token("[PAD]"));
+    let encoding = tokenizer.encode_pair(
+        "Hello world, this is a test of the tokenizer.",
+        "The quick brown fox jumps over the lazy dog!",
+        true,
+    );
+    assert_encoding(
+        &encoding,
+        &[
+            "[CLS]", "hello", "world", ",", "th", "##is", "i", "[SEP]", "the", "[UNK]", "brown",
+            "fo", "##x", "[SEP]", "[PAD]", "[PAD]",
+        ],
+        &[2, 346, 347, 16, 31, 21, 23, 3, 36, 1, 45, 49, 47, 3, 0, 0],
+    );
+    assert_eq!(
+        encoding.attention_mask(),
+        [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0]
+    );
+    assert_eq!(
+        encoding.token_type_ids(),
+        [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0]
+    );
+}
+
+#[test]
+fn wordpiece_pair_batch() {
+    let mut tokenizer = wordpiece();
+    tokenizer.set_padding(PaddingStrategy::Longest, 0);
+    tokenizer.set_padding_side(PaddingSide::Right);
+    assert!(tokenizer.set_pad_token("[PAD]"));
+    let batch = tokenizer.encode_pair_batch(
+        vec![
+            "Hello world, this is a test of the tokenizer.".into(),
+            "b".into(),
+        ],
+        vec![
+            "a".into(),
+            "The quick brown fox jumps over the lazy dog!".into(),
+        ],
+        true,
+    );
+    assert_eq!(batch.sequence_length(), 18);
+    assert_eq!(
+        batch.input_ids(),
+        [
+            2, 346, 347, 16, 31, 21, 23, 20, 24, 27, 29, 30, 36, 1, 35, 3, 24, 3, 2, 40, 3, 36, 1,
+            45, 49, 47, 53, 51, 54, 60, 36, 1, 69, 70, 3, 0
+        ]
+    );
+    assert_eq!(
+        batch.attention_mask(),
+        [
+            1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 0
+        ]
+    );
+    assert_eq!(
+        batch.token_type_ids(),
+        [
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1,
+            1, 1, 1, 1, 1, 1, 0
+        ]
+    );
+}
+
+#[test]
+fn wordpiece_cased_strips_accents() {
+    let tokenizer = wordpiece_cased();
+    assert_encoding(
+        &tokenizer.encode("Ünïcödé façade naïve café résumé", true),
+        &[
+            "[CLS]", "[UNK]", "f", "##a", "##c", "##ade", "n", "##a", "##i", "##ve", "c", "##af",
+            "##e", "r", "##es", "##um", "##e", "[SEP]",
+        ],
+        &[
+            2, 1, 55, 70, 47, 103, 106, 70, 22, 68, 110, 112, 6, 113, 91, 114, 6, 3,
+        ],
+    );
+    assert_encoding(
+        &tokenizer.encode("Zebra quiz jazz QZX", true),
+        &["[CLS]", "[UNK]", "[UNK]", "j", "##azz", "[UNK]", "[SEP]"],
+        &[2, 1, 1, 59, 295, 1, 3],
+    );
+    assert_encoding(
+        &tokenizer.encode("İstanbul ŞEHİR", true),
+        &["[CLS]", "[UNK]", "[UNK]", "[SEP]"],
+        &[2, 1, 1, 3],
+    );
+}
+
+// 超过 max_input_chars_per_word 的词整体记为 [UNK]，放宽上限后按 ## 续接切分
+#[test]
+fn wordpiece_long_word_is_unknown() {
+    let tokenizer = wordpiece_cased();
+    assert_encoding(
+        &tokenizer.encode("supercalifragilistic and ZWSP\u{200b}here\u{ad}soft", true),
+        &["[CLS]", "[UNK]", "and", "[UNK]", "[SEP]"],
+        &[2, 1, 84, 1, 3],
+    );
+}
+
+#[test]
+fn wordpiece_long_word_within_limit() {
+    let tokenizer = wordpiece_cased_with_max_chars(100);
+    assert_encoding(
+        &tokenizer.encode("supercalifragilistic and ZWSP\u{200b}here\u{ad}soft", true),
+        &[
+            "[CLS]", "s", "##u", "##p", "##er", "##c", "##a", "##l", "##i", "##f", "##r", "##a",
+            "##g", "##i", "##l", "##is", "##t", "##i", "##c", "and", "[UNK]", "[SEP]",
+        ],
+        &[
+            2, 89, 46, 61, 40, 47, 70, 7, 22, 34, 14, 70, 75, 22, 7, 25, 29, 22, 47, 84, 1, 3,
+        ],
+    );
+}
+
+#[test]
+fn wordpiece_cased_pair() {
+    let tokenizer = wordpiece_cased();
+    let encoding = tokenizer.encode_pair("Hello world", "Zebra quiz", true);
+    assert_encoding(
+        &encoding,
+        &[
+            "[CLS]", "Hello", "worl", "##d", "[SEP]", "[UNK]", "[UNK]", "[SEP]",
+        ],
+        &[2, 12, 17, 15, 3, 1, 1, 3],
+    );
+    assert_eq!(encoding.token_type_ids(), [0, 0, 0, 0, 0, 1, 1, 1]);
+}
+
+// transformers.js 整串转小写，词尾的 Σ 得到 ς；Hugging Face tokenizers 逐字符转换会得到 σ
+#[test]
+fn wordpiece_final_sigma() {
+    let tokenizer = wordpiece();
+    assert_encoding(
+        &tokenizer.encode("Ελληνικά ΣΟΦΟΣ σοφός", true),
+        &["[CLS]", "ελληνικα", "σοφο", "##ς", "σοφο", "##ς", "[SEP]"],
+        &[2, 305, 311, 309, 311, 309, 3],
+    );
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::Token;
+use crate::codec::DecodeError;
+
+// 未登录字符的分数惩罚，与 SentencePiece 一致
+const UNK_PENALTY: f64 = 10.0;
+
+// SentencePiece Unigram 模型：对每个预分词结果做 Viterbi，选出总对数概率最大的切分
+pub(super) struct Unigram {
+    vocab: Vec<(String, f64)>,
+    token_to_id: HashMap<String, u32>,
+    unk_id: Option<u32>,
+    byte_fallback: bool,
+    min_score: f64,
+    // 词表中最长 piece 的字节数，限定前缀匹配的范围
+    max_piece_len: usize,
+}
+
+// Viterbi 格点：以该位置结尾的最优路径
+#[derive(Clone, Copy)]
+struct BestPath {
+    id: u32,
+    score: f64,
+    starts_at: Option<usize>,
+}
+
+impl Unigram {
+    pub(super) fn from_json(node: &Value) -> Result<Unigram, DecodeError> {
+        let entries = node
+            .get("vocab")
+            .and_then(Value::as_array)
+            .ok_or_else(|| DecodeError::Invalid("Unigram model needs a vocab".into()))?;
+        let mut vocab = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let pair = entry.as_array().filter(|p| p.len() == 2);
+            let piece = pair.and_then(|p| p[0].as_str());
+            let score = pair.and_then(|p| p[1].as_f64());
+            match (piece, score) {
+                (Some(piece), Some(score)) => vocab.push((piece.to_string(), score)),
+                _ => {
+                    return Err(DecodeError::Invalid(
+                        "Unigram vocab entries must be [piece, score]".into(),
+                    ))
+                }
+            }
+        }
+        let unk_id = node
+            .get("unk_id")
+            .and_then(Value::as_u64)
+            .map(|id| id as u32);
+        if unk_id.is_some_and(|id| id as usize >= vocab.len()) {
+            return Err(DecodeError::Invalid("unk_id is outside the vocab".into()));
+        }
+
+        let mut token_to_id = HashMap::with_capacity(vocab.len());
+        for (id, (piece, _)) in vocab.iter().enumerate() {
+            token_to_id.insert(piece.clone(), id as u32);
+        }
+        Ok(Unigram {
+            min_score: vocab.iter().map(|v| v.1).fold(f64::INFINITY, f64::min),
+            max_piece_len: vocab.iter().map(|v| v.0.len()).max().unwrap_or(0),
+            token_to_id,
+            unk_id,
+            byte_fallback: node
+                .get("byte_fallback")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            vocab,
+        })
+    }
+
+    pub(super) fn vocab_size(&self) -> usize {
+        self.vocab.len()
+    }
+
+    pub(super) fn token_to_id(&self, token: &str) -> Option<u32> {
+        self.token_to_id.get(token).copied()
+    }
+
+    pub(super) fn id_to_token(&self, id: u32) -> Option<&str> {
+        self.vocab.get(id as usize).map(|v| v.0.as_str())
+    }
+
+    pub(super) fn tokenize(&self, word: &str, tokens: &mut Vec<Token>) {
+        for piece in self.encode(word) {
+            if let Some(id) = self.token_to_id(&piece) {
+                tokens.push(Token { id, text: piece });
+                continue;
+            }
+            if self.byte_fallback {
+                let bytes: Option<Vec<Token>> = piece
+                    .bytes()
+                    .map(|b| {
+                        let text = format!("<0x{:02X}>", b);
+                        self.token_to_id(&text).map(|id| Token { id, text })
+                    })
+                    .collect();
+                if let Some(bytes) = bytes {
+                    tokens.extend(bytes);
+                    continue;
+                }
+            }
+            if let Some(id) = self.unk_id {
+                tokens.push(Token { id, text: piece });
+            }
+        }
+    }
+
+    // Viterbi 切分，连续的未登录字符合并为一个 piece
+    fn encode(&self, sentence: &str) -> Vec<String> {
+        if sentence.is_empty() {
+            return Vec::new();
+        }
+        let size = sentence.len();
+        let unk_score = self.min_score - UNK_PENALTY;
+        let mut best = vec![
+            BestPath {
+                id: 0,
+                score: 0.0,
+                starts_at: None,
+            };
+            size + 1
+        ];
+
+        for (starts_at, c) in sentence.char_indices() {
+            let score_till_here = best[starts_at].score;
+            let char_len = c.len_utf8();
+            let mut has_single_node = false;
+
+            let limit = (starts_at + self.max_piece_len).min(size);
+            for end in (starts_at + 1..=limit).filter(|&e| sentence.is_char_boundary(e)) {
+                let id = match self.token_to_id.get(&sentence[starts_at..end]) {
+                    Some(&id) => id,
+                    None => continue,
+                };
+                let candidate = score_till_here + self.vocab[id as usize].1;
+                let node = &mut best[end];
+                if node.starts_at.is_none() || candidate > node.score {
+                    *node = BestPath {
+                        id,
+                        score: candidate,
+                        starts_at: Some(starts_at),
+                    };
+                }
+                if end - starts_at == char_len {
+                    has_single_node = true;
+                }
+            }
+
+            if !has_single_node {
+                if let Some(unk_id) = self.unk_id {
+                    let candidate = score_till_here + unk_score;
+                    let node = &mut best[starts_at + char_len];
+                    if node.starts_at.is_none() || candidate > node.score {
+                        *node = BestPath {
+                            id: unk_id,
+                            score: candidate,
+                            starts_at: Some(starts_at),
+                        };
+                    }
+                }
+            }
+        }
+
+        let mut pieces = Vec::new();
+        let mut unknown: Vec<&str> = Vec::new();
+        let mut ends_at = size;
+        while ends_at > 0 {
+            let node = best[ends_at];
+            let starts_at = match node.starts_at {
+                Some(starts_at) => starts_at,
+                // 没有 unk_id 时无法覆盖的字符，跳过该字符
+                None => {
+                    let mut prev = ends_at - 1;
+                    while !sentence.is_char_boundary(prev) {
+                        prev -= 1;
+                    }
+                    ends_at = prev;
+                    continue;
+                }
+            };
+            let piece = &sentence[starts_at..ends_at];
+            if Some(node.id) == self.unk_id {
+                unknown.push(piece);
+            } else {
+                if !unknown.is_empty() {
+                    unknown.reverse();
+                    pieces.push(unknown.concat());
+                    unknown.clear();
+                }
+                pieces.push(piece.to_string());
+            }
+            ends_at = starts_at;
+        }
+        if !unknown.is_empty() {
+            unknown.reverse();
+            pieces.push(unknown.concat());
+        }
+        pieces.reverse();
+        pieces
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use super::{str_field, Token};
+use crate::codec::DecodeError;
+
+// BERT 系列的 WordPiece 模型：对每个词做最长前缀贪心匹配，后续片段带 ## 前缀
+pub(super) struct WordPiece {
+    vocab: Vec<String>,
+    token_to_id: HashMap<String, u32>,
+    unk_token: String,
+    continuing_subword_prefix: String,
+    max_input_chars_per_word: usize,
+}
+
+impl WordPiece {
+    pub(super) fn from_json(node: &Value) -> Result<WordPiece, DecodeError> {
+        let entries = node
+            .get("vocab")
+            .and_then(Value::as_object)
+            .ok_or_else(|| DecodeError::Invalid("WordPiece model needs a vocab".into()))?;
+        let mut token_to_id = HashMap::with_capacity(entries.len());
+        for (token, id) in entries {
+            let id = id.as_u64().ok_or_else(|| {
+                DecodeError::Invalid("WordPiece vocab ids must be integers".into())
+            })?;
+            token_to_id.insert(token.clone(), id as u32);
+        }
+        let size = token_to_id.values().max().map_or(0, |&id| id as usize + 1);
+        let mut vocab = vec![String::new(); size];
+        for (token, &id) in &token_to_id {
+            vocab[id as usize] = token.clone();
+        }
+
+        Ok(WordPiece {
+            vocab,
+            token_to_id,
+            unk_token: str_field(node, "unk_token").unwrap_or("[UNK]").to_string(),
+            continuing_subword_prefix: str_field(node, "continuing_subword_prefix")
+                .unwrap_or("##")
+                .to_string(),
+            max_input_chars_per_word: node
+                .get("max_input_chars_per_word")
+                .and_then(Value::as_u64)
+                .unwrap_or(100) as usize,
+        })
+    }
+
+    pub(super) fn vocab_size(&self) -> usize {
+        self.token_to_id.len()
+    }
+
+    pub(super) fn token_to_id(&self, token: &str) -> Option<u32> {
+        self.token_to_id.get(token).copied()
+    }
+
+    pub(super) fn id_to_token(&self, id: u32) -> Option<&str> {
+        self.vocab
+            .get(id as usize)
+            .map(String::as_str)
+            .filter(|t| !t.is_empty())
+    }
+
+    // 任一位置无法匹配时整个词记为 unk
+    pub(super) fn tokenize(&self, word: &str, tokens: &mut Vec<Token>) {
+        if word.chars().count() > self.max_input_chars_per_word {
+            self.push_unk(tokens);
+            return;
+        }
+
+        let first = tokens.len();
+        let mut start = 0;
+        while start < word.len() {
+            let mut end = word.len();
+            let mut matched = None;
+            while start < end {
+                let piece = if start > 0 {
+                    format!("{}{}", self.continuing_subword_prefix, &word[start..end])
+                } else {
+                    word[start..end].to_string()
+                };
+                if let Some(id) = self.token_to_id(&piece) {
+                    matched = Some(Token { id, text: piece });
+                    break;
+                }
+                end -= word[..end].chars().next_back().map_or(1, char::len_utf8);
+            }
+            match matched {
+                Some(token) => tokens.push(token),
+                None => {
+                    tokens.truncate(first);
+                    self.push_unk(tokens);
+                    return;
+                }
+            }
+            start = end;
+        }
+    }
+
+    fn push_unk(&self, tokens: &mut Vec<Token>) {
+        if let Some(id) = self.token_to_id(&self.unk_token) {
+            tokens.push(Token {
+                id,
+                text: self.unk_token.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::metric::{for_each_score, Metric};
+
+// Top-K 结果：按从好到差排序的索引（或 id）与分数
+#[wasm_bindgen]
+#[derive(Clone, Debug, Default)]
+pub struct TopKResults {
+    indices: Vec<u32>,
+    scores: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl TopKResults {
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn scores(&self) -> Vec<f32> {
+        self.scores.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.indices.len()
+    }
+}
+
+impl TopKResults {
+    // 由已排好序的结果直接构造
+    pub(crate) fn from_parts(indices: Vec<u32>, scores: Vec<f32>) -> TopKResults {
+        debug_assert_eq!(indices.len(), scores.len());
+        TopKResults { indices, scores }
+    }
+}
+
+// 堆元素：key 越大越好，key 相同时索引越小越好
+#[derive(Clone, Copy)]
+struct Candidate {
+    key: f32,
+    index: u32,
+    score: f32,
+}
+
+impl Candidate {
+    #[inline]
+    fn better_than(&self, other: &Candidate) -> bool {
+        self.cmp_quality(other) == Ordering::Greater
+    }
+
+    #[inline]
+    fn cmp_quality(&self, other: &Candidate) -> Ordering {
+        self.key
+            .total_cmp(&other.key)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    // 反转质量顺序，使 BinaryHeap 堆顶为当前最差的候选
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cmp_quality(self)
+    }
+}
+
+// 有界堆：只保留最好的 k 个候选
+pub(crate) struct TopKHeap {
+    k: usize,
+    higher_is_better: bool,
+    heap: BinaryHeap<Candidate>,
+}
+
+impl TopKHeap {
+    pub(crate) fn new(k: usize, higher_is_better: bool) -> Self {
+        TopKHeap {
+            k,
+            higher_is_better,
+            heap: BinaryHeap::with_capacity(k.saturating_add(1).min(4096)),
+        }
+    }
+
+    pub(crate) fn for_metric(k: usize, metric: Metric) -> Self {
+        Self::new(k, metric.higher_is_better())
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, index: u32, score: f32) {
+        if self.k == 0 || score.is_nan() {
+            return;
+        }
+        let key = if self.higher_is_better { score } else { -score };
+        let candidate = Candidate { key, index, score };
+
+        if self.heap.len() < self.k {
+            self.heap.push(candidate);
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if candidate.better_than(&worst) {
+                *worst = candidate;
+            }
+        }
+    }
+
+    pub(crate) fn into_results(self) -> TopKResults {
+        // into_sorted_vec 按 Ord 升序，即质量从好到差
+        let sorted = self.heap.into_sorted_vec();
+        TopKResults {
+            indices: sorted.iter().map(|c| c.index).collect(),
+            scores: sorted.iter().map(|c| c.score).collect(),
+        }
+    }
+}
+
+// 查询向量对打包向量做打分并只保留前 k 个
+pub(crate) fn batch_top_k(
+    metric: Metric,
+    vectors: &[f32],
+    query: &[f32],
+    vector_dim: usize,
+    k: usize,
+) -> TopKResults {
+    let mut heap = TopKHeap::for_metric(k, metric);
+    for_each_score(metric, vectors, query, vector_dim, |i, score| {
+        heap.push(i as u32, score)
+    });
+    heap.into_results()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metric::batch_scores;
+    use crate::rng::{random_vectors, Rng};
+
+    // 参照实现：全排序后取前 k 个，同分按索引升序
+    fn full_sort(scores: &[f32], k: usize, higher_is_better: bool) -> Vec<(u32, f32)> {
+        let mut sorted: Vec<(u32, f32)> = scores
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !s.is_nan())
+            .map(|(i, &s)| (i as u32, s))
+            .collect();
+        sorted.sort_by(|a, b| {
+            let order = if higher_is_better {
+                b.1.total_cmp(&a.1)
+            } else {
+                a.1.total_cmp(&b.1)
+            };
+            order.then(a.0.cmp(&b.0))
+        });
+        sorted.truncate(k);
+        sorted
+    }
+
+    fn top_k(scores: &[f32], k: usize, higher_is_better: bool) -> Vec<(u32, f32)> {
+        let mut heap = TopKHeap::new(k, higher_is_better);
+        for (i, &score) in scores.iter().enumerate() {
+            heap.push(i as u32, score);
+        }
+        let results = heap.into_results();
+        results
+            .indices()
+            .into_iter()
+            .zip(results.scores())
+            .collect()
+    }
+
+    #[test]
+    fn matches_full_sort_with_ties() {
+        // 只有 7 种取值，大量同分
+        let mut rng = Rng::new(9);
+        let scores: Vec<f32> = (0..200).map(|_| rng.below(7) as f32 - 3.0).collect();
+        for higher_is_better in [true, false] {
+            for k in [1, 2, 5, 17, 199, 200] {
+                assert_eq!(
+                    top_k(&scores, k, higher_is_better),
+                    full_sort(&scores, k, higher_is_better),
+                    "k {} higher_is_better {}",
+                    k,
+                    higher_is_better
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn k_larger_than_input_returns_everything_sorted() {
+        let scores = [0.5, -1.0, 2.0, 0.5, f32::INFINITY, f32::NEG_INFINITY];
+        for higher_is_better in [true, false] {
+            let results = top_k(&scores, 100, higher_is_better);
+            assert_eq!(results.len(), scores.len());
+            assert_eq!(results, full_sort(&scores, 100, higher_is_better));
+        }
+        assert_eq!(
+            top_k(&scores, 100, true)
+                .iter()
+                .map(|r| r.0)
+                .collect::<Vec<_>>(),
+            [4, 2, 0, 3, 1, 5]
+        );
+    }
+
+    #[test]
+    fn nan_and_zero_k_are_ignored() {
+        let scores = [1.0, f32::NAN, 3.0, f32::NAN, 2.0];
+        assert_eq!(top_k(&scores, 10, true), [(2, 3.0), (4, 2.0), (0, 1.0)]);
+        assert_eq!(top_k(&scores, 2, false), [(0, 1.0), (4, 2.0)]);
+        assert!(top_k(&scores, 0, true).is_empty());
+        assert!(top_k(&[], 3, true).is_empty());
+    }
+
+    #[test]
+    fn batch_top_k_matches_sorted_scores() {
+        let dim = 7;
+        let vectors = random_vectors(31, 50, dim);
+        let query = random_vectors(32, 1, dim);
+        for metric in [Metric::Dot, Metric::Cosine, Metric::L2Squared, Metric::L1] {
+            let scores = batch_scores(metric, &vectors, &query, dim);
+            let results = batch_top_k(metric, &vectors, &query, dim, 10);
+            let expected = full_sort(&scores, 10, metric.higher_is_better());
+            assert_eq!(
+                results
+                    .indices()
+                    .into_iter()
+                    .zip(results.scores())
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+}
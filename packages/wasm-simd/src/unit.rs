@@ -0,0 +1,54 @@
+use crate::kernels::{compute_norm_squared_simd, scale_in_place_simd};
+use crate::metric::packed_count;
+
+// 判断是否为单位向量时允许的 |‖v‖² - 1| 误差
+pub(crate) const UNIT_NORM_TOLERANCE: f32 = 1e-3;
+
+// 原地 L2 归一化，零向量保持不变
+#[inline]
+pub(crate) fn normalize_in_place(vec: &mut [f32]) {
+    let norm_sq = compute_norm_squared_simd(vec);
+    if norm_sq == 0.0 {
+        return;
+    }
+    scale_in_place_simd(vec, 1.0 / norm_sq.sqrt());
+}
+
+#[inline]
+pub(crate) fn is_unit(vec: &[f32], tolerance: f32) -> bool {
+    (compute_norm_squared_simd(vec) - 1.0).abs() <= tolerance
+}
+
+// 找出打包向量中不是单位长度的向量索引
+pub(crate) fn non_unit_indices(vectors: &[f32], vector_dim: usize, tolerance: f32) -> Vec<u32> {
+    if packed_count(vectors, vector_dim).is_none() {
+        return Vec::new();
+    }
+    vectors
+        .chunks_exact(vector_dim)
+        .enumerate()
+        .filter(|(_, v)| !is_unit(v, tolerance))
+        .map(|(i, _)| i as u32)
+        .collect()
+}
+
+// 调试构建下检查点积快速路径的输入是否真的是单位向量
+#[cfg(all(debug_assertions, target_arch = "wasm32"))]
+pub(crate) fn debug_check_unit_vectors(context: &str, vectors: &[f32], vector_dim: usize) {
+    let offenders = non_unit_indices(vectors, vector_dim, UNIT_NORM_TOLERANCE);
+    if !offenders.is_empty() {
+        web_sys::console::warn_1(
+            &format!(
+                "{}: {} vector(s) are not unit length, dot-product scores will not equal cosine similarity (first: {:?})",
+                context,
+                offenders.len(),
+                &offenders[..offenders.len().min(8)]
+            )
+            .into(),
+        );
+    }
+}
+
+#[cfg(not(all(debug_assertions, target_arch = "wasm32")))]
+#[inline(always)]
+pub(crate) fn debug_check_unit_vectors(_context: &str, _vectors: &[f32], _vector_dim: usize) {}